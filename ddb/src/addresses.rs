@@ -3,8 +3,8 @@
 //! Returns all addresses for users as paragraph entities.
 //! Each user can have multiple addresses with primary/mailing flags.
 
-use crate::Result;
-use sqlx::{MySqlPool, mysql::MySql};
+use crate::{Db, Result};
+use sqlx::mysql::MySql;
 
 /// User address record from Drupal database
 /// Each row represents an address paragraph entity
@@ -16,17 +16,17 @@ pub struct Address {
     pub user_uid: u64,
     /// Ordering within user's addresses (0 = first)
     pub delta: u32,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub street_address: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub street_address_2: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub city: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub state: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub zip_code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub country: Option<String>,
     /// Is this the user's primary address?
     pub is_primary: bool,
@@ -72,23 +72,27 @@ fn fetch_address_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
 }
 
 /// Fetch all addresses from Drupal
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Address>> {
-    fetch_address_query()
-        .push(" ORDER BY ua.entity_id, ua.delta")
-        .build_query_as::<Address>()
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn all(db: &Db) -> Result<Vec<Address>> {
+    db.fetch("addresses", async || {
+        fetch_address_query()
+            .push(" ORDER BY ua.entity_id, ua.delta")
+            .build_query_as::<Address>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
 /// Fetch addresses for a specific user
-pub async fn by_user_id(pool: &MySqlPool, user_uid: u64) -> Result<Vec<Address>> {
-    fetch_address_query()
-        .push(" AND ua.entity_id = ")
-        .push_bind(user_uid)
-        .push(" ORDER BY ua.delta")
-        .build_query_as::<Address>()
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn by_user_id(db: &Db, user_uid: u64) -> Result<Vec<Address>> {
+    db.fetch("addresses", async || {
+        fetch_address_query()
+            .push(" AND ua.entity_id = ")
+            .push_bind(user_uid)
+            .push(" ORDER BY ua.delta")
+            .build_query_as::<Address>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
@@ -3,9 +3,9 @@
 //! Returns all ownership records (paragraphs) with full date tracking.
 //! Each ownership paragraph links a user to an airstream with join/leave dates.
 
-use crate::Result;
+use crate::{Db, Result};
 use chrono::NaiveDate;
-use sqlx::{MySqlPool, mysql::MySql};
+use sqlx::mysql::MySql;
 
 /// Airstream ownership record from Drupal database
 /// Each row represents an ownership period (paragraph entity)
@@ -22,20 +22,20 @@ pub struct Airstream {
     /// Ownership start date (required in Drupal)
     pub join_date: NaiveDate,
     /// Ownership end date (NULL = current owner)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub leave_date: Option<NaiveDate>,
     // Vehicle details
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub vin: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub model: Option<String>,
     /// Type: "Trailer", "Class A", "Class B"
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub airstream_type: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub year: Option<i32>,
     /// Length in feet (MySQL DECIMAL maps to String for precision)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub length: Option<String>,
 }
 
@@ -78,22 +78,27 @@ fn fetch_airstream_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
 }
 
 /// Fetch all airstream ownership records from Drupal
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Airstream>> {
-    let airstreams = fetch_airstream_query()
-        .build_query_as::<Airstream>()
-        .fetch_all(pool)
-        .await?;
-
-    Ok(airstreams)
+pub async fn all(db: &Db) -> Result<Vec<Airstream>> {
+    db.fetch("airstreams", async || {
+        fetch_airstream_query()
+            .build_query_as::<Airstream>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
 /// Fetch airstream ownership records for a specific user
-pub async fn by_user_id(pool: &MySqlPool, user_id: u64) -> Result<Vec<Airstream>> {
-    let airstreams = fetch_airstream_query()
-        .push(" AND m.field_member_target_id = ")
-        .push_bind(user_id)
-        .build_query_as::<Airstream>()
-        .fetch_all(pool)
+pub async fn by_user_id(db: &Db, user_id: u64) -> Result<Vec<Airstream>> {
+    let airstreams = db
+        .fetch("airstreams", async || {
+            fetch_airstream_query()
+                .push(" AND m.field_member_target_id = ")
+                .push_bind(user_id)
+                .build_query_as::<Airstream>()
+                .fetch_all(db.pool())
+                .await
+        })
         .await?;
 
     Ok(airstreams)
@@ -2,8 +2,8 @@
 //!
 //! Uses `ssp_complete_brn` joined with `node_field_data` for BRN number and acquire date.
 
-use crate::Result;
-use sqlx::MySqlPool;
+use crate::{Db, Result};
+use std::collections::HashSet;
 
 /// A single BRN record from Drupal (one row per BRN)
 #[derive(Debug, serde::Serialize, Clone)]
@@ -24,31 +24,96 @@ struct BrnRow {
     acquire_date: Option<i64>,
 }
 
+const FETCH_BRNS_QUERY: &str = r#"
+    SELECT
+        b.user_id,
+        n.title AS brn_number,
+        b.acquire_date
+    FROM ssp_complete_brn b
+    JOIN node_field_data n ON n.nid = b.brn_id
+    WHERE b.user_id IS NOT NULL
+"#;
+
 /// Fetch all assigned BRNs from Drupal with acquire dates
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Brn>> {
-    let rows: Vec<BrnRow> = sqlx::query_as(
-        r#"
-        SELECT
-            b.user_id,
-            n.title AS brn_number,
-            b.acquire_date
-        FROM ssp_complete_brn b
-        JOIN node_field_data n ON n.nid = b.brn_id
-        WHERE b.user_id IS NOT NULL
-        "#,
-    )
-    .fetch_all(pool)
-    .await?;
+pub async fn all(db: &Db) -> Result<Vec<Brn>> {
+    let rows: Vec<BrnRow> = db
+        .fetch("brns", async || {
+            sqlx::query_as(FETCH_BRNS_QUERY).fetch_all(db.pool()).await
+        })
+        .await?;
+
+    Ok(from_rows(rows))
+}
+
+/// Fetch the BRNs assigned to one user
+pub async fn by_user(db: &Db, user_uid: u64) -> Result<Vec<Brn>> {
+    let rows: Vec<BrnRow> = db
+        .fetch("brns", async || {
+            sqlx::QueryBuilder::new(FETCH_BRNS_QUERY)
+                .push(" AND b.user_id = ")
+                .push_bind(user_uid)
+                .build_query_as()
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+
+    Ok(from_rows(rows))
+}
 
+fn from_rows(rows: Vec<BrnRow>) -> Vec<Brn> {
     let brns = rows
         .into_iter()
         .filter(|row| row.user_id > 0)
         .map(|row| Brn {
             user_uid: row.user_id as u64,
-            number: row.brn_number.trim().to_string(),
+            number: normalize(&row.brn_number),
             acquire_date: row.acquire_date,
         })
         .collect();
 
-    Ok(brns)
+    dedupe(brns)
+}
+
+/// Normalize a BRN number as stored in Drupal (e.g. " 07569" -> "07569")
+pub fn normalize(number: &str) -> String {
+    number.trim().to_string()
+}
+
+/// Remove duplicate `(user_uid, number)` pairs, keeping the first occurrence.
+///
+/// The same number may still appear for different users.
+pub fn dedupe(brns: Vec<Brn>) -> Vec<Brn> {
+    let mut seen = HashSet::new();
+    brns.into_iter()
+        .filter(|brn| seen.insert((brn.user_uid, normalize(&brn.number))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brn(user_uid: u64, number: &str) -> Brn {
+        Brn {
+            user_uid,
+            number: number.to_string(),
+            acquire_date: None,
+        }
+    }
+
+    #[test]
+    fn test_dedupe_per_user() {
+        let brns = dedupe(vec![
+            brn(1, "07569"),
+            brn(1, " 07569"),
+            brn(2, "07569"),
+            brn(1, "123"),
+        ]);
+        let pairs: Vec<_> = brns
+            .iter()
+            .map(|b| (b.user_uid, b.number.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![(1, "07569"), (2, "07569"), (1, "123")]);
+    }
 }
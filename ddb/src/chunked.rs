@@ -0,0 +1,58 @@
+//! Splitting long `IN (...)` lists over several queries.
+//!
+//! Every bound value is a placeholder, and MySQL rejects a prepared statement
+//! with more than 65,535 of them; long lists also run into
+//! `max_allowed_packet`. Lookups by a caller-supplied list of IDs therefore
+//! go through [`chunked_in`], which runs one query per chunk and concatenates
+//! the rows.
+//!
+//! The chunk size is carried by the [`crate::Db`] handle
+//! ([`crate::Db::with_in_chunk_size`], the CLI's `--in-chunk-size`),
+//! defaulting to [`DEFAULT_IN_CHUNK_SIZE`].
+
+use crate::Result;
+use std::num::NonZeroUsize;
+
+/// Values bound per `IN (...)` list unless the [`crate::Db`] says otherwise
+pub(crate) const DEFAULT_IN_CHUNK_SIZE: NonZeroUsize = NonZeroUsize::new(1000).unwrap();
+
+/// Run `query` once per chunk of at most `chunk_size` items, in order, and
+/// concatenate the rows. No query runs for an empty `items`.
+///
+/// Rows are not deduplicated, so `query` should select on a key that can only
+/// match within its own chunk (e.g. `uid IN (...)` with unique uids).
+pub(crate) async fn chunked_in<'a, T, R>(
+    items: &'a [T],
+    chunk_size: usize,
+    mut query: impl AsyncFnMut(&'a [T]) -> Result<Vec<R>>,
+) -> Result<Vec<R>> {
+    let mut rows = Vec::new();
+    for chunk in items.chunks(chunk_size.max(1)) {
+        rows.extend(query(chunk).await?);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunked_in() {
+        let ids: Vec<u64> = (1..=7).collect();
+        let mut chunk_lens = Vec::new();
+        let rows = chunked_in(&ids, 3, async |chunk: &[u64]| {
+            chunk_lens.push(chunk.len());
+            Ok(chunk.iter().map(|id| id * 10).collect())
+        })
+        .await
+        .unwrap();
+        assert_eq!(rows, vec![10, 20, 30, 40, 50, 60, 70]);
+        assert_eq!(chunk_lens, vec![3, 3, 1]);
+
+        let rows: Vec<u64> = chunked_in(&[] as &[u64], 3, async |_: &[u64]| unreachable!())
+            .await
+            .unwrap();
+        assert!(rows.is_empty());
+    }
+}
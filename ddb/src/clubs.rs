@@ -1,46 +1,120 @@
-use crate::{Error, Result};
-use futures::TryFutureExt;
-use sqlx::{MySql, MySqlPool};
-
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Club>> {
-    sqlx::query_as::<_, Club>(FETCH_CLUBS_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
+use crate::{Db, Result, events::Event, regions::Region};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use sqlx::MySql;
+use std::collections::{BTreeMap, HashMap};
+
+/// Fetch all clubs, logging any without a region assignment
+pub async fn all(db: &Db) -> Result<Vec<Club>> {
+    let clubs = db
+        .fetch("clubs", async || {
+            sqlx::query_as::<_, Club>(FETCH_CLUBS_QUERY)
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+    for club in clubs.iter().filter(|club| club.region.is_none()) {
+        log::warn!("club {} ({}) has no region", club.uid, club.name);
+    }
+    Ok(clubs)
+}
+
+/// Number of clubs [`all`] would return, counted on the ssp_club nodes
+/// alone without the export's joins
+pub async fn count(db: &Db) -> Result<u64> {
+    db.fetch("clubs", async || {
+        sqlx::query_scalar(
+            "SELECT CAST(COUNT(*) AS UNSIGNED) FROM node_field_data nd \
+             WHERE nd.type = 'ssp_club'",
+        )
+        .fetch_one(db.pool())
         .await
+    })
+    .await
 }
 
-pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<Club>> {
-    let club = fetch_clubs_query()
-        .push(" AND nd.nid = ")
-        .push_bind(uid)
-        .build_query_as::<Club>()
-        .fetch_optional(pool)
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<Club>> {
+    let club = db
+        .fetch("clubs", async || {
+            fetch_clubs_query()
+                .push(" AND nd.nid = ")
+                .push_bind(uid)
+                .build_query_as::<Club>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(club)
 }
 
-pub async fn by_number(pool: &MySqlPool, number: i32) -> Result<Option<Club>> {
-    let club = fetch_clubs_query()
-        .push(" AND cn.field_club_number_value = ")
-        .push_bind(number)
-        .build_query_as::<Club>()
-        .fetch_optional(pool)
+pub async fn by_number(db: &Db, number: i32) -> Result<Option<Club>> {
+    let club = db
+        .fetch("clubs", async || {
+            fetch_clubs_query()
+                .push(" AND cn.field_club_number_value = ")
+                .push_bind(number)
+                .build_query_as::<Club>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(club)
 }
 
-pub async fn search_by_name(pool: &MySqlPool, pattern: &str) -> Result<Vec<Club>> {
-    let like = format!("%{pattern}%");
-    fetch_clubs_query()
-        .push(" AND LOWER(nd.title) LIKE LOWER(")
-        .push_bind(like)
-        .push(")")
-        .build_query_as::<Club>()
-        .fetch_all(pool)
-        .map_err(Error::from)
+/// Fetch the clubs with any of the given club numbers, one query per
+/// [`Db::in_chunk_size`] numbers
+pub async fn by_numbers(db: &Db, numbers: &[i64]) -> Result<Vec<Club>> {
+    crate::chunked::chunked_in(numbers, db.in_chunk_size(), async |chunk| {
+        db.fetch("clubs", async || {
+            let mut query = fetch_clubs_query();
+            query.push(" AND cn.field_club_number_value IN (");
+            let mut separated = query.separated(", ");
+            for number in chunk {
+                separated.push_bind(*number);
+            }
+            query
+                .push(")")
+                .build_query_as::<Club>()
+                .fetch_all(db.pool())
+                .await
+        })
         .await
+    })
+    .await
+}
+
+/// Clubs whose ssp_club node was saved at or after a unix timestamp, in the
+/// same shape as [`all`], for incremental syncs.
+///
+/// Only the club node's own `changed` is compared; edits to a club's
+/// microsite pages don't touch it and show up in the microsite `changed`
+/// timestamps instead.
+pub async fn changed_since(db: &Db, since: i64) -> Result<Vec<Club>> {
+    db.fetch("clubs", async || {
+        fetch_clubs_query()
+            .push(" AND nd.changed >= ")
+            .push_bind(since)
+            .push(" ORDER BY nd.changed, nd.nid")
+            .build_query_as::<Club>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+pub async fn search_by_name(db: &Db, pattern: &str) -> Result<Vec<Club>> {
+    let like = format!("%{pattern}%");
+    db.fetch("clubs", async || {
+        fetch_clubs_query()
+            .push(" AND LOWER(nd.title) LIKE LOWER(")
+            .push_bind(&like)
+            .push(")")
+            .build_query_as::<Club>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
 const FETCH_CLUBS_QUERY: &str = r#"
@@ -49,10 +123,14 @@ const FETCH_CLUBS_QUERY: &str = r#"
             cn.field_club_number_value as number,
             nd.title as name,
             nr.field_region_target_id as region,
+            region.title as region_name,
+            CAST(fd.field_founded_value AS DATE) as founded,
             nd.status as active
         FROM node_field_data nd
         LEFT JOIN node__field_club_number cn ON cn.entity_id = nd.nid
         LEFT JOIN node__field_region nr ON nr.entity_id = nd.nid
+        LEFT JOIN node_field_data region ON region.nid = nr.field_region_target_id
+        LEFT JOIN node__field_founded fd ON fd.entity_id = nd.nid AND fd.deleted = 0
         WHERE nd.type = 'ssp_club'
     "#;
 
@@ -63,15 +141,449 @@ fn fetch_clubs_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct Club {
     pub uid: u64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub number: Option<i64>,
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(skip_serializing_if = "crate::ser::skip_empty_str")]
     pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Region node ID
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub region: Option<u64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub region_name: Option<String>,
+    /// Founding (charter) date
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub founded: Option<NaiveDate>,
+    /// Published ssp_club node; dissolved clubs are unpublished but still exported
     pub active: bool,
 }
 
+/// Club meeting/rally location for mapping
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct ClubLocation {
+    pub uid: u64,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_number: Option<i64>,
+    pub name: String,
+    /// `None` when only an address is stored; left for a downstream geocoder
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub lat: Option<f64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub lng: Option<f64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub address: Option<String>,
+}
+
+/// Fetch locations for all clubs that have coordinates or an address.
+///
+/// Coordinates come from the `field_coordinates` paragraph (same structure as
+/// events), the address from `field_location`.
+pub async fn locations(db: &Db) -> Result<Vec<ClubLocation>> {
+    db.fetch("club locations", async || {
+        sqlx::query_as::<_, ClubLocation>(
+            r#"
+        SELECT
+            nd.nid AS uid,
+            cn.field_club_number_value AS club_number,
+            nd.title AS name,
+            CAST(lat.field_latitude_value AS DOUBLE) AS lat,
+            CAST(lon.field_longitude_value AS DOUBLE) AS lng,
+            loc.field_location_value AS address
+        FROM node_field_data nd
+        LEFT JOIN node__field_club_number cn ON cn.entity_id = nd.nid
+        LEFT JOIN node__field_location loc ON loc.entity_id = nd.nid AND loc.deleted = 0
+        LEFT JOIN node__field_coordinates coord ON coord.entity_id = nd.nid AND coord.deleted = 0
+        LEFT JOIN paragraph__field_latitude lat
+            ON lat.entity_id = coord.field_coordinates_target_id AND lat.deleted = 0
+        LEFT JOIN paragraph__field_longitude lon
+            ON lon.entity_id = coord.field_coordinates_target_id AND lon.deleted = 0
+        WHERE nd.type = 'ssp_club'
+          AND (loc.field_location_value IS NOT NULL
+               OR (lat.field_latitude_value IS NOT NULL AND lon.field_longitude_value IS NOT NULL))
+        ORDER BY cn.field_club_number_value, nd.title
+        "#,
+        )
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
+
+/// A club's social media and website links
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct SocialLinks {
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub facebook: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub instagram: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub youtube: Option<String>,
+    /// Any other external link
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub website: Option<String>,
+}
+
+impl SocialLinks {
+    /// Sort links by host; the first link of each kind wins and non-web
+    /// links (`internal:`, `entity:`) are ignored
+    fn from_urls(urls: impl IntoIterator<Item = String>) -> Self {
+        let mut links = Self::default();
+        for url in urls {
+            let Some(host) = url_host(&url) else {
+                continue;
+            };
+            let slot = if host == "fb.com" || is_on(&host, "facebook.com") {
+                &mut links.facebook
+            } else if is_on(&host, "instagram.com") {
+                &mut links.instagram
+            } else if is_on(&host, "youtube.com") || host == "youtu.be" {
+                &mut links.youtube
+            } else {
+                &mut links.website
+            };
+            slot.get_or_insert(url);
+        }
+        links
+    }
+}
+
+/// Whether `host` is `domain` or one of its subdomains
+fn is_on(host: &str, domain: &str) -> bool {
+    host.strip_suffix(domain)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+}
+
+/// Lowercase host of an http(s) URL
+fn url_host(url: &str) -> Option<String> {
+    let url = url.trim().to_ascii_lowercase();
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let host = rest.split(['/', '?', '#']).next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// Fetch a club's social links from both the club node and its microsite
+/// homepage.
+///
+/// Links come from the `field_social_media_new` paragraphs on either node,
+/// plus a homepage button pointing at Facebook (see
+/// [`crate::microsites::homepage_assets`]). When both nodes have a link of
+/// the same kind, the club node's wins. Unknown clubs have no links.
+pub async fn social_links(db: &Db, club_number: i64) -> Result<SocialLinks> {
+    let Some(club) = by_numbers(db, &[club_number]).await?.into_iter().next() else {
+        return Ok(SocialLinks::default());
+    };
+    let homepage_nid = crate::microsites::clubs_with_microsites_in(db, &[club_number])
+        .await?
+        .into_iter()
+        .next()
+        .map(|microsite| microsite.homepage_nid);
+
+    let urls: Vec<String> = db
+        .fetch("club social links", async || {
+            sqlx::query_scalar(
+                r#"
+        SELECT url FROM (
+            SELECT
+                sml.field_social_media_link_uri AS url,
+                smn.entity_id <> ? AS source,
+                smn.delta
+            FROM node__field_social_media_new smn
+            JOIN paragraph__field_social_media_link sml
+                ON sml.entity_id = smn.field_social_media_new_target_id AND sml.deleted = 0
+            WHERE smn.entity_id IN (?, ?) AND smn.deleted = 0
+
+            UNION ALL
+
+            SELECT field_button_uri AS url, 2 AS source, delta
+            FROM node__field_button
+            WHERE entity_id = ? AND deleted = 0
+            AND field_button_uri LIKE '%facebook.com%'
+        ) links
+        ORDER BY source, delta
+        "#,
+            )
+            .bind(club.uid)
+            .bind(club.uid)
+            .bind(homepage_nid)
+            .bind(homepage_nid)
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?;
+
+    Ok(SocialLinks::from_urls(urls))
+}
+
+/// A club's regular meeting, as opposed to its one-off events.
+///
+/// Drupal has no recurrence field: each meeting is stored as a separate
+/// event. A series is the club's events tagged or titled "meeting" that share
+/// a title once month names and numbers are removed (so "March Meeting" and
+/// "April Meeting" are one series), and its schedule is inferred from the
+/// dates of the latest [`MEETING_PATTERN_OCCURRENCES`] occurrences.
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct Meeting {
+    /// Title of the latest occurrence
+    pub title: String,
+    /// `None` when there are too few occurrences or no consistent pattern
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub recurrence: Option<Recurrence>,
+    /// Local start time, when every occurrence starts at the same time
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub time: Option<NaiveTime>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub location_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub address: Option<String>,
+    /// Next occurrence stored in Drupal (local time), if any is scheduled
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub next: Option<NaiveDateTime>,
+    /// Event nid of the next occurrence
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub next_event_uid: Option<u64>,
+    /// Number of stored occurrences, past and future
+    pub occurrences: usize,
+}
+
+/// Monthly schedule inferred from meeting dates
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "frequency", rename_all = "snake_case")]
+pub enum Recurrence {
+    /// Same weekday and week of the month, e.g. the 2nd Tuesday. `week` is
+    /// 1 to 4, or -1 for the last such weekday of the month
+    MonthlyWeekday { week: i8, weekday: Weekday },
+    /// Same day of the month, e.g. the 15th
+    MonthlyDay { day: u32 },
+}
+
+/// Occurrences the recurrence pattern is inferred from, so older schedule
+/// changes don't hide the current one
+pub const MEETING_PATTERN_OCCURRENCES: usize = 6;
+
+/// Fetch a club's regular meetings (see [`Meeting`]); events without a start
+/// date are ignored
+pub async fn meetings(db: &Db, club_number: i64) -> Result<Vec<Meeting>> {
+    let events = crate::events::for_clubs(db, &[club_number]).await?;
+    Ok(meetings_from(events, chrono::Local::now().naive_local()))
+}
+
+fn meetings_from(events: Vec<Event>, now: NaiveDateTime) -> Vec<Meeting> {
+    let mut series: BTreeMap<String, Vec<Event>> = BTreeMap::new();
+    for event in events {
+        if event.start_date.is_some() && is_meeting(&event) {
+            series
+                .entry(series_key(&event.title))
+                .or_default()
+                .push(event);
+        }
+    }
+    series
+        .into_values()
+        .map(|mut events| {
+            events.sort_by_key(|event| event.start_date);
+            meeting_from(events, now)
+        })
+        .collect()
+}
+
+fn is_meeting(event: &Event) -> bool {
+    event.title.to_lowercase().contains("meeting")
+        || event
+            .tags
+            .iter()
+            .any(|tag| tag.eq_ignore_ascii_case("meeting"))
+}
+
+/// Lowercase title without month names and numbers
+fn series_key(title: &str) -> String {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty() && !MONTHS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Build a meeting from its occurrences, sorted by start date
+fn meeting_from(events: Vec<Event>, now: NaiveDateTime) -> Meeting {
+    let starts: Vec<NaiveDateTime> = events.iter().filter_map(|event| event.start_date).collect();
+    let recent = &starts[starts.len().saturating_sub(MEETING_PATTERN_OCCURRENCES)..];
+    let next = events
+        .iter()
+        .find(|event| event.start_date.is_some_and(|start| start >= now));
+    let latest = next.or(events.last()).expect("a series has occurrences");
+    Meeting {
+        title: events
+            .last()
+            .expect("a series has occurrences")
+            .title
+            .clone(),
+        recurrence: recurrence(recent),
+        time: same(recent.iter().map(|start| start.time())),
+        location_name: latest.location_name.clone(),
+        address: latest.address.clone(),
+        next: next.and_then(|event| event.start_date),
+        next_event_uid: next.map(|event| event.uid),
+        occurrences: events.len(),
+    }
+}
+
+/// Monthly pattern shared by all `starts`, at least two of them
+fn recurrence(starts: &[NaiveDateTime]) -> Option<Recurrence> {
+    if starts.len() < 2 {
+        return None;
+    }
+    let dates = || starts.iter().map(|start| start.date());
+    if let Some(weekday) = same(dates().map(|date| date.weekday())) {
+        if let Some(week) = same(dates().map(|date| date.day0() / 7 + 1)) {
+            return Some(Recurrence::MonthlyWeekday {
+                week: week as i8,
+                weekday,
+            });
+        }
+        // A 4th/5th mix is the last weekday when every date is in the month's last week
+        if dates().all(|date| (date + chrono::Days::new(7)).month() != date.month()) {
+            return Some(Recurrence::MonthlyWeekday { week: -1, weekday });
+        }
+    }
+    same(dates().map(|date| date.day())).map(|day| Recurrence::MonthlyDay { day })
+}
+
+/// The value every item has, `None` if they differ or there are none
+fn same<T: PartialEq>(mut items: impl Iterator<Item = T>) -> Option<T> {
+    let first = items.next()?;
+    items.all(|item| item == first).then_some(first)
+}
+
+/// Heading of the directory group for clubs without a region
+pub const INTERNATIONAL_GROUP: &str = "International";
+
+/// One region's section of the club directory printed in Blue Beret
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct RegionGroup {
+    /// Region node ID, `None` for the final international/unassigned group
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub region: Option<u64>,
+    pub name: String,
+    /// Sorted by name
+    pub clubs: Vec<DirectoryClub>,
+}
+
+/// A club's directory entry
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct DirectoryClub {
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub number: Option<i64>,
+    pub name: String,
+    /// Microsite path alias (see [`crate::microsites::club_slugs`]), else
+    /// the slugified club name
+    pub slug: String,
+    /// Name of the current president, if the club has one on record
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub contact_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub contact_email: Option<String>,
+}
+
+/// Name and email of a club's directory contact
+type Contact = (String, String);
+
+/// Fetch the club directory: active clubs grouped by region in region number
+/// order, with clubs without a region in a final [`INTERNATIONAL_GROUP`].
+/// Clubs and regions come from `ctx`.
+pub async fn directory(ctx: &mut crate::SyncContext) -> Result<Vec<RegionGroup>> {
+    let leadership =
+        crate::leadership::for_all_clubs(ctx.db(), crate::leadership::DateFilter::Current).await?;
+    let contacts: HashMap<u64, Contact> = leadership
+        .into_iter()
+        .filter(|officer| officer.role.title.eq_ignore_ascii_case("President"))
+        .map(|officer| {
+            let contact = (
+                officer.user.full_name(),
+                officer.user.email.trim().to_string(),
+            );
+            (officer.entity_uid, contact)
+        })
+        .collect();
+    let mut slugs = HashMap::new();
+    for slug in crate::microsites::club_slugs(ctx.db()).await? {
+        slugs.entry(slug.club_nid).or_insert(slug.slug);
+    }
+    // Both borrow the context, so the region numbers are copied out first
+    let numbers = region_numbers(ctx.regions().await?);
+    let clubs = ctx.clubs().await?;
+    Ok(directory_from(clubs, &numbers, &slugs, &contacts))
+}
+
+fn region_numbers(regions: &[Region]) -> HashMap<u64, Option<i32>> {
+    regions
+        .iter()
+        .map(|region| (region.uid, region.number))
+        .collect()
+}
+
+fn directory_from(
+    clubs: &[Club],
+    numbers: &HashMap<u64, Option<i32>>,
+    slugs: &HashMap<u64, String>,
+    contacts: &HashMap<u64, Contact>,
+) -> Vec<RegionGroup> {
+    let mut groups: BTreeMap<Option<u64>, RegionGroup> = BTreeMap::new();
+    for club in clubs.iter().filter(|club| club.active) {
+        let group = groups.entry(club.region).or_insert_with(|| RegionGroup {
+            region: club.region,
+            name: match club.region {
+                Some(_) => club.region_name.clone().unwrap_or_default(),
+                None => INTERNATIONAL_GROUP.to_string(),
+            },
+            clubs: vec![],
+        });
+        let (contact_name, contact_email) = contacts.get(&club.uid).cloned().unzip();
+        group.clubs.push(DirectoryClub {
+            number: club.number,
+            slug: slugs
+                .get(&club.uid)
+                .cloned()
+                .unwrap_or_else(|| crate::slug::slugify(&club.name)),
+            name: club.name.clone(),
+            contact_name,
+            contact_email,
+        });
+    }
+    let mut groups: Vec<RegionGroup> = groups.into_values().collect();
+    for group in &mut groups {
+        group.clubs.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    // Unassigned last, then unnumbered regions after numbered ones
+    groups.sort_by_key(|group| {
+        let number = group
+            .region
+            .and_then(|uid| numbers.get(&uid).copied().flatten());
+        (
+            group.region.is_none(),
+            number.is_none(),
+            number,
+            group.name.clone(),
+        )
+    });
+    groups
+}
+
 pub mod db {
     use super::*;
     use ::db as app_db;
@@ -105,3 +617,169 @@ pub mod db {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meeting(uid: u64, title: &str, start: &str) -> Event {
+        let mut event = Event::test(uid, title);
+        event.start_date = Some(NaiveDateTime::parse_from_str(start, "%Y-%m-%d %H:%M").unwrap());
+        event
+    }
+
+    #[test]
+    fn test_meetings_from() {
+        let now = NaiveDateTime::parse_from_str("2025-03-01 00:00", "%Y-%m-%d %H:%M").unwrap();
+        let events = vec![
+            meeting(1, "January Meeting", "2025-01-14 19:00"),
+            meeting(2, "February Meeting", "2025-02-11 19:00"),
+            meeting(3, "March Meeting", "2025-03-11 19:00"),
+            meeting(4, "Board meeting", "2025-01-31 10:00"),
+            meeting(5, "Board meeting", "2025-02-28 10:00"),
+            meeting(6, "Spring Rally", "2025-04-05 12:00"),
+        ];
+        let meetings = meetings_from(events, now);
+        assert_eq!(meetings.len(), 2);
+
+        let board = &meetings[0];
+        assert_eq!(
+            board.recurrence,
+            Some(Recurrence::MonthlyWeekday {
+                week: -1,
+                weekday: Weekday::Fri
+            })
+        );
+        assert_eq!(board.next, None);
+
+        let monthly = &meetings[1];
+        assert_eq!(monthly.title, "March Meeting");
+        assert_eq!(
+            monthly.recurrence,
+            Some(Recurrence::MonthlyWeekday {
+                week: 2,
+                weekday: Weekday::Tue
+            })
+        );
+        assert_eq!(monthly.time, NaiveTime::from_hms_opt(19, 0, 0));
+        assert_eq!(monthly.next_event_uid, Some(3));
+        assert_eq!(monthly.occurrences, 3);
+    }
+
+    #[test]
+    fn test_recurrence_day_of_month() {
+        let starts: Vec<NaiveDateTime> = ["2025-01-15 18:00", "2025-02-15 18:00"]
+            .iter()
+            .map(|s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap())
+            .collect();
+        assert_eq!(
+            recurrence(&starts),
+            Some(Recurrence::MonthlyDay { day: 15 })
+        );
+        assert_eq!(recurrence(&starts[..1]), None);
+    }
+
+    #[test]
+    fn test_social_links_from_urls() {
+        let urls = [
+            "internal:/about",
+            "https://www.facebook.com/club",
+            "https://www.Instagram.com/club/",
+            "https://facebook.com/club-old",
+            "https://youtu.be/abc",
+            "http://club.example.org",
+        ];
+        let links = SocialLinks::from_urls(urls.map(String::from));
+        assert_eq!(
+            links,
+            SocialLinks {
+                facebook: Some("https://www.facebook.com/club".to_string()),
+                instagram: Some("https://www.Instagram.com/club/".to_string()),
+                youtube: Some("https://youtu.be/abc".to_string()),
+                website: Some("http://club.example.org".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_social_links_ignore_look_alike_hosts() {
+        let urls = [
+            "https://notfacebook.com/club",
+            "https://evilinstagram.com/club",
+            "https://myyoutube.com/club",
+            "https://m.youtube.com/@club",
+        ];
+        let links = SocialLinks::from_urls(urls.map(String::from));
+        assert_eq!(
+            links,
+            SocialLinks {
+                facebook: None,
+                instagram: None,
+                youtube: Some("https://m.youtube.com/@club".to_string()),
+                website: Some("https://notfacebook.com/club".to_string()),
+            }
+        );
+    }
+
+    fn club(uid: u64, name: &str, region: Option<(u64, &str)>) -> Club {
+        Club {
+            uid,
+            number: Some(uid as i64),
+            name: name.to_string(),
+            region: region.map(|(uid, _)| uid),
+            region_name: region.map(|(_, name)| name.to_string()),
+            founded: None,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_directory_from() {
+        let regions = [
+            Region {
+                uid: 100,
+                number: Some(2),
+                name: Some("Southeast".to_string()),
+                active: true,
+            },
+            Region {
+                uid: 200,
+                number: Some(1),
+                name: Some("Northeast".to_string()),
+                active: true,
+            },
+        ];
+        let mut dissolved = club(4, "Old Timers", Some((200, "Northeast")));
+        dissolved.active = false;
+        let clubs = vec![
+            club(1, "Sunshine Unit", Some((100, "Southeast"))),
+            club(2, "International Caravanners", None),
+            club(3, "Bay State Unit", Some((200, "Northeast"))),
+            dissolved,
+            club(5, "Gator Unit", Some((100, "Southeast"))),
+        ];
+        let contact = ("Pat Smith".to_string(), "pat@example.com".to_string());
+        let contacts = HashMap::from([(3, contact)]);
+
+        let slugs = HashMap::from([(1, "sunshine".to_string())]);
+
+        let groups = directory_from(&clubs, &region_numbers(&regions), &slugs, &contacts);
+        let names: Vec<&str> = groups.iter().map(|group| group.name.as_str()).collect();
+        assert_eq!(names, vec!["Northeast", "Southeast", INTERNATIONAL_GROUP]);
+        assert_eq!(groups[0].clubs.len(), 1);
+        assert_eq!(groups[0].clubs[0].slug, "bay-state-unit");
+        assert_eq!(
+            groups[0].clubs[0].contact_name.as_deref(),
+            Some("Pat Smith")
+        );
+        assert_eq!(groups[1].clubs[0].contact_email, None);
+        let southeast: Vec<&str> = groups[1]
+            .clubs
+            .iter()
+            .map(|club| club.name.as_str())
+            .collect();
+        assert_eq!(southeast, vec!["Gator Unit", "Sunshine Unit"]);
+        assert_eq!(groups[1].clubs[1].slug, "sunshine");
+        assert_eq!(groups[2].region, None);
+    }
+}
@@ -0,0 +1,188 @@
+//! `--anonymize`: replace personal data in exported records with stand-ins.
+//!
+//! Records keep their shape: fields are rewritten or nulled by key, never
+//! removed, and IDs are left alone so relationships between exports still
+//! line up. Fake names and emails are derived from the record's user ID, so
+//! the same user gets the same stand-ins in every export. Values without a
+//! user ID (event contacts, officer lists) are derived from a hash of the
+//! original value instead, which is also stable across exports.
+
+use serde_json::{Map, Value};
+
+/// Keys holding the ID of the user a record describes
+const UID_KEYS: &[&str] = &["uid", "user_uid", "user_id", "legacyId"];
+
+/// Keys whose values are replaced with null
+const REDACTED_KEYS: &[&str] = &[
+    "pass",
+    "birthday",
+    "member_notes",
+    "phone",
+    "contact_phone",
+    "street_address",
+    "street_address_2",
+    "zip_code",
+];
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Bailey", "Casey", "Dana", "Emerson", "Finley", "Harper", "Jordan", "Kendall", "Logan",
+    "Morgan", "Parker", "Quinn", "Riley", "Sawyer", "Taylor",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Anderson", "Brooks", "Carter", "Dalton", "Ellis", "Foster", "Garcia", "Hayes", "Irving",
+    "Jensen", "Keller", "Lawson", "Mercer", "Nolan", "Porter", "Reyes",
+];
+
+/// Anonymize every record in `value`, including nested ones
+pub fn anonymize(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(anonymize),
+        Value::Object(record) => anonymize_record(record),
+        _ => {}
+    }
+}
+
+fn anonymize_record(record: &mut Map<String, Value>) {
+    let uid = UID_KEYS
+        .iter()
+        .find_map(|key| record.get(*key).and_then(Value::as_u64));
+    let partner_uid = record.get("partner_uid").and_then(Value::as_u64);
+    let author_uid = record.get("last_author_uid").and_then(Value::as_u64);
+
+    for (key, value) in record.iter_mut() {
+        let key = key.as_str();
+        if REDACTED_KEYS.contains(&key) {
+            *value = Value::Null;
+            continue;
+        }
+        if key == "secondary_emails"
+            && let Value::Array(emails) = value
+        {
+            for email in emails {
+                if let Value::String(original) = email {
+                    *original = hashed_email(original);
+                }
+            }
+            continue;
+        }
+        let Value::String(original) = value else {
+            anonymize(value);
+            continue;
+        };
+        let replacement = match key {
+            "email" | "mail" => match uid {
+                Some(uid) => format!("user{uid}@example.invalid"),
+                None => hashed_email(original),
+            },
+            "contact_email" => hashed_email(original),
+            "first_name" | "firstName" => fake_first_name(seed(uid, original)).to_string(),
+            "last_name" | "lastName" => fake_last_name(seed(uid, original)).to_string(),
+            "partner_first_name" => fake_first_name(seed(partner_uid, original)).to_string(),
+            "partner_last_name" => fake_last_name(seed(partner_uid, original)).to_string(),
+            "user_name" => fake_full_name(seed(uid, original)),
+            "contact_name" => fake_full_name(seed(None, original)),
+            "last_author_name" => fake_full_name(seed(author_uid, original)),
+            _ => continue,
+        };
+        *original = replacement;
+    }
+}
+
+/// The user ID if known, else a hash of the original value
+fn seed(uid: Option<u64>, original: &str) -> u64 {
+    uid.unwrap_or_else(|| fnv1a(original))
+}
+
+fn hashed_email(original: &str) -> String {
+    format!(
+        "anon-{:016x}@example.invalid",
+        fnv1a(&original.to_lowercase())
+    )
+}
+
+fn fake_first_name(seed: u64) -> &'static str {
+    FIRST_NAMES[(mix(seed) % FIRST_NAMES.len() as u64) as usize]
+}
+
+fn fake_last_name(seed: u64) -> &'static str {
+    LAST_NAMES[((mix(seed) >> 32) % LAST_NAMES.len() as u64) as usize]
+}
+
+fn fake_full_name(seed: u64) -> String {
+    format!("{} {}", fake_first_name(seed), fake_last_name(seed))
+}
+
+/// FNV-1a, chosen over `DefaultHasher` because its output is fixed across
+/// Rust releases
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// SplitMix64 finalizer, so consecutive IDs get unrelated names
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_anonymize_member() {
+        let mut value = json!([{
+            "uid": 42,
+            "email": "pat@example.com",
+            "first_name": "Pat",
+            "last_name": "Smith",
+            "birthday": "1960-01-01",
+            "secondary_emails": ["pat.smith@example.org"],
+            "member_class": "Lifetime",
+            "partner": {"uid": 43, "email": "sam@example.com", "first_name": "Sam"},
+            "brns": ["07569"]
+        }]);
+        anonymize(&mut value);
+
+        let member = &value[0];
+        assert_eq!(member["email"], "user42@example.invalid");
+        assert_eq!(member["partner"]["email"], "user43@example.invalid");
+        assert_ne!(member["first_name"], "Pat");
+        assert_eq!(member["birthday"], Value::Null);
+        assert_eq!(
+            member["secondary_emails"],
+            json!([hashed_email("pat.smith@example.org")])
+        );
+        assert_eq!(member["member_class"], "Lifetime");
+        assert_eq!(member["brns"], json!(["07569"]));
+
+        // The same user gets the same stand-ins in any record
+        let mut user = json!({"uid": 42, "first_name": "Patricia", "last_name": "Smith"});
+        anonymize(&mut user);
+        assert_eq!(user["first_name"], member["first_name"]);
+        assert_eq!(user["last_name"], member["last_name"]);
+    }
+
+    #[test]
+    fn test_anonymize_without_uid() {
+        let mut officers = json!([
+            {"club_number": 1, "user_name": "Pat Smith", "email": "Pat@example.com"},
+            {"club_number": 2, "user_name": "Pat Smith", "email": "pat@example.com"}
+        ]);
+        anonymize(&mut officers);
+        assert_eq!(officers[0]["email"], officers[1]["email"]);
+        assert_eq!(officers[0]["user_name"], officers[1]["user_name"]);
+        assert!(
+            officers[0]["email"]
+                .as_str()
+                .unwrap()
+                .ends_with("@example.invalid")
+        );
+        assert_ne!(officers[0]["user_name"], "Pat Smith");
+    }
+}
@@ -1,5 +1,5 @@
-use super::{Result, connect_from_env, print_json};
-use aci_ddb::clubs;
+use super::{Output, Result, connect_from_env, count_only_unsupported, print_count};
+use aci_ddb::{SyncContext, clubs};
 use anyhow::anyhow;
 
 /// Club management commands
@@ -8,6 +8,9 @@ use anyhow::anyhow;
 ///   # List all clubs
 ///   aci-ddb clubs
 ///
+///   # List clubs changed since a unix timestamp
+///   aci-ddb clubs --since 1700000000
+///
 ///   # Get club by uid
 ///   aci-ddb clubs 12345
 ///
@@ -27,6 +30,21 @@ use anyhow::anyhow;
 ///   aci-ddb clubs leadership 2020-01-15
 ///   aci-ddb clubs leadership 12345 2020-01-15
 ///   aci-ddb clubs leadership --number 42 2020-01-15
+///
+///   # Get the club directory grouped by region, as printed in Blue Beret
+///   aci-ddb clubs directory
+///
+///   # Get meeting/rally locations for all clubs
+///   aci-ddb clubs locations
+///
+///   # Get the email list of all current club presidents
+///   aci-ddb clubs officer-emails --role President
+///
+///   # Get social media links for a club by number
+///   aci-ddb clubs social 42
+///
+///   # Get the regular meetings of a club by number
+///   aci-ddb clubs meetings 42
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
     /// Club uid or number (depending on --number flag). Omit to list all clubs.
@@ -36,29 +54,63 @@ pub struct Cmd {
     #[arg(long)]
     pub number: bool,
 
+    /// Only clubs whose club node changed at or after this unix timestamp
+    #[arg(long, conflicts_with_all = ["id", "number"])]
+    pub since: Option<i64>,
+
     #[command(subcommand)]
     cmd: Option<ClubCmd>,
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match &self.cmd {
-            Some(cmd) => cmd.run().await,
+            Some(cmd) => cmd.run(output).await,
             None => {
                 Get {
                     id: self.id,
                     number: self.number,
+                    since: self.since,
                 }
-                .run()
+                .run(output)
                 .await
             }
         }
     }
+
+    /// `--count-only` for the list of all clubs
+    pub async fn count(&self, output: &Output) -> Result {
+        if self.cmd.is_some() || self.id.is_some() || self.since.is_some() {
+            return Err(count_only_unsupported());
+        }
+        let db = connect_from_env().await?;
+        print_count(output, "clubs", clubs::count(&db).await?)
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
 pub enum ClubCmd {
     Leadership(LeadershipCmd),
+    /// Active clubs grouped by region, with their president as contact
+    Directory,
+    /// Meeting/rally locations with coordinates for mapping
+    Locations,
+    /// Current club officers' emails for governance announcements
+    OfficerEmails {
+        /// Only officers holding this role, e.g. President (case-insensitive)
+        #[arg(long)]
+        role: Option<String>,
+    },
+    /// Social media and website links from the club node and its microsite
+    Social {
+        /// Club number
+        number: i64,
+    },
+    /// Regular meetings with their inferred monthly schedule and next date
+    Meetings {
+        /// Club number
+        number: i64,
+    },
 }
 
 #[derive(Debug, clap::Args)]
@@ -75,7 +127,7 @@ pub(crate) struct LeadershipCmd {
 }
 
 impl ClubCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
             Self::Leadership(args) => {
                 Leadership {
@@ -83,9 +135,34 @@ impl ClubCmd {
                     number: args.number,
                     as_of: args.as_of,
                 }
-                .run()
+                .run(output)
                 .await
             }
+            Self::Directory => {
+                let mut ctx = SyncContext::new(connect_from_env().await?);
+                let directory = clubs::directory(&mut ctx).await?;
+                output.print_json(&directory)
+            }
+            Self::Locations => {
+                let db = connect_from_env().await?;
+                let locations = clubs::locations(&db).await?;
+                output.print_json(&locations)
+            }
+            Self::OfficerEmails { role } => {
+                let mut ctx = SyncContext::new(connect_from_env().await?);
+                let emails = aci_ddb::leadership::officer_emails(&mut ctx, role.as_deref()).await?;
+                output.print_json(&emails)
+            }
+            Self::Social { number } => {
+                let db = connect_from_env().await?;
+                let links = clubs::social_links(&db, *number).await?;
+                output.print_json(&links)
+            }
+            Self::Meetings { number } => {
+                let db = connect_from_env().await?;
+                let meetings = clubs::meetings(&db, *number).await?;
+                output.print_json(&meetings)
+            }
         }
     }
 }
@@ -93,31 +170,37 @@ impl ClubCmd {
 struct Get {
     id: Option<u64>,
     number: bool,
+    since: Option<i64>,
 }
 
 impl Get {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
 
+        if let Some(since) = self.since {
+            let clubs = clubs::changed_since(&db, since).await?;
+            return output.print_json(&clubs);
+        }
+
         match (self.id, self.number) {
             (Some(id), true) => {
                 // Lookup by number
                 let club = clubs::by_number(&db, id as i32)
                     .await?
                     .ok_or_else(|| anyhow!("Club number {id} not found"))?;
-                print_json(&club)
+                output.print_json(&club)
             }
             (Some(id), false) => {
                 // Lookup by uid
                 let club = clubs::by_uid(&db, id)
                     .await?
                     .ok_or_else(|| anyhow!("Club uid {id} not found"))?;
-                print_json(&club)
+                output.print_json(&club)
             }
             (None, _) => {
                 // No id - get all clubs
                 let clubs = clubs::all(&db).await?;
-                print_json(&clubs)
+                output.print_json(&clubs)
             }
         }
     }
@@ -130,7 +213,7 @@ struct Leadership {
 }
 
 impl Leadership {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         use aci_ddb::leadership::DateFilter;
 
         let db = connect_from_env().await?;
@@ -144,6 +227,6 @@ impl Leadership {
             (None, _) => aci_ddb::leadership::for_all_clubs(&db, filter).await?,
         };
 
-        print_json(&leadership)
+        output.print_json(&leadership)
     }
 }
@@ -0,0 +1,206 @@
+use super::{Output, Result};
+use anyhow::{Context, bail};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Compare two exports of the same entity
+///
+/// Both files must hold a JSON array of objects (any entity export). Records
+/// are matched by `--key` and reported as added, removed or changed, with the
+/// old and new value of every changed top-level field. Records are listed in
+/// key order, numerically for integer keys. A field that is absent on one side
+/// and `null` on the other is not a change.
+///
+/// Examples:
+///   aci-ddb diff old/users.json new/users.json
+///   aci-ddb diff old/clubs.json new/clubs.json --key number
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Earlier export
+    old: PathBuf,
+    /// Later export
+    new: PathBuf,
+    /// Field identifying a record in both exports
+    #[arg(long, default_value = "uid")]
+    key: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let old = read_records(&self.old)?;
+        let new = read_records(&self.new)?;
+        let diff = diff_records(&self.key, old, new)?;
+        output.print_json(&diff)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+struct Diff {
+    unchanged: usize,
+    added: Vec<Value>,
+    removed: Vec<Value>,
+    changed: Vec<ChangedRecord>,
+}
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+struct ChangedRecord {
+    key: Value,
+    fields: BTreeMap<String, FieldChange>,
+}
+
+/// A field's value before and after; `null` when the field is absent
+#[derive(Debug, PartialEq, serde::Serialize)]
+struct FieldChange {
+    old: Value,
+    new: Value,
+}
+
+fn read_records(path: &Path) -> Result<Vec<Value>> {
+    let file = std::fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let value: Value = serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("parsing {}", path.display()))?;
+    match value {
+        Value::Array(records) => Ok(records),
+        _ => bail!("{} is not a JSON array of records", path.display()),
+    }
+}
+
+/// A record's `--key` value as it is matched and ordered: integers
+/// numerically (whether written as a number or a string), anything else by
+/// its string form after them
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum RecordKey {
+    Integer(i128),
+    Text(String),
+}
+
+impl From<&Value> for RecordKey {
+    fn from(id: &Value) -> Self {
+        let text = match id {
+            Value::String(id) => id.clone(),
+            other => other.to_string(),
+        };
+        match text.parse::<i128>() {
+            // Not "007", which would otherwise match "7"
+            Ok(number) if number.to_string() == text => Self::Integer(number),
+            _ => Self::Text(text),
+        }
+    }
+}
+
+/// Index records by their `key` field
+fn by_key(key: &str, records: Vec<Value>) -> Result<BTreeMap<RecordKey, (Value, Value)>> {
+    let mut indexed = BTreeMap::new();
+    for record in records {
+        let Some(id) = record.get(key).filter(|id| !id.is_null()).cloned() else {
+            bail!("record without a {key:?} field: {record}");
+        };
+        if indexed
+            .insert(RecordKey::from(&id), (id.clone(), record))
+            .is_some()
+        {
+            bail!("duplicate {key} {id}");
+        }
+    }
+    Ok(indexed)
+}
+
+fn diff_records(key: &str, old: Vec<Value>, new: Vec<Value>) -> Result<Diff> {
+    let mut old = by_key(key, old)?;
+    let mut diff = Diff::default();
+    for (index, (id, record)) in by_key(key, new)? {
+        let Some((_, previous)) = old.remove(&index) else {
+            diff.added.push(id);
+            continue;
+        };
+        let fields = changed_fields(&previous, &record);
+        if fields.is_empty() {
+            diff.unchanged += 1;
+        } else {
+            diff.changed.push(ChangedRecord { key: id, fields });
+        }
+    }
+    diff.removed = old.into_values().map(|(id, _)| id).collect();
+    Ok(diff)
+}
+
+fn changed_fields(old: &Value, new: &Value) -> BTreeMap<String, FieldChange> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+    old.keys()
+        .chain(new.keys())
+        .filter_map(|field| {
+            let change = FieldChange {
+                old: old.get(field).cloned().unwrap_or_default(),
+                new: new.get(field).cloned().unwrap_or_default(),
+            };
+            (change.old != change.new).then(|| (field.clone(), change))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_records() {
+        let old = vec![
+            json!({"uid": 1, "email": "a@example.com", "first_name": "A"}),
+            json!({"uid": 2, "email": "b@example.com"}),
+            json!({"uid": 3, "email": "c@example.com"}),
+        ];
+        let new = vec![
+            json!({"uid": 1, "email": "a@example.com", "first_name": "A"}),
+            json!({"uid": 2, "email": "b2@example.com", "last_name": "B"}),
+            json!({"uid": 4, "email": "d@example.com"}),
+        ];
+        let diff = diff_records("uid", old, new).unwrap();
+
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added, vec![json!(4)]);
+        assert_eq!(diff.removed, vec![json!(3)]);
+        assert_eq!(diff.changed.len(), 1);
+        let changed = &diff.changed[0];
+        assert_eq!(changed.key, json!(2));
+        assert_eq!(
+            changed.fields.keys().collect::<Vec<_>>(),
+            vec!["email", "last_name"]
+        );
+        assert_eq!(changed.fields["last_name"].old, Value::Null);
+    }
+
+    #[test]
+    fn test_diff_records_orders_integer_keys_numerically() {
+        let old = vec![json!({"uid": 10}), json!({"uid": 9}), json!({"uid": "x"})];
+        let new = vec![json!({"uid": 100}), json!({"uid": 2}), json!({"uid": "11"})];
+        let diff = diff_records("uid", old, new).unwrap();
+        assert_eq!(diff.added, vec![json!(2), json!("11"), json!(100)]);
+        assert_eq!(diff.removed, vec![json!(9), json!(10), json!("x")]);
+
+        let diff = diff_records("uid", vec![json!({"uid": "7"})], vec![json!({"uid": 7})]).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed[0].key, json!(7));
+    }
+
+    #[test]
+    fn test_diff_records_treats_absent_as_null() {
+        let old = vec![json!({"uid": 1, "first_name": null})];
+        let new = vec![json!({"uid": 1, "last_name": null})];
+        let diff = diff_records("uid", old, new).unwrap();
+        assert_eq!(diff.unchanged, 1);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_diff_records_requires_unique_key() {
+        let records = vec![json!({"uid": 1}), json!({"uid": 1})];
+        assert!(diff_records("uid", records, vec![]).is_err());
+        assert!(diff_records("nid", vec![json!({"uid": 1})], vec![]).is_err());
+    }
+}
@@ -0,0 +1,302 @@
+#[cfg(feature = "s3")]
+use super::sink::S3Sink;
+use super::{
+    Output, Result, Spinner,
+    anonymize::anonymize,
+    connect_from_env,
+    sink::{FileSink, OutputSink, StdoutSink},
+};
+use aci_ddb::{
+    addresses, airstreams, brns, clubs, events, leadership, leadership::DateFilter, members, races,
+    rallies, regions, roles, standing_committees, users,
+};
+use anyhow::bail;
+use std::{future::Future, path::PathBuf};
+
+/// Export every entity to stdout, a directory or S3
+///
+/// Each entity is written as `<entity>.json` (on stdout, as one
+/// `{"entity": ..., "data": ...}` NDJSON line per entity), and
+/// `manifest.json` records the row count or the error for every entity. A
+/// failing entity doesn't stop the dump; the command exits non-zero at the
+/// end if any entity failed.
+///
+/// Scheduled runs can use `--fail-on-empty` and `--min-rows` as a canary: an
+/// entity with too few rows is still written but counts as failed.
+///
+/// Examples:
+///   aci-ddb dump --output-dir ./export
+///   aci-ddb dump --s3-bucket aci-exports --s3-prefix nightly/
+///   aci-ddb dump --output-dir ./export --fail-on-empty --min-rows users=10000
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Directory to write the entity files and manifest into (default: NDJSON
+    /// on stdout)
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+
+    /// Fail if any entity has no rows
+    #[arg(long)]
+    fail_on_empty: bool,
+
+    /// Fail if an entity has fewer rows than this, e.g. `users=10000` (repeatable)
+    #[arg(long, value_name = "ENTITY=N", value_parser = parse_min_rows)]
+    min_rows: Vec<(String, usize)>,
+
+    /// S3 bucket to upload the entity files and manifest to
+    #[cfg(feature = "s3")]
+    #[arg(long, conflicts_with = "output_dir")]
+    s3_bucket: Option<String>,
+
+    /// Key prefix for uploads, e.g. `nightly/`
+    #[cfg(feature = "s3")]
+    #[arg(long, default_value = "", requires = "s3_bucket")]
+    s3_prefix: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Manifest {
+    /// Unix timestamps of the dump start and end
+    started: i64,
+    finished: i64,
+    entities: Vec<EntityResult>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct EntityResult {
+    entity: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        #[cfg(feature = "s3")]
+        if let Some(bucket) = &self.s3_bucket {
+            let sink = S3Sink::from_env(bucket, &self.s3_prefix).await;
+            return dump_to(&sink, self, output).await;
+        }
+        match &self.output_dir {
+            Some(dir) => dump_to(&FileSink::new(dir)?, self, output).await,
+            None => dump_to(&StdoutSink, self, output).await,
+        }
+    }
+
+    /// Fewest rows an entity may have without failing the dump
+    fn min_rows(&self, entity: &str) -> usize {
+        self.min_rows
+            .iter()
+            .rev()
+            .find(|(name, _)| name == entity)
+            .map_or(usize::from(self.fail_on_empty), |(_, min)| *min)
+    }
+
+    /// Mark entities with fewer rows than required as failed
+    fn check_row_counts(&self, entities: &mut [EntityResult]) -> Result {
+        for (name, _) in &self.min_rows {
+            if !entities.iter().any(|entity| entity.entity == name) {
+                bail!("--min-rows: unknown entity {name:?}");
+            }
+        }
+        for entity in entities.iter_mut() {
+            let min = self.min_rows(entity.entity);
+            if let Some(count) = entity.count.filter(|count| *count < min) {
+                let error = format!("{count} rows, expected at least {min}");
+                eprintln!("error: {}: {error}", entity.entity);
+                entity.error = Some(error);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_min_rows(arg: &str) -> std::result::Result<(String, usize), String> {
+    let (entity, min) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected ENTITY=N, got {arg:?}"))?;
+    let min = min
+        .parse()
+        .map_err(|e| format!("invalid row count {min:?}: {e}"))?;
+    Ok((entity.to_string(), min))
+}
+
+/// Dump every entity and the manifest into `sink`
+async fn dump_to(sink: &impl OutputSink, cmd: &Cmd, output: &Output) -> Result {
+    let started = chrono::Utc::now().timestamp();
+    let db = connect_from_env().await?;
+    let spinner = Spinner::new();
+    let progress = |event| spinner.report(event);
+
+    let mut entities = vec![
+        dump(
+            sink,
+            output,
+            "users",
+            users::all_with_progress(&db, Some(&progress)),
+        )
+        .await,
+        dump(sink, output, "user_tombstones", users::tombstones(&db)).await,
+        dump(
+            sink,
+            output,
+            "members",
+            members::all_with_progress(&db, Some(&progress)),
+        )
+        .await,
+        dump(sink, output, "clubs", clubs::all(&db)).await,
+        dump(sink, output, "regions", regions::all(&db)).await,
+        dump(
+            sink,
+            output,
+            "standing_committees",
+            standing_committees::all(&db),
+        )
+        .await,
+        dump(
+            sink,
+            output,
+            "club_leadership",
+            leadership::for_all_clubs(&db, DateFilter::All),
+        )
+        .await,
+        dump(
+            sink,
+            output,
+            "region_leadership",
+            leadership::for_all_regions(&db, DateFilter::All),
+        )
+        .await,
+        dump(
+            sink,
+            output,
+            "committee_leadership",
+            leadership::for_all_standing_committees(&db, DateFilter::All),
+        )
+        .await,
+        dump(sink, output, "events", events::all(&db)).await,
+        dump(sink, output, "rallies", rallies::all_rallies(&db)).await,
+        dump(
+            sink,
+            output,
+            "rally_registrations",
+            rallies::all_registrations(&db),
+        )
+        .await,
+        dump(sink, output, "brns", brns::all(&db)).await,
+        dump(sink, output, "airstreams", airstreams::all(&db)).await,
+        dump(sink, output, "addresses", addresses::all(&db)).await,
+        dump(sink, output, "roles", roles::all(&db)).await,
+        dump(sink, output, "races", races::all(&db)).await,
+    ];
+
+    drop(spinner);
+    cmd.check_row_counts(&mut entities)?;
+
+    let failed = entities
+        .iter()
+        .filter(|entity| entity.error.is_some())
+        .count();
+    let manifest = Manifest {
+        started,
+        finished: chrono::Utc::now().timestamp(),
+        entities,
+    };
+    sink.write_entity("manifest", serde_json::to_vec_pretty(&manifest)?)
+        .await?;
+
+    if failed > 0 {
+        bail!(
+            "{failed} of {} entities failed; see {}",
+            manifest.entities.len(),
+            sink.location("manifest")
+        );
+    }
+    Ok(())
+}
+
+/// Fetch one entity and write it to the sink, capturing any failure.
+///
+/// Only `--anonymize` applies; the other output options don't fit a dump.
+async fn dump<T: serde::Serialize>(
+    sink: &impl OutputSink,
+    output: &Output,
+    entity: &'static str,
+    rows: impl Future<Output = aci_ddb::Result<Vec<T>>>,
+) -> EntityResult {
+    let file = format!("{entity}.json");
+    let result: Result<usize> = async {
+        let rows = rows.await?;
+        let body = if output.anonymize {
+            let mut value = serde_json::to_value(&rows)?;
+            anonymize(&mut value);
+            serde_json::to_vec_pretty(&value)?
+        } else {
+            serde_json::to_vec_pretty(&rows)?
+        };
+        sink.write_entity(entity, body).await?;
+        Ok(rows.len())
+    }
+    .await;
+
+    match result {
+        Ok(count) => EntityResult {
+            entity,
+            file: Some(file),
+            count: Some(count),
+            error: None,
+        },
+        Err(e) => {
+            eprintln!("error: {entity}: {e:#}");
+            EntityResult {
+                entity,
+                file: None,
+                count: None,
+                error: Some(format!("{e:#}")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Debug, clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        dump: Cmd,
+    }
+
+    fn entity(entity: &'static str, count: usize) -> EntityResult {
+        EntityResult {
+            entity,
+            file: Some(format!("{entity}.json")),
+            count: Some(count),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_check_row_counts() {
+        let cmd = Cli::parse_from(["dump", "--fail-on-empty", "--min-rows", "users=2"]).dump;
+        let mut entities = vec![entity("users", 1), entity("clubs", 0), entity("races", 3)];
+        cmd.check_row_counts(&mut entities).unwrap();
+        let errors: Vec<Option<&str>> = entities.iter().map(|e| e.error.as_deref()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                Some("1 rows, expected at least 2"),
+                Some("0 rows, expected at least 1"),
+                None
+            ]
+        );
+
+        let cmd = Cli::parse_from(["dump", "--min-rows", "user=2"]).dump;
+        assert!(cmd.check_row_counts(&mut entities).is_err());
+    }
+}
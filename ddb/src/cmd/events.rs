@@ -0,0 +1,113 @@
+use super::{
+    Output, Result, connect_from_env, count_only_unsupported, print_count, warn_unknown_clubs,
+};
+use aci_ddb::{Db, SyncContext, clubs, events, regions};
+
+/// Event export commands
+///
+/// Examples:
+///   # Export all published events as JSON
+///   aci-ddb events
+///
+///   # Export all published events without description and body, for list views
+///   aci-ddb events --summary
+///
+///   # Export events owned by a club or region (ssp_club/ssp_region nid)
+///   aci-ddb events --owner 12345
+///
+///   # Export events with region/club names for breadcrumbs
+///   aci-ddb events --with-path
+///
+///   # Export events owned by a set of clubs (by club number)
+///   aci-ddb events --clubs 1,7,42
+///
+///   # Export a club's events as a subscribable iCalendar feed
+///   aci-ddb events --owner 12345 ics > club.ics
+///
+///   # Export a club's events as an RSS feed
+///   aci-ddb events --owner 12345 rss > club.xml
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Only include events owned by this club or region nid
+    #[arg(long, global = true)]
+    pub owner: Option<u64>,
+
+    /// Only include events owned by these club numbers (comma separated)
+    #[arg(long, global = true, value_delimiter = ',', conflicts_with = "owner")]
+    pub clubs: Vec<i64>,
+
+    /// Leave out the description and body HTML, for list views
+    #[arg(long, conflicts_with_all = ["owner", "clubs"])]
+    pub summary: bool,
+
+    /// Include the owning region and club names (JSON output only)
+    #[arg(long)]
+    pub with_path: bool,
+
+    #[command(subcommand)]
+    cmd: Option<EventCmd>,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let events = match self.owner {
+            Some(owner) => events::for_owner(&db, owner).await?,
+            None if !self.clubs.is_empty() => {
+                let found = clubs::by_numbers(&db, &self.clubs).await?;
+                warn_unknown_clubs(&self.clubs, found.iter().map(|club| club.number));
+                events::for_clubs(&db, &self.clubs).await?
+            }
+            None if self.summary => events::summaries(&db).await?,
+            None => events::all(&db).await?,
+        };
+
+        match &self.cmd {
+            None if self.with_path => {
+                let mut ctx = SyncContext::new(db);
+                output.print_json(&events::attach_hierarchy(&mut ctx, events).await?)
+            }
+            None => output.print_json(&events),
+            Some(EventCmd::Ics) => {
+                print!("{}", events::to_ics(&events));
+                Ok(())
+            }
+            Some(EventCmd::Rss) => {
+                let owner_name = match self.owner {
+                    Some(owner) => owner_name(&db, owner).await?,
+                    None => "Airstream Club International".to_string(),
+                };
+                print!("{}", events::to_rss(&owner_name, &events));
+                Ok(())
+            }
+        }
+    }
+
+    /// `--count-only` for all published events, in any output format
+    pub async fn count(&self, output: &Output) -> Result {
+        if self.owner.is_some() || !self.clubs.is_empty() {
+            return Err(count_only_unsupported());
+        }
+        let db = connect_from_env().await?;
+        print_count(output, "events", events::count(&db).await?)
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum EventCmd {
+    /// Output events as an iCalendar (.ics) feed
+    Ics,
+    /// Output events as an RSS 2.0 feed
+    Rss,
+}
+
+/// Club or region name for an owner nid
+async fn owner_name(db: &Db, owner: u64) -> Result<String> {
+    if let Some(club) = clubs::by_uid(db, owner).await? {
+        return Ok(club.name);
+    }
+    let region = regions::by_uid(db, owner).await?;
+    Ok(region
+        .and_then(|region| region.name)
+        .unwrap_or_else(|| format!("Owner {owner}")))
+}
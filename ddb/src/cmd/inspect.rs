@@ -0,0 +1,25 @@
+use super::{Output, Result, connect_from_env};
+use aci_ddb::inspect;
+
+/// Print every raw field value of a node, for debugging field mappings
+///
+/// Reads all `node__field_*` tables, so any field can be checked without
+/// writing SQL. Fields without a value are left out.
+///
+/// Examples:
+///   aci-ddb inspect-node --nid 55629
+///   aci-ddb inspect-node --nid 55629 --fields field_club,field_body
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Node ID
+    #[arg(long)]
+    nid: u64,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let fields = inspect::node_fields(&db, self.nid).await?;
+        output.print_json(&fields)
+    }
+}
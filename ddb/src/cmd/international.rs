@@ -1,4 +1,5 @@
-use super::{Result, connect_from_env, print_json};
+use super::{Output, Result, connect_from_env};
+use anyhow::anyhow;
 
 /// International organization commands
 ///
@@ -8,6 +9,12 @@ use super::{Result, connect_from_env, print_json};
 ///
 ///   # Get international leadership as of a specific date
 ///   aci-ddb international leadership 2020-01-15
+///
+///   # Get current and future international rallies
+///   aci-ddb international upcoming-rallies
+///
+///   # Reconcile a rally's registration payments (rally nid)
+///   aci-ddb international rally-financials 12345
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
     #[command(subcommand)]
@@ -15,14 +22,18 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
-        self.cmd.run().await
+    pub async fn run(&self, output: &Output) -> Result {
+        self.cmd.run(output).await
     }
 }
 
 #[derive(Debug, clap::Subcommand)]
 pub enum InternationalCmd {
     Leadership(LeadershipCmd),
+    /// Rallies starting today or later, or with registration still open
+    UpcomingRallies(UpcomingRalliesCmd),
+    /// Expected fees vs billed, collected and outstanding payments of a rally
+    RallyFinancials(RallyFinancialsCmd),
 }
 
 #[derive(Debug, clap::Args)]
@@ -31,10 +42,30 @@ pub(crate) struct LeadershipCmd {
     pub as_of: Option<chrono::NaiveDate>,
 }
 
+#[derive(Debug, clap::Args)]
+pub(crate) struct UpcomingRalliesCmd {
+    /// Optional date (YYYY-MM-DD) to list rallies upcoming as of that date. Omit for today.
+    pub as_of: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct RallyFinancialsCmd {
+    /// Rally node ID
+    pub rally_uid: u64,
+}
+
 impl InternationalCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
-            Self::Leadership(args) => Leadership { as_of: args.as_of }.run().await,
+            Self::Leadership(args) => Leadership { as_of: args.as_of }.run(output).await,
+            Self::UpcomingRallies(args) => UpcomingRallies { as_of: args.as_of }.run(output).await,
+            Self::RallyFinancials(args) => {
+                let db = connect_from_env().await?;
+                let financials = aci_ddb::rallies::financials(&db, args.rally_uid)
+                    .await?
+                    .ok_or_else(|| anyhow!("rally {} not found", args.rally_uid))?;
+                output.print_json(&financials)
+            }
         }
     }
 }
@@ -44,12 +75,27 @@ struct Leadership {
 }
 
 impl Leadership {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         use aci_ddb::leadership::DateFilter;
 
         let db = connect_from_env().await?;
         let filter = self.as_of.map_or(DateFilter::Current, DateFilter::AsOf);
         let leadership = aci_ddb::leadership::for_international(&db, filter).await?;
-        print_json(&leadership)
+        output.print_json(&leadership)
+    }
+}
+
+struct UpcomingRallies {
+    as_of: Option<chrono::NaiveDate>,
+}
+
+impl UpcomingRallies {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let as_of = self
+            .as_of
+            .unwrap_or_else(|| chrono::Local::now().date_naive());
+        let rallies = aci_ddb::rallies::upcoming(&db, as_of).await?;
+        output.print_json(&rallies)
     }
 }
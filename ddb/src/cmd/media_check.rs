@@ -0,0 +1,111 @@
+//! `microsites assets --validate-media`: check that referenced media resolve
+//! on the live site before migrating them.
+
+use super::Result;
+use aci_ddb::{Limiter, microsites};
+use futures::StreamExt;
+use std::{collections::BTreeMap, num::NonZeroU32, time::Duration};
+
+/// Give up on a single request after this long
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of a HEAD request for one media URL
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct MediaStatus {
+    /// URL as found in the page content
+    pub url: String,
+    /// Absolute URL that was requested
+    pub resolved: String,
+    /// HTTP status after redirects, `None` when the request failed
+    #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+    pub error: Option<String>,
+}
+
+/// HEAD every URL against `base_url`, at most `concurrency` at a time and
+/// `rate` requests per second. Results are in the order of `urls`.
+pub async fn check(
+    urls: &[String],
+    base_url: &str,
+    concurrency: usize,
+    rate: NonZeroU32,
+) -> Result<Vec<MediaStatus>> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let limiter = Limiter::per_second(rate);
+    let statuses = futures::stream::iter(urls)
+        .map(|url| {
+            let resolved = microsites::resolve_media_url(base_url, url);
+            let (client, limiter) = (&client, &limiter);
+            async move {
+                limiter.acquire().await;
+                let (status, error) = match client.head(&resolved).send().await {
+                    Ok(response) => (Some(response.status().as_u16()), None),
+                    Err(err) => (None, Some(err.to_string())),
+                };
+                match status {
+                    Some(200) => {}
+                    Some(status) => log::warn!("{resolved}: HTTP {status}"),
+                    None => log::warn!("{resolved}: {}", error.as_deref().unwrap_or_default()),
+                }
+                MediaStatus {
+                    url: url.clone(),
+                    resolved,
+                    status,
+                    error,
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    Ok(statuses)
+}
+
+/// Count of results as `200`, `404`, `other` (any other status) and `error`
+/// (no response)
+pub fn status_counts(statuses: &[MediaStatus]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    for status in statuses {
+        let key = match status.status {
+            Some(200) => "200",
+            Some(404) => "404",
+            Some(_) => "other",
+            None => "error",
+        };
+        *counts.entry(key).or_default() += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn media_status(status: Option<u16>) -> MediaStatus {
+        MediaStatus {
+            url: "/sites/default/files/a.png".to_string(),
+            resolved: "https://example.org/sites/default/files/a.png".to_string(),
+            status,
+            error: status.is_none().then(|| "timed out".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_status_counts() {
+        let statuses = [
+            media_status(Some(200)),
+            media_status(Some(200)),
+            media_status(Some(404)),
+            media_status(Some(403)),
+            media_status(None),
+        ];
+        let counts = status_counts(&statuses);
+        assert_eq!(
+            counts,
+            BTreeMap::from([("200", 2), ("404", 1), ("other", 1), ("error", 1)])
+        );
+    }
+}
@@ -1,7 +1,13 @@
-use super::{Result, connect_from_env, print_json};
-use aci_ddb::members;
-use anyhow::anyhow;
+use super::{Output, Result, Spinner, connect_from_env, count_only_unsupported, print_count};
+use aci_ddb::{
+    Shard,
+    members::{self, MemberClass, MemberRecord, MemberStatus, MemberType},
+};
+use anyhow::{anyhow, bail};
+use chrono::NaiveDate;
+use std::io::Write;
 
+/// Member export commands
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
     #[command(subcommand)]
@@ -9,8 +15,15 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
-        self.cmd.run().await
+    pub async fn run(&self, output: &Output) -> Result {
+        self.cmd.run(output).await
+    }
+
+    pub async fn count(&self, output: &Output) -> Result {
+        match &self.cmd {
+            MemberCmd::All(cmd) => cmd.count(output).await,
+            _ => Err(count_only_unsupported()),
+        }
     }
 }
 
@@ -20,15 +33,17 @@ pub enum MemberCmd {
     Uid(Uid),
     Club(Club),
     All(All),
+    Directory(Directory),
 }
 
 impl MemberCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
-            Self::Email(cmd) => cmd.run().await,
-            Self::Uid(cmd) => cmd.run().await,
-            Self::Club(cmd) => cmd.run().await,
-            Self::All(cmd) => cmd.run().await,
+            Self::Email(cmd) => cmd.run(output).await,
+            Self::Uid(cmd) => cmd.run(output).await,
+            Self::Club(cmd) => cmd.run(output).await,
+            Self::All(cmd) => cmd.run(output).await,
+            Self::Directory(cmd) => cmd.run(output).await,
         }
     }
 }
@@ -41,13 +56,13 @@ pub struct Email {
 }
 
 impl Email {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
         let member = members::by_email(&db, &self.email)
             .await?
             .ok_or_else(|| anyhow!("Member {} not found", self.email))?;
 
-        print_json(&member)
+        output.print_json(&member)
     }
 }
 
@@ -59,13 +74,13 @@ pub struct Uid {
 }
 
 impl Uid {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
         let member = members::by_uid(&db, self.uid)
             .await?
             .ok_or_else(|| anyhow!("Member {} not found", self.uid))?;
 
-        print_json(&member)
+        output.print_json(&member)
     }
 }
 
@@ -79,23 +94,256 @@ pub struct Club {
 }
 
 impl Club {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
         let members = members::by_club(&db, self.uid).await?;
 
-        print_json(&members)
+        output.print_json_to(std::io::stdout().lock(), &members)
     }
 }
 
 /// Look up all active members in the database
+///
+/// Examples:
+///   # Export in four parallel slices
+///   for i in 0 1 2 3; do aci-ddb members all --shard $i/4 > members.part$i.json & done; wait
 #[derive(Debug, clap::Args)]
-pub struct All {}
+pub struct All {
+    /// Only export shard I of N (members whose uid % N = I)
+    #[arg(long, value_name = "I/N")]
+    pub shard: Option<Shard>,
+}
 
 impl All {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let spinner = Spinner::new();
+        let progress: aci_ddb::progress::Progress = Some(&|event| spinner.report(event));
+        let members = match self.shard {
+            Some(shard) => members::all_in_shard(&db, shard, progress).await?,
+            None => members::all_with_progress(&db, progress).await?,
+        };
+        drop(spinner);
+
+        output.print_json_iter_to(std::io::stdout().lock(), members)
+    }
+
+    pub async fn count(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
-        let members = members::all(&db).await?;
+        print_count(output, "members", members::count(&db, self.shard).await?)
+    }
+}
+
+/// Member directory: contact details, mailing address, clubs and BRNs per member
+#[derive(Debug, clap::Args)]
+pub struct Directory {
+    /// Write CSV (one row per member, clubs and BRNs joined by `;`, with `;`
+    /// and `\` inside a name or BRN escaped as `\;` and `\\`) instead of JSON
+    #[arg(long)]
+    csv: bool,
+}
+
+impl Directory {
+    pub async fn run(&self, output: &Output) -> Result {
+        if self.csv
+            && (!output.fields.is_empty() || output.stamp || output.envelope || output.anonymize)
+        {
+            bail!(
+                "--fields, --stamp, --envelope and --anonymize only apply to JSON output, not --csv"
+            );
+        }
+        let db = connect_from_env().await?;
+        let records = members::directory(&db).await?;
+
+        if self.csv {
+            write_directory_csv(std::io::stdout().lock(), records)
+        } else {
+            output.print_json_iter_to(std::io::stdout().lock(), records)
+        }
+    }
+}
+
+/// A [`MemberRecord`] flattened to one CSV row
+#[derive(serde::Serialize)]
+struct DirectoryRow<'a> {
+    uid: u64,
+    email: &'a str,
+    first_name: Option<&'a str>,
+    last_name: Option<&'a str>,
+    member_class: &'a MemberClass,
+    member_type: &'a MemberType,
+    member_status: &'a MemberStatus,
+    partner_first_name: Option<&'a str>,
+    partner_last_name: Option<&'a str>,
+    expiration_date: Option<NaiveDate>,
+    member_since: Option<NaiveDate>,
+    street_address: Option<&'a str>,
+    street_address_2: Option<&'a str>,
+    city: Option<&'a str>,
+    state: Option<&'a str>,
+    zip_code: Option<&'a str>,
+    country: Option<&'a str>,
+    /// Club numbers, home club first, position for position with
+    /// `club_names`
+    club_numbers: String,
+    club_names: String,
+    brns: String,
+}
+
+impl<'a> From<&'a MemberRecord> for DirectoryRow<'a> {
+    fn from(record: &'a MemberRecord) -> Self {
+        let address = record.address.as_ref();
+        let partner = record.partner.as_ref();
+        Self {
+            uid: record.uid,
+            email: &record.email,
+            first_name: record.first_name.as_deref(),
+            last_name: record.last_name.as_deref(),
+            member_class: &record.member_class,
+            member_type: &record.member_type,
+            member_status: &record.member_status,
+            partner_first_name: partner.and_then(|partner| partner.first_name.as_deref()),
+            partner_last_name: partner.and_then(|partner| partner.last_name.as_deref()),
+            expiration_date: record.expiration_date,
+            member_since: record.member_since,
+            street_address: address.and_then(|address| address.street_address.as_deref()),
+            street_address_2: address.and_then(|address| address.street_address_2.as_deref()),
+            city: address.and_then(|address| address.city.as_deref()),
+            state: address.and_then(|address| address.state.as_deref()),
+            zip_code: address.and_then(|address| address.zip_code.as_deref()),
+            country: address.and_then(|address| address.country.as_deref()),
+            club_numbers: join(
+                record
+                    .clubs
+                    .iter()
+                    .map(|club| club.club_number.map(|n| n.to_string())),
+            ),
+            club_names: join(record.clubs.iter().map(|club| club.club_name.clone())),
+            brns: join(record.brns.iter().cloned().map(Some)),
+        }
+    }
+}
+
+/// Join the values with `;`, leaving a missing one empty so that parallel
+/// lists still line up. A `;` or `\` within a value is escaped with `\`.
+fn join(values: impl Iterator<Item = Option<String>>) -> String {
+    values
+        .map(|value| {
+            value
+                .unwrap_or_default()
+                .replace('\\', "\\\\")
+                .replace(';', "\\;")
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Write the directory as CSV as the records come, one row at a time, with
+/// a header row even when there are no members
+fn write_directory_csv<W: Write>(
+    writer: W,
+    records: impl IntoIterator<Item = MemberRecord>,
+) -> Result {
+    let mut csv = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(std::io::BufWriter::new(writer));
+    csv.write_record(DIRECTORY_COLUMNS)?;
+    for record in records {
+        csv.serialize(DirectoryRow::from(&record))?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+/// Header of [`DirectoryRow`], written explicitly so it is present for an
+/// empty directory too
+const DIRECTORY_COLUMNS: &[&str] = &[
+    "uid",
+    "email",
+    "first_name",
+    "last_name",
+    "member_class",
+    "member_type",
+    "member_status",
+    "partner_first_name",
+    "partner_last_name",
+    "expiration_date",
+    "member_since",
+    "street_address",
+    "street_address_2",
+    "city",
+    "state",
+    "zip_code",
+    "country",
+    "club_numbers",
+    "club_names",
+    "brns",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aci_ddb::members::Address;
+
+    #[test]
+    fn test_write_directory_csv() {
+        let record = MemberRecord {
+            uid: 7,
+            email: "pat@example.com".to_string(),
+            first_name: Some("Pat \"PJ\"".to_string()),
+            last_name: Some("Smith, Jr.".to_string()),
+            member_class: MemberClass::Lifetime,
+            member_type: MemberType::Regular,
+            member_status: MemberStatus::Current,
+            partner: None,
+            expiration_date: None,
+            member_since: NaiveDate::from_ymd_opt(1999, 5, 1),
+            address: Some(Address {
+                user_id: Some(7),
+                street_address: Some("1 Main St, Apt 2".to_string()),
+                street_address_2: None,
+                city: Some("Jackson Center".to_string()),
+                state: Some("OH".to_string()),
+                zip_code: Some("45334".to_string()),
+                country: None,
+            }),
+            clubs: vec![],
+            brns: vec!["123".to_string(), "456".to_string()],
+        };
+        let mut buf = Vec::new();
+        write_directory_csv(&mut buf, [record]).unwrap();
+        let csv = String::from_utf8(buf).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(DIRECTORY_COLUMNS.join(",").as_str()));
+        assert_eq!(
+            lines.next(),
+            Some(
+                r#"7,pat@example.com,"Pat ""PJ""","Smith, Jr.",lifetime,regular,current,,,,1999-05-01,"1 Main St, Apt 2",,Jackson Center,OH,45334,,,,123;456"#
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
 
-        print_json(&members)
+    #[test]
+    fn test_join_keeps_positions() {
+        let numbers = [Some(12), None, Some(34)];
+        let names = [Some("Buckeye"), Some("Unnumbered"), None];
+        assert_eq!(
+            join(numbers.iter().map(|n| n.map(|n| n.to_string()))),
+            "12;;34"
+        );
+        assert_eq!(
+            join(names.iter().map(|name| name.map(str::to_string))),
+            "Buckeye;Unnumbered;"
+        );
+        assert_eq!(join(std::iter::empty()), "");
+        assert_eq!(
+            join(
+                ["A;B", "C\\D"]
+                    .into_iter()
+                    .map(|name| Some(name.to_string()))
+            ),
+            "A\\;B;C\\\\D"
+        );
     }
 }
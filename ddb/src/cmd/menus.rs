@@ -0,0 +1,22 @@
+use super::{Output, Result, connect_from_env};
+use aci_ddb::menus;
+
+/// Export a Drupal menu as a nested tree
+///
+/// Examples:
+///   aci-ddb menus main
+///   aci-ddb menus footer
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Drupal menu machine name (e.g. main, footer, microsites)
+    #[arg(default_value = "main")]
+    pub menu_name: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let tree = menus::tree(&db, &self.menu_name).await?;
+        output.print_json(&tree)
+    }
+}
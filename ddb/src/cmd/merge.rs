@@ -0,0 +1,212 @@
+use super::{Output, Result};
+use anyhow::{Context, bail};
+use serde::{
+    Serializer as _,
+    de::{self, DeserializeSeed, SeqAccess, Visitor},
+    ser::SerializeSeq,
+};
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+};
+
+/// Concatenate sharded exports into one JSON array
+///
+/// Every part must hold a JSON array of records, as written by e.g.
+/// `users all --shard I/N`. Parts are streamed record by record in the order
+/// given; a record whose `--key` was already written is dropped, so
+/// overlapping parts merge cleanly. Output options such as `--fields` were
+/// applied when the parts were exported and are not applied again.
+///
+/// Examples:
+///   aci-ddb merge users.part*.json --out users.json
+///   aci-ddb merge clubs.a.json clubs.b.json --key number > clubs.json
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Part files, merged in this order
+    #[arg(required = true)]
+    parts: Vec<PathBuf>,
+    /// Field identifying a record across parts
+    #[arg(long, default_value = "uid")]
+    key: String,
+    /// Write the merged array to this file instead of stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub async fn run(&self, _output: &Output) -> Result {
+        if let Some(out) = &self.out
+            && self.parts.contains(out)
+        {
+            bail!("{} is both a part and the output", out.display());
+        }
+        let stats = match &self.out {
+            Some(out) => {
+                let file =
+                    File::create(out).with_context(|| format!("creating {}", out.display()))?;
+                self.merge_to(file)?
+            }
+            None => self.merge_to(io::stdout().lock())?,
+        };
+        eprintln!(
+            "merged {} records from {} parts ({} duplicates dropped)",
+            stats.written,
+            self.parts.len(),
+            stats.duplicates
+        );
+        Ok(())
+    }
+
+    fn merge_to(&self, writer: impl Write) -> Result<MergeStats> {
+        let parts = self.parts.iter().map(|path| {
+            let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+            Ok((path.display().to_string(), BufReader::new(file)))
+        });
+        merge(parts, &self.key, writer)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct MergeStats {
+    written: usize,
+    duplicates: usize,
+}
+
+/// Write the records of every `(name, reader)` part to `writer` as one pretty
+/// JSON array, keeping the first record for each `key`
+fn merge<R: Read>(
+    parts: impl IntoIterator<Item = Result<(String, R)>>,
+    key: &str,
+    writer: impl Write,
+) -> Result<MergeStats> {
+    let mut writer = BufWriter::new(writer);
+    let mut serializer = serde_json::Serializer::pretty(&mut writer);
+    let mut records = serializer.serialize_seq(None)?;
+    let mut seen = HashSet::new();
+    let mut stats = MergeStats::default();
+    for part in parts {
+        let (name, reader) = part?;
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        PartRecords {
+            key,
+            seen: &mut seen,
+            records: &mut records,
+            stats: &mut stats,
+        }
+        .deserialize(&mut deserializer)
+        .and_then(|()| deserializer.end())
+        .with_context(|| format!("reading {name}"))?;
+    }
+    records.end()?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(stats)
+}
+
+/// Copies the records of one part array to the output as they are parsed
+struct PartRecords<'a, S> {
+    key: &'a str,
+    seen: &'a mut HashSet<String>,
+    records: &'a mut S,
+    stats: &'a mut MergeStats,
+}
+
+impl<'de, S: SerializeSeq> DeserializeSeed<'de> for PartRecords<'_, S> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, S: SerializeSeq> Visitor<'de> for PartRecords<'_, S> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON array of records")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        use de::Error as _;
+
+        while let Some(record) = seq.next_element::<Value>()? {
+            let id = match record.get(self.key) {
+                Some(Value::String(id)) => id.clone(),
+                Some(id) if !id.is_null() => id.to_string(),
+                _ => {
+                    return Err(A::Error::custom(format!(
+                        "record without a {:?} field: {record}",
+                        self.key
+                    )));
+                }
+            };
+            if self.seen.insert(id) {
+                self.records
+                    .serialize_element(&record)
+                    .map_err(A::Error::custom)?;
+                self.stats.written += 1;
+            } else {
+                self.stats.duplicates += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merge_parts(key: &str, parts: &[&str]) -> Result<(Value, MergeStats)> {
+        let parts = parts
+            .iter()
+            .enumerate()
+            .map(|(index, part)| Ok((format!("part{index}"), part.as_bytes())));
+        let mut merged = Vec::new();
+        let stats = merge(parts, key, &mut merged)?;
+        Ok((serde_json::from_slice(&merged)?, stats))
+    }
+
+    #[test]
+    fn test_merge_parts() {
+        let (merged, stats) = merge_parts(
+            "uid",
+            &[
+                r#"[{"uid": 2, "email": "b@example.com"}, {"uid": 4}]"#,
+                "[]",
+                r#"[{"uid": 1}, {"uid": 2, "email": "stale@example.com"}]"#,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            merged,
+            json!([{"uid": 2, "email": "b@example.com"}, {"uid": 4}, {"uid": 1}])
+        );
+        assert_eq!(
+            stats,
+            MergeStats {
+                written: 3,
+                duplicates: 1
+            }
+        );
+
+        let (merged, _) = merge_parts("number", &[r#"[{"number": "07"}]"#]).unwrap();
+        assert_eq!(merged, json!([{"number": "07"}]));
+    }
+
+    #[test]
+    fn test_merge_rejects_invalid_parts() {
+        assert!(merge_parts("uid", &[r#"{"uid": 1}"#]).is_err());
+        assert!(merge_parts("uid", &[r#"[{"nid": 1}]"#]).is_err());
+        assert!(merge_parts("uid", &[r#"[{"uid": 1}] [{"uid": 2}]"#]).is_err());
+    }
+}
@@ -1,8 +1,14 @@
 //! Microsite sync commands.
 
-use super::{Result, connect_from_env, print_json};
-use aci_ddb::microsites::{self, ClubMicrosite, MicrositePage};
+use super::{Output, Result, connect_from_env, media_check, warn_unknown_clubs, write_json};
+use aci_ddb::microsites::{self, ClubMicrosite, MicrositePage, PageOptions};
+use anyhow::Context;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Component, Path, PathBuf},
+};
 
+/// Microsite export commands
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
     #[command(subcommand)]
@@ -10,8 +16,8 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
-        self.cmd.run().await
+    pub async fn run(&self, output: &Output) -> Result {
+        self.cmd.run(output).await
     }
 }
 
@@ -21,25 +27,66 @@ pub enum MicrositeCommand {
     List(ListCmd),
     /// Show pages for a specific club
     Pages(PagesCmd),
+    /// List media files changed since a timestamp (for delta downloads)
+    Media(MediaCmd),
+    /// Write all microsites into the new site's clubs/<slug>/ file layout
+    Export(ExportCmd),
+    /// Inventory of all media referenced across microsites, with sizes
+    Assets(AssetsCmd),
+    /// List club pages that are not reachable from the microsite menu
+    Orphans(OrphansCmd),
+    /// Show the structured hero/intro of a club's homepage
+    Layout(LayoutCmd),
+    /// Show a club's microsite menu without page content
+    Menu(MenuCmd),
+    /// List the documents (PDFs etc.) linked from a club's pages
+    Documents(DocumentsCmd),
+    /// List clubs whose title matches no microsite homepage
+    Unmatched(UnmatchedCmd),
+    /// Report club slugs that are shared or not URL-safe
+    ValidateSlugs(ValidateSlugsCmd),
 }
 
 impl MicrositeCommand {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
-            Self::List(cmd) => cmd.run().await,
-            Self::Pages(cmd) => cmd.run().await,
+            Self::List(cmd) => cmd.run(output).await,
+            Self::Pages(cmd) => cmd.run(output).await,
+            Self::Media(cmd) => cmd.run(output).await,
+            Self::Export(cmd) => cmd.run(output).await,
+            Self::Assets(cmd) => cmd.run(output).await,
+            Self::Orphans(cmd) => cmd.run(output).await,
+            Self::Layout(cmd) => cmd.run(output).await,
+            Self::Menu(cmd) => cmd.run(output).await,
+            Self::Documents(cmd) => cmd.run(output).await,
+            Self::Unmatched(cmd) => cmd.run(output).await,
+            Self::ValidateSlugs(cmd) => cmd.run(output).await,
         }
     }
 }
 
 /// List all clubs with microsites
 #[derive(Debug, clap::Args)]
-pub struct ListCmd;
+pub struct ListCmd {
+    /// Only list these club numbers (comma separated, e.g. 1,7,42)
+    #[arg(long, value_delimiter = ',')]
+    clubs: Vec<i64>,
+
+    /// Leave out intraclubs (e.g. for the public club directory)
+    #[arg(long)]
+    exclude_intraclubs: bool,
+}
 
 impl ListCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let pool = connect_from_env().await?;
-        let clubs: Vec<ClubMicrosite> = microsites::clubs_with_microsites(&pool).await?;
+        let clubs: Vec<ClubMicrosite> = if self.clubs.is_empty() {
+            microsites::clubs_with_microsites_filtered(&pool, !self.exclude_intraclubs).await?
+        } else {
+            let clubs = microsites::clubs_with_microsites_in(&pool, &self.clubs).await?;
+            warn_unknown_clubs(&self.clubs, clubs.iter().map(|c| c.club_number));
+            clubs
+        };
 
         #[derive(serde::Serialize)]
         struct ClubInfo {
@@ -48,9 +95,11 @@ impl ListCmd {
             club_name: String,
             homepage_nid: u64,
             is_intraclub: bool,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            parent_club_nid: Option<u64>,
         }
 
-        let output: Vec<_> = clubs
+        let club_infos: Vec<_> = clubs
             .into_iter()
             .map(|c| ClubInfo {
                 club_nid: c.club_nid,
@@ -58,10 +107,11 @@ impl ListCmd {
                 club_name: c.club_name,
                 homepage_nid: c.homepage_nid,
                 is_intraclub: c.is_intraclub,
+                parent_club_nid: c.parent_club_nid,
             })
             .collect();
 
-        print_json(&output)
+        output.print_json(&club_infos)
     }
 }
 
@@ -75,59 +125,526 @@ pub struct PagesCmd {
     /// Club node ID to fetch pages for (intraclubs or by nid)
     #[arg(long, group = "selector")]
     nid: Option<u64>,
+
+    /// Include unpublished pages and disabled menu links (for previewing drafts)
+    #[arg(long)]
+    include_unpublished: bool,
+
+    /// Use the body of the latest published revision when it differs from the default revision
+    #[arg(long)]
+    latest_published_revision: bool,
+
+    /// Fix Windows-1252 mojibake (e.g. `â€™`) in page titles and bodies
+    #[arg(long)]
+    repair_encoding: bool,
+
+    /// Also list the summary, body, field_body and featured content separately
+    #[arg(long)]
+    body_sources: bool,
 }
 
 impl PagesCmd {
-    pub async fn run(&self) -> Result {
+    fn page_options(&self) -> PageOptions {
+        PageOptions {
+            include_unpublished: self.include_unpublished,
+            latest_published_revision: self.latest_published_revision,
+            repair_encoding: self.repair_encoding,
+            body_sources: self.body_sources,
+        }
+    }
+
+    pub async fn run(&self, output: &Output) -> Result {
         let pool = connect_from_env().await?;
 
-        // Find the club's homepage
-        let clubs: Vec<ClubMicrosite> = microsites::clubs_with_microsites(&pool).await?;
-        let club = if let Some(club_num) = self.club {
-            clubs
-                .into_iter()
-                .find(|c| c.club_number == Some(club_num))
-                .ok_or_else(|| anyhow::anyhow!("Club {} not found or has no microsite", club_num))?
-        } else if let Some(nid) = self.nid {
-            clubs
-                .into_iter()
-                .find(|c| c.club_nid == nid)
-                .ok_or_else(|| anyhow::anyhow!("Club nid {} not found or has no microsite", nid))?
-        } else {
-            anyhow::bail!("Either --club or --nid is required")
-        };
+        let club = find_club(&pool, self.club, self.nid).await?;
 
         // Fetch pages
         let pages: Vec<MicrositePage> =
-            microsites::pages_for_club(&pool, club.homepage_nid).await?;
+            microsites::pages_for_club_opts(&pool, club.homepage_nid, &self.page_options()).await?;
 
         #[derive(serde::Serialize)]
         struct PageInfo {
             nid: u64,
             title: String,
             status: bool,
+            changed: i64,
             menu_title: Option<String>,
             menu_weight: Option<i32>,
+            parent_nid: Option<u64>,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            last_author_uid: Option<u64>,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            last_author_name: Option<String>,
             body_length: usize,
+            word_count: usize,
+            reading_time_minutes: usize,
             media_urls: Vec<String>,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            body_sources: Option<microsites::BodySources>,
         }
 
-        let output: Vec<_> = pages
+        let page_infos: Vec<_> = pages
             .into_iter()
             .map(|p| {
                 let media_urls = microsites::extract_media_urls(&p.body_html);
+                let word_count = p.word_count();
+                let reading_time_minutes = p.reading_time_minutes();
                 PageInfo {
                     nid: p.nid,
                     title: p.title,
                     status: p.status,
+                    changed: p.changed,
                     menu_title: p.menu_title,
                     menu_weight: p.menu_weight,
+                    parent_nid: p.parent_nid,
+                    last_author_uid: p.last_author_uid,
+                    last_author_name: p.last_author_name,
                     body_length: p.body_html.len(),
+                    word_count,
+                    reading_time_minutes,
                     media_urls,
+                    body_sources: p.body_sources,
                 }
             })
             .collect();
 
-        print_json(&output)
+        output.print_json(&page_infos)
+    }
+}
+
+/// Find a club's microsite by club number or club node ID
+async fn find_club(db: &aci_ddb::Db, club: Option<i64>, nid: Option<u64>) -> Result<ClubMicrosite> {
+    let clubs: Vec<ClubMicrosite> = microsites::clubs_with_microsites(db).await?;
+    if let Some(club_num) = club {
+        clubs
+            .into_iter()
+            .find(|c| c.club_number == Some(club_num))
+            .ok_or_else(|| anyhow::anyhow!("Club {} not found or has no microsite", club_num))
+    } else if let Some(nid) = nid {
+        clubs
+            .into_iter()
+            .find(|c| c.club_nid == nid)
+            .ok_or_else(|| anyhow::anyhow!("Club nid {} not found or has no microsite", nid))
+    } else {
+        anyhow::bail!("Either --club or --nid is required")
+    }
+}
+
+/// List pages that belong to a club's microsite but are missing from its menu
+///
+/// These pages are skipped by `pages` and `export`; review them to decide
+/// whether they should be migrated or dropped.
+#[derive(Debug, clap::Args)]
+pub struct OrphansCmd {
+    /// Club number to check (regular clubs)
+    #[arg(short, long, group = "selector")]
+    club: Option<i64>,
+
+    /// Club node ID to check (intraclubs or by nid)
+    #[arg(long, group = "selector")]
+    nid: Option<u64>,
+}
+
+impl OrphansCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let club = find_club(&pool, self.club, self.nid).await?;
+        let pages = microsites::orphan_pages(&pool, club.homepage_nid).await?;
+
+        #[derive(serde::Serialize)]
+        struct OrphanInfo {
+            nid: u64,
+            title: String,
+            status: bool,
+            word_count: usize,
+        }
+
+        let orphans: Vec<_> = pages
+            .into_iter()
+            .map(|p| OrphanInfo {
+                word_count: p.word_count(),
+                nid: p.nid,
+                title: p.title,
+                status: p.status,
+            })
+            .collect();
+
+        output.print_json(&orphans)
+    }
+}
+
+/// List clubs without a title-matched microsite homepage
+///
+/// Clubs covered by a manual override are left out, so any club listed
+/// either has no microsite or needs an override added. Pass
+/// `--no-manual-overrides` to also list the overridden clubs.
+#[derive(Debug, clap::Args)]
+pub struct UnmatchedCmd {
+    /// Ignore the manual club -> homepage overrides
+    #[arg(long)]
+    no_manual_overrides: bool,
+}
+
+impl UnmatchedCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let clubs = microsites::unmatched_clubs(&pool, !self.no_manual_overrides).await?;
+
+        output.print_json(&clubs)
+    }
+}
+
+/// Report club slugs the new site can't route
+///
+/// Lists slugs used by more than one club (compared ignoring case) and slugs
+/// with anything but lowercase letters, digits and hyphens, with a suggested
+/// replacement. An empty list means every club slug is usable as is.
+///
+/// Examples:
+///   aci-ddb microsites validate-slugs
+#[derive(Debug, clap::Args)]
+pub struct ValidateSlugsCmd {}
+
+impl ValidateSlugsCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let issues = microsites::validate_slugs(&pool).await?;
+
+        output.print_json(&issues)
+    }
+}
+
+/// Show a club homepage's hero image, headline, intro and call-to-action buttons
+#[derive(Debug, clap::Args)]
+pub struct LayoutCmd {
+    /// Club number (regular clubs)
+    #[arg(short, long, group = "selector")]
+    club: Option<i64>,
+
+    /// Club node ID (intraclubs or by nid)
+    #[arg(long, group = "selector")]
+    nid: Option<u64>,
+}
+
+impl LayoutCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let club = find_club(&pool, self.club, self.nid).await?;
+        let layout = microsites::homepage_layout(&pool, club.homepage_nid).await?;
+
+        output.print_json(&layout)
     }
 }
+
+/// Show the nested navigation of a club microsite (labels, weights, target nodes)
+#[derive(Debug, clap::Args)]
+pub struct MenuCmd {
+    /// Club number (regular clubs)
+    #[arg(short, long, group = "selector")]
+    club: Option<i64>,
+
+    /// Club node ID (intraclubs or by nid)
+    #[arg(long, group = "selector")]
+    nid: Option<u64>,
+}
+
+impl MenuCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let club = find_club(&pool, self.club, self.nid).await?;
+        let menu = microsites::menu(&pool, club.homepage_nid).await?;
+
+        output.print_json(&menu)
+    }
+}
+
+/// List the downloadable documents of a club microsite (bylaws, newsletters)
+///
+/// Files linked from page bodies, except images, with their title (link
+/// text), size and mime type.
+#[derive(Debug, clap::Args)]
+pub struct DocumentsCmd {
+    /// Club number (regular clubs)
+    #[arg(short, long, group = "selector")]
+    club: Option<i64>,
+
+    /// Club node ID (intraclubs or by nid)
+    #[arg(long, group = "selector")]
+    nid: Option<u64>,
+}
+
+impl DocumentsCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let club = find_club(&pool, self.club, self.nid).await?;
+        let documents = microsites::documents(&pool, club.homepage_nid).await?;
+
+        output.print_json(&documents)
+    }
+}
+
+/// List media files uploaded or modified since a timestamp
+///
+/// With `--since-file`, the timestamp is read from the file (missing file =
+/// everything) and the file is updated with this run's start time after a
+/// successful export, so repeated runs only list new or changed files.
+#[derive(Debug, clap::Args)]
+pub struct MediaCmd {
+    /// Unix timestamp; list files changed at or after it
+    #[arg(long, group = "since_source")]
+    since: Option<i64>,
+
+    /// File holding the unix timestamp of the previous run
+    #[arg(long, group = "since_source")]
+    since_file: Option<std::path::PathBuf>,
+}
+
+impl MediaCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let started = chrono::Utc::now().timestamp();
+        let since = match (&self.since, &self.since_file) {
+            (Some(since), _) => *since,
+            (None, Some(path)) if path.exists() => std::fs::read_to_string(path)
+                .with_context(|| format!("reading {}", path.display()))?
+                .trim()
+                .parse()
+                .with_context(|| format!("parsing timestamp in {}", path.display()))?,
+            _ => 0,
+        };
+
+        let pool = connect_from_env().await?;
+        let files = microsites::changed_media_since(&pool, since).await?;
+        output.print_json(&files)?;
+
+        if let Some(path) = &self.since_file {
+            std::fs::write(path, format!("{started}\n"))
+                .with_context(|| format!("writing {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Write microsites into the layout of the new site:
+///
+///   clubs/<slug>/index.json        club info and page list
+///   clubs/<slug>/pages/<nid>.json  one file per page
+///   clubs/<slug>/media.json        media URLs referenced by the pages
+///
+/// Slugs come from the homepage path aliases; clubs without one are skipped
+/// with a warning.
+#[derive(Debug, clap::Args)]
+pub struct ExportCmd {
+    /// Directory to write the clubs/ tree into
+    #[arg(long)]
+    output_dir: PathBuf,
+
+    /// Include unpublished pages and disabled menu links
+    #[arg(long)]
+    include_unpublished: bool,
+
+    /// Use the body of the latest published revision when it differs from the default revision
+    #[arg(long)]
+    latest_published_revision: bool,
+
+    /// Fix Windows-1252 mojibake (e.g. `â€™`) in page titles and bodies
+    #[arg(long)]
+    repair_encoding: bool,
+
+    /// Also list the summary, body, field_body and featured content separately
+    #[arg(long)]
+    body_sources: bool,
+}
+
+impl ExportCmd {
+    fn page_options(&self) -> PageOptions {
+        PageOptions {
+            include_unpublished: self.include_unpublished,
+            latest_published_revision: self.latest_published_revision,
+            repair_encoding: self.repair_encoding,
+            body_sources: self.body_sources,
+        }
+    }
+
+    pub async fn run(&self, _output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let clubs = microsites::clubs_with_microsites(&pool).await?;
+        let slugs: HashMap<u64, String> = microsites::club_slugs(&pool)
+            .await?
+            .into_iter()
+            .map(|slug| (slug.club_nid, slug.slug))
+            .collect();
+
+        #[derive(serde::Serialize)]
+        struct PageRef<'a> {
+            nid: u64,
+            title: &'a str,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            parent_nid: Option<u64>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct ClubIndex<'a> {
+            #[serde(flatten)]
+            club: &'a ClubMicrosite,
+            slug: &'a str,
+            pages: Vec<PageRef<'a>>,
+        }
+
+        let (mut club_count, mut page_count) = (0, 0);
+        for club in &clubs {
+            let Some(slug) = slugs.get(&club.club_nid).filter(|slug| is_safe_slug(slug)) else {
+                eprintln!(
+                    "warning: skipping {} (nid {}): no usable slug",
+                    club.club_name, club.club_nid
+                );
+                continue;
+            };
+            let club_dir = self.output_dir.join("clubs").join(slug);
+            let pages_dir = club_dir.join("pages");
+            std::fs::create_dir_all(&pages_dir)
+                .with_context(|| format!("creating {}", pages_dir.display()))?;
+
+            let pages: Vec<MicrositePage> =
+                microsites::pages_for_club_opts(&pool, club.homepage_nid, &self.page_options())
+                    .await?;
+
+            for page in &pages {
+                write_json(&pages_dir.join(format!("{}.json", page.nid)), page)?;
+            }
+            let media = microsites::collect_page_media(&pages);
+
+            let index = ClubIndex {
+                club,
+                slug,
+                pages: pages
+                    .iter()
+                    .map(|page| PageRef {
+                        nid: page.nid,
+                        title: &page.title,
+                        parent_nid: page.parent_nid,
+                    })
+                    .collect(),
+            };
+            write_json(&club_dir.join("index.json"), &index)?;
+            write_json(&club_dir.join("media.json"), &media)?;
+
+            club_count += 1;
+            page_count += pages.len();
+        }
+
+        eprintln!(
+            "wrote {club_count} clubs and {page_count} pages to {}",
+            self.output_dir.join("clubs").display()
+        );
+        Ok(())
+    }
+}
+
+/// Count and size every asset referenced across all microsites
+///
+/// URLs are matched to `file_managed` for sizes and mime types; URLs without a
+/// managed file are listed under `missing`.
+///
+/// With `--validate-media`, every URL is also requested (HEAD) from the live
+/// site and its HTTP status reported under `validation`, so broken images
+/// show up before migration rather than after.
+///
+/// Example:
+///   aci-ddb microsites assets --validate-media --base-url https://airstreamclub.org
+#[derive(Debug, clap::Args)]
+pub struct AssetsCmd {
+    /// Check that each URL resolves on the live site
+    #[arg(long, requires = "base_url")]
+    validate_media: bool,
+
+    /// Site that relative media URLs are resolved against
+    #[arg(long)]
+    base_url: Option<String>,
+
+    /// Requests in flight at once
+    #[arg(long, default_value_t = 8)]
+    concurrency: usize,
+
+    /// Requests per second at most
+    #[arg(long, default_value = "10")]
+    rate: std::num::NonZeroU32,
+}
+
+impl AssetsCmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let pool = connect_from_env().await?;
+        let clubs = microsites::all_pages(&pool, &PageOptions::default()).await?;
+        let pages: Vec<MicrositePage> = clubs.into_iter().flat_map(|club| club.pages).collect();
+        let urls = microsites::collect_page_media(&pages);
+
+        let uris: Vec<String> = urls
+            .iter()
+            .filter_map(|url| microsites::media_url_to_uri(url))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let files = microsites::files_by_uri(&pool, &uris).await?;
+        let found: HashSet<&str> = files.iter().map(|file| file.uri.as_str()).collect();
+
+        #[derive(serde::Serialize)]
+        struct AssetInventory {
+            file_count: usize,
+            total_bytes: u64,
+            by_mime: BTreeMap<String, usize>,
+            missing: Vec<String>,
+            urls: Vec<String>,
+            #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+            validation: Option<Validation>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Validation {
+            by_status: BTreeMap<&'static str, usize>,
+            statuses: Vec<media_check::MediaStatus>,
+        }
+
+        let mut by_mime = BTreeMap::new();
+        for file in &files {
+            let mime = file
+                .filemime
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
+            *by_mime.entry(mime).or_default() += 1;
+        }
+        let missing = urls
+            .iter()
+            .filter(|url| {
+                microsites::media_url_to_uri(url).is_none_or(|uri| !found.contains(uri.as_str()))
+            })
+            .cloned()
+            .collect();
+        let validation = match (&self.base_url, self.validate_media) {
+            (Some(base_url), true) => {
+                let statuses =
+                    media_check::check(&urls, base_url, self.concurrency, self.rate).await?;
+                Some(Validation {
+                    by_status: media_check::status_counts(&statuses),
+                    statuses,
+                })
+            }
+            _ => None,
+        };
+
+        output.print_json(&AssetInventory {
+            file_count: files.len(),
+            total_bytes: files.iter().filter_map(|file| file.filesize).sum(),
+            by_mime,
+            missing,
+            urls,
+            validation,
+        })
+    }
+}
+
+/// Path aliases become directory names, so only plain relative paths are allowed
+fn is_safe_slug(slug: &str) -> bool {
+    !slug.is_empty()
+        && Path::new(slug)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
@@ -1,11 +1,78 @@
 pub type Result<T = ()> = anyhow::Result<T>;
 
+use aci_ddb::{Db, Limiter, RetryPolicy, SslMode, SslOptions};
 use anyhow::Context;
-use sqlx::{Executor, MySqlPool};
+use profile::Profile;
+use sqlx::{
+    Executor,
+    mysql::{MySqlConnectOptions, MySqlPoolOptions},
+};
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    path::PathBuf,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
-pub async fn connect_from_env() -> Result<MySqlPool> {
-    let url = std::env::var("ACI__DDB_URL").context("DDB_DB_URL environment variable not set")?;
-    let pool = MySqlPool::connect(&url).await.context("opening database")?;
+/// Query rate limit from `--max-qps`, shared by every pool the command opens
+static LIMITER: OnceLock<Limiter> = OnceLock::new();
+
+/// Retry policy from `--retries` and `--retry-delay`, for every pool the
+/// command opens
+static RETRY_POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Set by `--in-chunk-size` for every connection
+static IN_CHUNK_SIZE: OnceLock<NonZeroUsize> = OnceLock::new();
+
+/// Profile selected with `--profile`
+static PROFILE: OnceLock<Profile> = OnceLock::new();
+
+/// Set by `--warm`; cleared once the first pool has been warmed
+static WARM: AtomicBool = AtomicBool::new(false);
+
+/// Connect using the `ACI__DDB_*` environment variables, falling back to
+/// the `--profile` settings for any that are unset
+pub async fn connect_from_env() -> Result<Db> {
+    let profile = PROFILE.get();
+    let url = std::env::var("ACI__DDB_URL")
+        .ok()
+        .or_else(|| profile.and_then(|profile| profile.url.clone()))
+        .context(
+            "no database URL: set ACI__DDB_URL or pick a --profile with a `url` in the config file",
+        )?;
+    let ssl = SslOptions {
+        ca: std::env::var_os("ACI__DDB_SSL_CA")
+            .map(Into::into)
+            .or_else(|| profile.and_then(|profile| profile.ssl_ca.clone())),
+        mode: std::env::var("ACI__DDB_SSL_MODE")
+            .ok()
+            .or_else(|| profile.and_then(|profile| profile.ssl_mode.clone()))
+            .map(|mode| mode.parse::<SslMode>())
+            .transpose()
+            .map_err(anyhow::Error::msg)
+            .context("parsing ACI__DDB_SSL_MODE")?,
+    };
+    let options = ssl.apply(
+        url.parse::<MySqlConnectOptions>()
+            .context("parsing ACI__DDB_URL")?,
+    );
+    let mut pool_options = MySqlPoolOptions::new();
+    if let Some(max) = profile.and_then(|profile| profile.max_connections) {
+        pool_options = pool_options.max_connections(max);
+    }
+    if let Some(min) = profile.and_then(|profile| profile.min_connections) {
+        pool_options = pool_options.min_connections(min);
+    }
+    if let Some(limiter) = LIMITER.get() {
+        pool_options = limiter.apply(pool_options);
+    }
+    let pool = pool_options
+        .connect_with(options)
+        .await
+        .context("opening database")?;
     let _ = pool
         .execute(
             r#"
@@ -15,31 +82,152 @@ pub async fn connect_from_env() -> Result<MySqlPool> {
         )
         .await
         .context("preparing database caches")?;
-    Ok(pool)
+    let mut db = Db::new(pool).with_retry_policy(RETRY_POLICY.get().copied().unwrap_or_default());
+    if let Some(&size) = IN_CHUNK_SIZE.get() {
+        db = db.with_in_chunk_size(size);
+    }
+    if WARM.swap(false, Ordering::Relaxed) {
+        aci_ddb::prefetch(&db)
+            .await
+            .context("warming database caches")?;
+    }
+    Ok(db)
 }
 
+/// Commands `--count-only` works with, for the error on any other.
+///
+/// These are the full list exports, the ones big enough to be worth checking
+/// first. Lookups return at most one record, and the filtered sub-exports
+/// (e.g. `users dormant`) would each need their own count query kept in step
+/// with their filters.
+const COUNT_ONLY_COMMANDS: &str =
+    "`users all`, `members all`, `clubs`, `events`, `regions` and `standing-committees`";
+
+fn count_only_unsupported() -> anyhow::Error {
+    anyhow::anyhow!("--count-only only works with {COUNT_ONLY_COMMANDS}")
+}
+
+/// Print `{"<entity>": count}` for `--count-only`
+fn print_count(output: &Output, entity: &str, count: u64) -> Result {
+    output.print_json(&std::collections::BTreeMap::from([(entity, count)]))
+}
+
+/// Warn about `--clubs` numbers that matched nothing; the export carries on
+/// with the clubs that were found.
+pub fn warn_unknown_clubs(requested: &[i64], found: impl IntoIterator<Item = Option<i64>>) {
+    let found: std::collections::HashSet<i64> = found.into_iter().flatten().collect();
+    for number in requested.iter().filter(|number| !found.contains(number)) {
+        eprintln!("warning: club {number} not found");
+    }
+}
+
+mod anonymize;
 pub mod clubs;
+pub mod diff;
+pub mod dump;
+pub mod events;
+pub mod inspect;
 pub mod international;
+mod media_check;
 pub mod members;
+pub mod menus;
+pub mod merge;
 pub mod microsites;
+mod output;
+pub mod profile;
 pub mod regions;
+pub mod seed;
+pub mod sink;
+mod spinner;
 pub mod standing_committees;
+pub mod taxonomy;
 pub mod users;
 
-pub fn print_json<T: ?Sized + serde::Serialize>(value: &T) -> Result {
-    println!("{}", serde_json::to_string_pretty(value)?);
-    Ok(())
-}
+pub use output::{Output, write_json};
+pub use spinner::Spinner;
 
 #[derive(Debug, clap::Args)]
 pub struct Cmd {
     #[command(subcommand)]
     cmd: DdbCommand,
+
+    #[command(flatten)]
+    output: Output,
+
+    /// Limit database queries per second (a bulk fetch counts as one), to
+    /// stay under the ceiling agreed for the production primary
+    #[arg(long, global = true)]
+    max_qps: Option<NonZeroU32>,
+
+    /// Times to re-run a bulk query that hit a deadlock, lock wait timeout or
+    /// lost connection (default 3; 0 disables retries)
+    #[arg(long, global = true)]
+    retries: Option<u32>,
+
+    /// Milliseconds to wait before the first retry, doubled for each further
+    /// one up to 8 seconds (default 250)
+    #[arg(long, global = true, value_name = "MS")]
+    retry_delay: Option<u64>,
+
+    /// Most IDs bound in one `IN (...)` list; longer ID lists are fetched
+    /// over several queries (default 1000)
+    #[arg(long, global = true, value_name = "N")]
+    in_chunk_size: Option<NonZeroUsize>,
+
+    /// Print how many records the export would return instead of the records.
+    /// Counted with a lean query over the base tables, not the export query;
+    /// `members all` merges members sharing an email after fetching, so its
+    /// count can be slightly high. Works with `users all`, `members all`,
+    /// `clubs`, `events`, `regions` and `standing-committees`.
+    #[arg(long, global = true)]
+    count_only: bool,
+
+    /// Warm MySQL's caches with cheap reads of the hot tables before the
+    /// export starts, to cut cold-start latency in serverless runs
+    #[arg(long, global = true)]
+    warm: bool,
+
+    /// Connection profile from the config file, e.g. `prod` for `[profiles.prod]`
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Config file holding the `--profile` sections
+    #[arg(long, global = true, default_value = "aci-export.toml")]
+    config: PathBuf,
 }
 
 impl Cmd {
     pub async fn run(&self) -> Result {
-        self.cmd.run().await
+        let output = match &self.profile {
+            Some(name) => {
+                let profile = Profile::from_file(&self.config, name)?;
+                let output = profile.apply(&self.output);
+                let _ = PROFILE.set(profile);
+                output
+            }
+            None => self.output.clone(),
+        };
+        if let Some(qps) = self.max_qps {
+            let _ = LIMITER.set(Limiter::per_second(qps));
+        }
+        WARM.store(self.warm, Ordering::Relaxed);
+        if let Some(size) = self.in_chunk_size {
+            let _ = IN_CHUNK_SIZE.set(size);
+        }
+        let default = RetryPolicy::default();
+        let _ = RETRY_POLICY.set(RetryPolicy {
+            attempts: self
+                .retries
+                .map_or(default.attempts, |retries| retries.saturating_add(1)),
+            base_delay: self
+                .retry_delay
+                .map_or(default.base_delay, Duration::from_millis),
+            ..default
+        });
+        if self.count_only {
+            return self.cmd.count(&output).await;
+        }
+        self.cmd.run(&output).await
     }
 }
 
@@ -48,22 +236,81 @@ pub enum DdbCommand {
     Users(users::Cmd),
     Members(members::Cmd),
     Clubs(clubs::Cmd),
+    Events(events::Cmd),
     Regions(regions::Cmd),
     StandingCommittees(standing_committees::Cmd),
     International(international::Cmd),
     Microsites(microsites::Cmd),
+    Menus(menus::Cmd),
+    Taxonomy(taxonomy::Cmd),
+    Dump(dump::Cmd),
+    Diff(diff::Cmd),
+    Merge(merge::Cmd),
+    Seed(seed::Cmd),
+    InspectNode(inspect::Cmd),
+    /// List the exportable entities and the command for each
+    ListEntities,
 }
 
 impl DdbCommand {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
-            Self::Users(cmd) => cmd.run().await,
-            Self::Members(cmd) => cmd.run().await,
-            Self::Clubs(cmd) => cmd.run().await,
-            Self::Regions(cmd) => cmd.run().await,
-            Self::StandingCommittees(cmd) => cmd.run().await,
-            Self::International(cmd) => cmd.run().await,
-            Self::Microsites(cmd) => cmd.run().await,
+            Self::Users(cmd) => cmd.run(output).await,
+            Self::Members(cmd) => cmd.run(output).await,
+            Self::Clubs(cmd) => cmd.run(output).await,
+            Self::Events(cmd) => cmd.run(output).await,
+            Self::Regions(cmd) => cmd.run(output).await,
+            Self::StandingCommittees(cmd) => cmd.run(output).await,
+            Self::International(cmd) => cmd.run(output).await,
+            Self::Microsites(cmd) => cmd.run(output).await,
+            Self::Menus(cmd) => cmd.run(output).await,
+            Self::Taxonomy(cmd) => cmd.run(output).await,
+            Self::Dump(cmd) => cmd.run(output).await,
+            Self::Diff(cmd) => cmd.run(output).await,
+            Self::Merge(cmd) => cmd.run(output).await,
+            Self::Seed(cmd) => cmd.run(output).await,
+            Self::InspectNode(cmd) => cmd.run(output).await,
+            Self::ListEntities => list_entities(),
         }
     }
+
+    /// `--count-only`: print the export's record count instead of running it
+    pub async fn count(&self, output: &Output) -> Result {
+        match self {
+            Self::Users(cmd) => cmd.count(output).await,
+            Self::Members(cmd) => cmd.count(output).await,
+            Self::Clubs(cmd) => cmd.count(output).await,
+            Self::Events(cmd) => cmd.count(output).await,
+            Self::Regions(cmd) => cmd.count(output).await,
+            Self::StandingCommittees(cmd) => cmd.count(output).await,
+            _ => Err(count_only_unsupported()),
+        }
+    }
+}
+
+/// Print the entity commands compiled into this binary, read from the clap
+/// definition of [`DdbCommand`] so the list always matches what can be run.
+fn list_entities() -> Result {
+    use clap::Subcommand;
+
+    let bin = env!("CARGO_BIN_NAME");
+    let command = DdbCommand::augment_subcommands(clap::Command::new(bin));
+    for entity in command.get_subcommands().filter(|entity| {
+        !matches!(
+            entity.get_name(),
+            "list-entities" | "diff" | "merge" | "seed" | "inspect-node"
+        )
+    }) {
+        let about = entity.get_about().map(ToString::to_string);
+        println!("{:<20} {}", entity.get_name(), about.unwrap_or_default());
+
+        let nested: Vec<&str> = entity.get_subcommands().map(|sub| sub.get_name()).collect();
+        let usage = match (nested.is_empty(), entity.is_subcommand_required_set()) {
+            (true, _) => String::new(),
+            (false, true) => format!(" <{}>", nested.join("|")),
+            (false, false) => format!(" [{}]", nested.join("|")),
+        };
+        println!("{:<20} {bin} {}{usage}", "", entity.get_name());
+    }
+    Ok(())
 }
@@ -0,0 +1,607 @@
+//! Output options shared by all commands.
+
+use super::{Result, anonymize::anonymize};
+use anyhow::Context;
+use anyhow::bail;
+use futures::{Stream, StreamExt};
+use serde::{
+    Serialize, Serializer,
+    ser::{Error as _, SerializeMap},
+};
+use serde_json::Value;
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeSet,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    pin::pin,
+    sync::OnceLock,
+};
+
+/// Provenance recorded by `--stamp` and `--envelope`
+const SOURCE: &str = "drupal";
+
+/// Most NDJSON records written between two flushes
+const NDJSON_BATCH: usize = 1024;
+
+/// Export time shared by every record written by this process (RFC 3339)
+fn exported_at() -> &'static str {
+    static EXPORTED_AT: OnceLock<String> = OnceLock::new();
+    EXPORTED_AT
+        .get_or_init(|| chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct Output {
+    /// Only output these top-level fields of each record (comma separated, e.g. uid,email)
+    #[arg(long, global = true, value_delimiter = ',')]
+    pub fields: Vec<String>,
+
+    /// Output missing values as explicit nulls instead of omitting the keys
+    #[arg(long, global = true)]
+    pub explicit_nulls: bool,
+
+    /// Add `_source` and `_exported_at` to each record (or to a single
+    /// top-level object)
+    #[arg(long, global = true)]
+    pub stamp: bool,
+
+    /// Wrap array output in `{exported_at, source, data, count}`
+    #[arg(long, global = true)]
+    pub envelope: bool,
+
+    /// Replace emails, names, birthdays, notes and street addresses with
+    /// stand-ins derived from the user ID, for sharing outside the club
+    #[arg(long, global = true)]
+    pub anonymize: bool,
+}
+
+impl Output {
+    /// Print a value as pretty JSON, applying the output options
+    pub fn print_json<T: ?Sized + Serialize>(&self, value: &T) -> Result {
+        self.serializing(|| self.print_json_value_to(io::stdout().lock(), value))
+    }
+
+    /// Write a value as pretty JSON to `writer`, applying the output options
+    fn print_json_value_to<W: Write, T: ?Sized + Serialize>(
+        &self,
+        mut writer: W,
+        value: &T,
+    ) -> Result {
+        if self.fields.is_empty() && !self.stamp && !self.envelope && !self.anonymize {
+            serde_json::to_writer_pretty(&mut writer, value)?;
+            writeln!(writer)?;
+            return Ok(());
+        }
+        if !self.fields.is_empty() {
+            let all_fields = aci_ddb::ser::with_all_fields(|| serde_json::to_value(value))?;
+            match &all_fields {
+                Value::Array(records) => self.check_fields_of(records.first())?,
+                record => self.check_fields_of(Some(record))?,
+            }
+        }
+        let mut value = serde_json::to_value(value)?;
+        if self.anonymize {
+            // Before projecting, which may drop the user ID the stand-ins derive from
+            anonymize(&mut value);
+        }
+        match self.project(value) {
+            Value::Array(records) => {
+                let output = Self {
+                    fields: vec![],
+                    anonymize: false,
+                    ..self.clone()
+                };
+                output.print_json_iter_to(writer, records)
+            }
+            value => {
+                let stamp = self.stamp && value.is_object();
+                serialize_stamped(
+                    stamp,
+                    value,
+                    &mut serde_json::Serializer::pretty(&mut writer),
+                )?;
+                writeln!(writer)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Write records as a pretty JSON array to `writer`, applying the output
+    /// options, without building the whole document in memory first
+    pub fn print_json_to<W: Write, T: Serialize>(&self, writer: W, rows: &[T]) -> Result {
+        self.print_json_iter_to(writer, rows)
+    }
+
+    /// Streaming variant of [`Self::print_json_to`] that serializes records
+    /// as the iterator yields them.
+    ///
+    /// `--fields` is checked against the first record before anything is
+    /// written. With `--envelope` the `count` follows `data`, since it is only
+    /// known once every record has been seen.
+    pub fn print_json_iter_to<W, I>(&self, writer: W, rows: I) -> Result
+    where
+        W: Write,
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        self.serializing(|| self.write_json_records(writer, rows))
+    }
+
+    fn write_json_records<W, I>(&self, writer: W, rows: I) -> Result
+    where
+        W: Write,
+        I: IntoIterator,
+        I::Item: Serialize,
+    {
+        let mut rows = rows.into_iter().peekable();
+        self.check_fields_of(rows.peek())?;
+        let mut writer = io::BufWriter::new(writer);
+        let count = Cell::new(0);
+        let rows = rows.map(|row| {
+            count.set(count.get() + 1);
+            Projected {
+                fields: &self.fields,
+                stamp: self.stamp,
+                anonymize: self.anonymize,
+                row,
+            }
+        });
+        let mut serializer = serde_json::Serializer::pretty(&mut writer);
+        if self.envelope {
+            let mut envelope = serializer.serialize_map(None)?;
+            envelope.serialize_entry("exported_at", exported_at())?;
+            envelope.serialize_entry("source", SOURCE)?;
+            envelope.serialize_entry("data", &Records(RefCell::new(Some(rows))))?;
+            envelope.serialize_entry("count", &count.get())?;
+            envelope.end()?;
+        } else {
+            serializer.collect_seq(rows)?;
+        }
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write records as newline-delimited JSON (one compact record per line)
+    /// as the stream yields them, applying the output options.
+    ///
+    /// Rows that arrive together are written as one batch, and the output is
+    /// flushed whenever the stream has to wait for more, so a reader sees the
+    /// first records as soon as the database sends them. `--envelope` needs
+    /// the complete array and is rejected. `--fields` is checked against the
+    /// first record before it is written.
+    pub async fn print_ndjson_stream_to<W, S, T>(&self, writer: W, rows: S) -> Result
+    where
+        W: Write,
+        S: Stream<Item = aci_ddb::Result<T>>,
+        T: Serialize,
+    {
+        if self.envelope {
+            bail!("--envelope doesn't apply to NDJSON output");
+        }
+        let mut writer = BufWriter::new(writer);
+        let mut checked = false;
+        let mut batches = pin!(rows.ready_chunks(NDJSON_BATCH));
+        while let Some(batch) = batches.next().await {
+            for row in batch {
+                let row = row?;
+                if !checked {
+                    self.check_fields_of(Some(&row))?;
+                    checked = true;
+                }
+                let row = Projected {
+                    fields: &self.fields,
+                    stamp: self.stamp,
+                    anonymize: self.anonymize,
+                    row,
+                };
+                self.serializing(|| serde_json::to_writer(&mut writer, &row))?;
+                writeln!(writer)?;
+            }
+            writer.flush()?;
+        }
+        if !checked {
+            self.check_fields_of(None::<&T>)?;
+        }
+        Ok(())
+    }
+
+    /// Run `f`, which serializes records, with `--explicit-nulls` applied
+    fn serializing<R>(&self, f: impl FnOnce() -> R) -> R {
+        if self.explicit_nulls {
+            aci_ddb::ser::with_explicit_nulls(f)
+        } else {
+            f()
+        }
+    }
+
+    /// Restrict records to the `--fields` allowlist, which must have been
+    /// checked with [`Self::check_fields_of`].
+    ///
+    /// Records are either the top-level object or each object in a top-level
+    /// array.
+    fn project(&self, value: Value) -> Value {
+        if self.fields.is_empty() {
+            return value;
+        }
+        match value {
+            Value::Object(map) => keep(&self.fields, map),
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::Object(map) => keep(&self.fields, map),
+                        other => other,
+                    })
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Fail if a requested field is not declared by the type of `record`,
+    /// whether or not this record has a value for it.
+    ///
+    /// All records of an output share a type, so any one of them will do.
+    /// Without a record there is nothing to check against, which is only
+    /// warned about since nothing would be output anyway.
+    fn check_fields_of<T: ?Sized + Serialize>(&self, record: Option<&T>) -> Result {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        let Some(record) = record else {
+            eprintln!(
+                "warning: no records, so --fields {} could not be checked",
+                self.fields.join(",")
+            );
+            return Ok(());
+        };
+        match aci_ddb::ser::with_all_fields(|| serde_json::to_value(record))? {
+            Value::Object(map) => self.check_fields(map.keys().map(String::as_str).collect()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail if a requested field is not among the `known` record fields
+    fn check_fields(&self, known: BTreeSet<&str>) -> Result {
+        let unknown: Vec<&str> = self
+            .fields
+            .iter()
+            .map(String::as_str)
+            .filter(|field| !known.contains(field))
+            .collect();
+        if !unknown.is_empty() {
+            bail!(
+                "unknown field(s) {}; available fields: {}",
+                unknown.join(", "),
+                known.into_iter().collect::<Vec<_>>().join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Write a value as pretty JSON to a new file at `path`
+pub fn write_json<T: ?Sized + Serialize>(path: &Path, value: &T) -> Result {
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, value)
+        .with_context(|| format!("writing {}", path.display()))?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn keep(fields: &[String], record: serde_json::Map<String, Value>) -> Value {
+    Value::Object(
+        record
+            .into_iter()
+            .filter(|(key, _)| fields.contains(key))
+            .collect(),
+    )
+}
+
+/// A record projected to the `--fields` allowlist (and anonymized) while it
+/// is serialized
+struct Projected<'a, T> {
+    fields: &'a [String],
+    stamp: bool,
+    anonymize: bool,
+    row: T,
+}
+
+impl<T: Serialize> Serialize for Projected<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if self.fields.is_empty() && !self.anonymize && !self.stamp {
+            return self.row.serialize(serializer);
+        }
+        let mut value = serde_json::to_value(&self.row).map_err(S::Error::custom)?;
+        if self.anonymize {
+            // Before projecting, which may drop the user ID the stand-ins derive from
+            anonymize(&mut value);
+        }
+        match value {
+            Value::Object(map) if !self.fields.is_empty() => {
+                serialize_stamped(self.stamp, keep(self.fields, map), serializer)
+            }
+            Value::Object(map) => serialize_stamped(self.stamp, map, serializer),
+            // Only objects have room for the stamp
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+/// A record followed by the `--stamp` provenance fields
+#[derive(Serialize)]
+struct Stamped<T> {
+    #[serde(flatten)]
+    record: T,
+    #[serde(rename = "_source")]
+    source: &'static str,
+    #[serde(rename = "_exported_at")]
+    exported_at: &'static str,
+}
+
+/// Serialize `record`, stamped if `stamp` is set. Stamped records must
+/// serialize as a map (any struct or JSON object).
+fn serialize_stamped<T: Serialize, S: Serializer>(
+    stamp: bool,
+    record: T,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    if !stamp {
+        return record.serialize(serializer);
+    }
+    Stamped {
+        record,
+        source: SOURCE,
+        exported_at: exported_at(),
+    }
+    .serialize(serializer)
+}
+
+/// Serializes the records of an iterator as a sequence, once
+struct Records<I>(RefCell<Option<I>>);
+
+impl<I> Serialize for Records<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let rows = self.0.borrow_mut().take();
+        serializer.collect_seq(rows.ok_or_else(|| S::Error::custom("records already written"))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn output(fields: &[&str]) -> Output {
+        Output {
+            fields: fields.iter().map(|f| f.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_project_fields() {
+        let value = json!([
+            {"uid": 1, "email": "a@example.com", "first_name": "A"},
+            {"uid": 2, "email": "b@example.com"}
+        ]);
+        let projected = output(&["uid", "email"]).project(value);
+        assert_eq!(
+            projected,
+            json!([
+                {"uid": 1, "email": "a@example.com"},
+                {"uid": 2, "email": "b@example.com"}
+            ])
+        );
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        uid: u64,
+        #[serde(skip_serializing_if = "aci_ddb::ser::skip_none")]
+        email: Option<String>,
+        #[serde(skip_serializing_if = "aci_ddb::ser::skip_empty")]
+        brns: Vec<String>,
+    }
+
+    #[test]
+    fn test_check_fields_against_the_record_type() {
+        let record = Record {
+            uid: 1,
+            email: None,
+            brns: vec![],
+        };
+        output(&["uid", "email", "brns"])
+            .check_fields_of(Some(&record))
+            .unwrap();
+        let err = output(&["uid", "emial"])
+            .check_fields_of(Some(&record))
+            .unwrap_err();
+        assert!(err.to_string().contains("emial"));
+        output(&["emial"]).check_fields_of(None::<&Record>).unwrap();
+    }
+
+    #[test]
+    fn test_print_json_to_projects_streamed_rows() {
+        let rows = vec![
+            json!({"uid": 1, "email": "a@example.com", "first_name": "A"}),
+            json!({"uid": 2, "email": "b@example.com"}),
+        ];
+        let mut buf = Vec::new();
+        output(&["uid"]).print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written, json!([{"uid": 1}, {"uid": 2}]));
+
+        let mut buf = Vec::new();
+        let err = output(&["emial"])
+            .print_json_iter_to(&mut buf, rows)
+            .unwrap_err();
+        assert!(err.to_string().contains("emial"));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_print_json_to_anonymizes_before_projecting() {
+        let rows = vec![json!({"uid": 7, "email": "pat@example.com", "birthday": "1960-01-01"})];
+        let output = Output {
+            fields: vec!["email".to_string(), "birthday".to_string()],
+            anonymize: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        output.print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(
+            written,
+            json!([{"email": "user7@example.invalid", "birthday": null}])
+        );
+    }
+
+    #[test]
+    fn test_print_json_anonymizes_before_projecting() {
+        let output = Output {
+            fields: vec!["email".to_string()],
+            anonymize: true,
+            ..Default::default()
+        };
+        let rows = json!([{"uid": 7, "email": "pat@example.com"}]);
+        let mut buf = Vec::new();
+        output.print_json_value_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written, json!([{"email": "user7@example.invalid"}]));
+
+        let record = json!({"uid": 7, "email": "pat@example.com"});
+        let mut buf = Vec::new();
+        output.print_json_value_to(&mut buf, &record).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written, json!({"email": "user7@example.invalid"}));
+    }
+
+    #[test]
+    fn test_print_json_to_writes_explicit_nulls() {
+        let rows = vec![Record {
+            uid: 1,
+            email: None,
+            brns: vec![],
+        }];
+        let mut buf = Vec::new();
+        output(&[]).print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written, json!([{"uid": 1}]));
+
+        let output = Output {
+            explicit_nulls: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        output.print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(written, json!([{"uid": 1, "email": null}]));
+    }
+
+    #[test]
+    fn test_print_json_to_stamps_and_envelopes() {
+        let rows = vec![json!({"uid": 1}), json!({"uid": 2})];
+        let output = Output {
+            stamp: true,
+            envelope: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        output.print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(
+            written,
+            json!({
+                "exported_at": exported_at(),
+                "source": "drupal",
+                "count": 2,
+                "data": [
+                    {"uid": 1, "_source": "drupal", "_exported_at": exported_at()},
+                    {"uid": 2, "_source": "drupal", "_exported_at": exported_at()}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_print_json_to_stamps_only_objects() {
+        let rows = vec![json!({"uid": 1}), json!("lone"), json!(2)];
+        let output = Output {
+            stamp: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        output.print_json_to(&mut buf, &rows).unwrap();
+        let written: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!(
+            written,
+            json!([
+                {"uid": 1, "_source": "drupal", "_exported_at": exported_at()},
+                "lone",
+                2
+            ])
+        );
+    }
+
+    /// Records what had been written at each flush
+    #[derive(Default)]
+    struct Flushes {
+        buf: Vec<u8>,
+        flushed: Vec<String>,
+    }
+
+    impl Write for &mut Flushes {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.buf.write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushed
+                .push(String::from_utf8_lossy(&self.buf).into_owned());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_print_ndjson_stream_flushes_before_waiting() {
+        use futures::stream;
+
+        // The second row arrives only after the stream has had to wait
+        let rows = stream::iter([Ok(json!({"uid": 1, "email": "a@example.com"}))]).chain(
+            stream::once(async {
+                tokio::task::yield_now().await;
+                Ok(json!({"uid": 2}))
+            }),
+        );
+        let mut flushes = Flushes::default();
+        output(&["uid"])
+            .print_ndjson_stream_to(&mut flushes, rows)
+            .await
+            .unwrap();
+        assert_eq!(
+            flushes.flushed,
+            vec!["{\"uid\":1}\n", "{\"uid\":1}\n{\"uid\":2}\n"]
+        );
+
+        let envelope = Output {
+            envelope: true,
+            ..Default::default()
+        };
+        let rows = stream::iter([Ok(json!({"uid": 1}))]);
+        assert!(
+            envelope
+                .print_ndjson_stream_to(&mut Flushes::default(), rows)
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Named connection profiles from `aci-export.toml`.
+//!
+//! ```toml
+//! [profiles.staging]
+//! url = "mysql://export@staging-db/drupal"
+//! ssl_mode = "required"
+//! max_connections = 4
+//!
+//! [profiles.staging.output]
+//! explicit_nulls = true
+//! ```
+//!
+//! A profile is only read when selected with `--profile`. The `ACI__DDB_*`
+//! environment variables still override its connection settings, and output
+//! flags given on the command line are combined with its output defaults.
+
+use super::{Output, Result};
+use anyhow::{Context, anyhow};
+use config::{Config, File, FileFormat, Source};
+use std::path::{Path, PathBuf};
+
+/// Connection and output settings for one target database
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct Profile {
+    /// Database URL, overridden by `ACI__DDB_URL`
+    pub url: Option<String>,
+    /// CA bundle path, overridden by `ACI__DDB_SSL_CA`
+    pub ssl_ca: Option<PathBuf>,
+    /// TLS mode, overridden by `ACI__DDB_SSL_MODE`
+    pub ssl_mode: Option<String>,
+    /// Pool size limits (sqlx defaults when unset)
+    pub max_connections: Option<u32>,
+    pub min_connections: Option<u32>,
+    #[serde(default)]
+    pub output: OutputDefaults,
+}
+
+/// Output options applied unless given on the command line
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct OutputDefaults {
+    #[serde(default)]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub explicit_nulls: bool,
+    #[serde(default)]
+    pub stamp: bool,
+    #[serde(default)]
+    pub envelope: bool,
+    #[serde(default)]
+    pub anonymize: bool,
+}
+
+impl Profile {
+    /// Read profile `name` from the TOML file at `path`
+    pub fn from_file(path: &Path, name: &str) -> Result<Self> {
+        let file = File::new(&path.to_string_lossy(), FileFormat::Toml);
+        Self::from_source(file, name).with_context(|| format!("reading {}", path.display()))
+    }
+
+    fn from_source(source: impl Source + Send + Sync + 'static, name: &str) -> Result<Self> {
+        let config = Config::builder().add_source(source).build()?;
+        config
+            .get::<Option<Profile>>(&format!("profiles.{name}"))?
+            .ok_or_else(|| anyhow!("no [profiles.{name}] section"))
+    }
+
+    /// `output` with this profile's output defaults filled in
+    pub fn apply(&self, output: &Output) -> Output {
+        let defaults = &self.output;
+        Output {
+            fields: if output.fields.is_empty() {
+                defaults.fields.clone()
+            } else {
+                output.fields.clone()
+            },
+            explicit_nulls: output.explicit_nulls || defaults.explicit_nulls,
+            stamp: output.stamp || defaults.stamp,
+            envelope: output.envelope || defaults.envelope,
+            anonymize: output.anonymize || defaults.anonymize,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [profiles.prod]
+        url = "mysql://prod/drupal"
+        max_connections = 2
+
+        [profiles.staging]
+        url = "mysql://staging/drupal"
+
+        [profiles.staging.output]
+        fields = ["uid", "email"]
+        stamp = true
+    "#;
+
+    fn profile(name: &str) -> Result<Profile> {
+        Profile::from_source(File::from_str(CONFIG, FileFormat::Toml), name)
+    }
+
+    #[test]
+    fn test_profile_from_source() {
+        let prod = profile("prod").unwrap();
+        assert_eq!(prod.url.as_deref(), Some("mysql://prod/drupal"));
+        assert_eq!(prod.max_connections, Some(2));
+        assert!(prod.output.fields.is_empty());
+
+        assert!(profile("replica").is_err());
+    }
+
+    #[test]
+    fn test_profile_apply_output_defaults() {
+        let staging = profile("staging").unwrap();
+        let output = staging.apply(&Output {
+            explicit_nulls: true,
+            ..Default::default()
+        });
+        assert_eq!(output.fields, vec!["uid", "email"]);
+        assert!(output.explicit_nulls && output.stamp && !output.envelope);
+
+        let output = staging.apply(&Output {
+            fields: vec!["uid".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(output.fields, vec!["uid"]);
+    }
+}
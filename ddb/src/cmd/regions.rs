@@ -1,4 +1,4 @@
-use super::{Result, connect_from_env, print_json};
+use super::{Output, Result, connect_from_env, count_only_unsupported, print_count};
 use aci_ddb::regions;
 use anyhow::anyhow;
 
@@ -41,19 +41,28 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match &self.cmd {
-            Some(cmd) => cmd.run().await,
+            Some(cmd) => cmd.run(output).await,
             None => {
                 Get {
                     id: self.id,
                     number: self.number,
                 }
-                .run()
+                .run(output)
                 .await
             }
         }
     }
+
+    /// `--count-only` for the list of all regions
+    pub async fn count(&self, output: &Output) -> Result {
+        if self.cmd.is_some() || self.id.is_some() {
+            return Err(count_only_unsupported());
+        }
+        let db = connect_from_env().await?;
+        print_count(output, "regions", regions::count(&db).await?)
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -75,7 +84,7 @@ pub(crate) struct LeadershipCmd {
 }
 
 impl RegionCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
             Self::Leadership(args) => {
                 Leadership {
@@ -83,7 +92,7 @@ impl RegionCmd {
                     number: args.number,
                     as_of: args.as_of,
                 }
-                .run()
+                .run(output)
                 .await
             }
         }
@@ -96,7 +105,7 @@ struct Get {
 }
 
 impl Get {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
 
         match (self.id, self.number) {
@@ -105,19 +114,19 @@ impl Get {
                 let region = regions::by_number(&db, id as i32)
                     .await?
                     .ok_or_else(|| anyhow!("Region number {id} not found"))?;
-                print_json(&region)
+                output.print_json(&region)
             }
             (Some(id), false) => {
                 // Lookup by uid
                 let region = regions::by_uid(&db, id)
                     .await?
                     .ok_or_else(|| anyhow!("Region uid {id} not found"))?;
-                print_json(&region)
+                output.print_json(&region)
             }
             (None, _) => {
                 // No id - get all regions
                 let regions = regions::all(&db).await?;
-                print_json(&regions)
+                output.print_json(&regions)
             }
         }
     }
@@ -130,7 +139,7 @@ struct Leadership {
 }
 
 impl Leadership {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         use aci_ddb::leadership::DateFilter;
 
         let db = connect_from_env().await?;
@@ -144,6 +153,6 @@ impl Leadership {
             (None, _) => aci_ddb::leadership::for_all_regions(&db, filter).await?,
         };
 
-        print_json(&leadership)
+        output.print_json(&leadership)
     }
 }
@@ -0,0 +1,22 @@
+use super::{Output, Result, connect_from_env};
+use aci_ddb::{events, seed, users};
+
+/// Export users and events as GraphQL seed mutation inputs for the new site
+///
+/// Prints `{"schemaVersion", "createUser": [...], "createEvent": [...]}`,
+/// one `CreateUserInput`/`CreateEventInput` object per record. See the
+/// `aci_ddb::seed` module for the schema version targeted.
+///
+/// Examples:
+///   aci-ddb seed > seed.json
+#[derive(Debug, clap::Args)]
+pub struct Cmd {}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let users = users::all(&db).await?;
+        let events = events::all(&db).await?;
+        output.print_json(&seed::batch(&users, &events))
+    }
+}
@@ -0,0 +1,141 @@
+//! Destinations for exported entity documents.
+//!
+//! Commands that write several entities (e.g. `dump`) serialize each one and
+//! hand the bytes to an [`OutputSink`], so the same export runs locally
+//! (stdout, a directory) or in a Lambda writing straight to S3.
+
+use super::Result;
+use anyhow::Context;
+use std::{io::Write, path::PathBuf};
+
+/// Destination for named entity documents
+pub trait OutputSink {
+    /// Store the serialized entity `name` (e.g. `users`, `manifest`)
+    async fn write_entity(&self, name: &str, bytes: Vec<u8>) -> Result;
+
+    /// Human readable location of an entity, for log and error messages
+    fn location(&self, name: &str) -> String;
+}
+
+/// Writes every entity to stdout as NDJSON, one
+/// `{"entity": <name>, "data": <document>}` line per entity, so a consumer
+/// can tell the documents apart
+#[derive(Debug, Default)]
+pub struct StdoutSink;
+
+impl OutputSink for StdoutSink {
+    async fn write_entity(&self, name: &str, bytes: Vec<u8>) -> Result {
+        write_entity_line(std::io::stdout().lock(), name, &bytes)
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("stdout ({name})")
+    }
+}
+
+/// Write the JSON document `bytes` to `writer` as one NDJSON line tagged with
+/// the entity `name`
+fn write_entity_line<W: Write>(mut writer: W, name: &str, bytes: &[u8]) -> Result {
+    #[derive(serde::Serialize)]
+    struct Line<'a> {
+        entity: &'a str,
+        data: serde_json::Value,
+    }
+    let data = serde_json::from_slice(bytes).with_context(|| format!("parsing {name}"))?;
+    serde_json::to_writer(&mut writer, &Line { entity: name, data })?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes each entity to `<dir>/<name>.json`, creating the directory
+#[derive(Debug)]
+pub struct FileSink(PathBuf);
+
+impl FileSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+        Ok(Self(dir))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(format!("{name}.json"))
+    }
+}
+
+impl OutputSink for FileSink {
+    async fn write_entity(&self, name: &str, bytes: Vec<u8>) -> Result {
+        let path = self.path(name);
+        std::fs::write(&path, bytes).with_context(|| format!("writing {}", path.display()))
+    }
+
+    fn location(&self, name: &str) -> String {
+        self.path(name).display().to_string()
+    }
+}
+
+/// Uploads each entity to `s3://<bucket>/<prefix><name>.json`.
+///
+/// Credentials and region come from the standard AWS environment (env vars,
+/// profile or the Lambda execution role).
+#[cfg(feature = "s3")]
+#[derive(Debug)]
+pub struct S3Sink {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3Sink {
+    pub async fn from_env(bucket: impl Into<String>, prefix: impl Into<String>) -> Self {
+        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        Self {
+            client: aws_sdk_s3::Client::new(&config),
+            bucket: bucket.into(),
+            prefix: prefix.into(),
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}{name}.json", self.prefix)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl OutputSink for S3Sink {
+    async fn write_entity(&self, name: &str, bytes: Vec<u8>) -> Result {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.key(name))
+            .content_type("application/json")
+            .body(bytes.into())
+            .send()
+            .await
+            .with_context(|| format!("uploading {}", self.location(name)))?;
+        Ok(())
+    }
+
+    fn location(&self, name: &str) -> String {
+        format!("s3://{}/{}", self.bucket, self.key(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_entity_line() {
+        let mut buf = Vec::new();
+        write_entity_line(&mut buf, "users", b"[\n  {\"uid\": 1}\n]").unwrap();
+        write_entity_line(&mut buf, "manifest", b"{\"entities\": []}").unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "{\"entity\":\"users\",\"data\":[{\"uid\":1}]}\n\
+             {\"entity\":\"manifest\",\"data\":{\"entities\":[]}}\n"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Progress display for the bulk exports.
+
+use aci_ddb::progress::ProgressEvent;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+/// Spinner on stderr showing the latest [`ProgressEvent`]. Hidden when
+/// stderr isn't a terminal, so scheduled runs log nothing extra; cleared
+/// when dropped.
+pub struct Spinner(ProgressBar);
+
+impl Spinner {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new_spinner();
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg}").expect("Invalid spinner template"),
+        );
+        bar.enable_steady_tick(Duration::from_millis(100));
+        Self(bar)
+    }
+
+    pub fn report(&self, event: ProgressEvent) {
+        let state = if event.done { "done" } else { "fetching" };
+        self.0.set_message(format!(
+            "{} {state}: {} rows in {}s",
+            event.entity,
+            event.rows,
+            event.elapsed.as_secs()
+        ));
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Result, connect_from_env, print_json};
+use super::{Output, Result, connect_from_env, count_only_unsupported, print_count};
 use aci_ddb::standing_committees;
 use anyhow::anyhow;
 
@@ -30,12 +30,25 @@ pub struct Cmd {
 }
 
 impl Cmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match &self.cmd {
-            Some(cmd) => cmd.run().await,
-            None => Get { uid: self.uid }.run().await,
+            Some(cmd) => cmd.run(output).await,
+            None => Get { uid: self.uid }.run(output).await,
         }
     }
+
+    /// `--count-only` for the list of all standing committees
+    pub async fn count(&self, output: &Output) -> Result {
+        if self.cmd.is_some() || self.uid.is_some() {
+            return Err(count_only_unsupported());
+        }
+        let db = connect_from_env().await?;
+        print_count(
+            output,
+            "standing_committees",
+            standing_committees::count(&db).await?,
+        )
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -53,14 +66,14 @@ pub(crate) struct LeadershipCmd {
 }
 
 impl StandingCommitteeCmd {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         match self {
             Self::Leadership(args) => {
                 Leadership {
                     uid: args.uid,
                     as_of: args.as_of,
                 }
-                .run()
+                .run(output)
                 .await
             }
         }
@@ -72,7 +85,7 @@ struct Get {
 }
 
 impl Get {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         let db = connect_from_env().await?;
 
         match self.uid {
@@ -80,11 +93,11 @@ impl Get {
                 let committee = standing_committees::by_uid(&db, uid)
                     .await?
                     .ok_or_else(|| anyhow!("Standing committee uid {uid} not found"))?;
-                print_json(&committee)
+                output.print_json(&committee)
             }
             None => {
                 let committees = standing_committees::all(&db).await?;
-                print_json(&committees)
+                output.print_json(&committees)
             }
         }
     }
@@ -96,7 +109,7 @@ struct Leadership {
 }
 
 impl Leadership {
-    pub async fn run(&self) -> Result {
+    pub async fn run(&self, output: &Output) -> Result {
         use aci_ddb::leadership::DateFilter;
 
         let db = connect_from_env().await?;
@@ -107,6 +120,6 @@ impl Leadership {
             None => aci_ddb::leadership::for_all_standing_committees(&db, filter).await?,
         };
 
-        print_json(&leadership)
+        output.print_json(&leadership)
     }
 }
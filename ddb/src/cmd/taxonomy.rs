@@ -0,0 +1,21 @@
+use super::{Output, Result, connect_from_env};
+use aci_ddb::taxonomy;
+
+/// Export the terms of a taxonomy vocabulary (tid, name, parent, weight)
+///
+/// Examples:
+///   aci-ddb taxonomy ssp_race
+///   aci-ddb taxonomy ssp_race --fields tid,name
+#[derive(Debug, clap::Args)]
+pub struct Cmd {
+    /// Vocabulary machine name
+    pub vocabulary: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, output: &Output) -> Result {
+        let db = connect_from_env().await?;
+        let terms = taxonomy::vocabulary(&db, &self.vocabulary).await?;
+        output.print_json(&terms)
+    }
+}
@@ -0,0 +1,124 @@
+//! Per-run lookup cache for small reference tables.
+//!
+//! Exports that resolve the same clubs, regions and roles over and over can
+//! pass a [`SyncContext`] instead of a bare [`Db`]. Each reference table is
+//! fetched once on first use and served from memory afterwards, so e.g.
+//! [`crate::events::attach_hierarchy`], [`crate::clubs::directory`] and
+//! [`crate::leadership::officer_emails`] share one clubs query when handed the
+//! same context. The context is explicitly owned by the caller; there is no
+//! global state.
+//!
+//! The microsite export ([`crate::microsites::all_pages`] and the CLI's
+//! `microsites export`) takes a bare [`Db`]: it fetches the microsite clubs
+//! and their slugs once up front, and every per-club query after that is
+//! keyed by the homepage nid, so there is no club or region lookup to share.
+
+use crate::{
+    Db, Result,
+    clubs::{self, Club},
+    owner::{self, EventOwner},
+    regions::{self, Region},
+};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct SyncContext {
+    db: Db,
+    clubs: Option<Vec<Club>>,
+    regions: Option<Vec<Region>>,
+    /// role taxonomy tid -> role name
+    role_names: Option<HashMap<u64, String>>,
+    /// node nid -> resolved owner
+    owners: HashMap<u64, Option<EventOwner>>,
+}
+
+impl SyncContext {
+    pub fn new(db: Db) -> Self {
+        Self {
+            db,
+            clubs: None,
+            regions: None,
+            role_names: None,
+            owners: HashMap::new(),
+        }
+    }
+
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Every club, as [`clubs::all`]
+    pub async fn clubs(&mut self) -> Result<&[Club]> {
+        if self.clubs.is_none() {
+            self.clubs = Some(clubs::all(&self.db).await?);
+        }
+        Ok(self.clubs.as_deref().unwrap_or_default())
+    }
+
+    /// Every region, as [`regions::all`]
+    pub async fn regions(&mut self) -> Result<&[Region]> {
+        if self.regions.is_none() {
+            self.regions = Some(regions::all(&self.db).await?);
+        }
+        Ok(self.regions.as_deref().unwrap_or_default())
+    }
+
+    /// Club number for an ssp_club nid (`None` for intraclubs or unknown clubs)
+    pub async fn club_number(&mut self, club_uid: u64) -> Result<Option<i64>> {
+        Ok(self
+            .clubs()
+            .await?
+            .iter()
+            .find(|club| club.uid == club_uid)
+            .and_then(|club| club.number))
+    }
+
+    /// Region name for an ssp_region nid
+    pub async fn region_name(&mut self, region_uid: u64) -> Result<Option<String>> {
+        Ok(self
+            .regions()
+            .await?
+            .iter()
+            .find(|region| region.uid == region_uid)
+            .and_then(|region| region.name.clone()))
+    }
+
+    /// Leadership role name for a role taxonomy tid
+    pub async fn role_name(&mut self, role_uid: u64) -> Result<Option<String>> {
+        if self.role_names.is_none() {
+            let rows: Vec<(u64, String)> = self
+                .db
+                .fetch("roles", async || {
+                    sqlx::query_as(
+                        r#"
+                        SELECT t.tid, t.name
+                        FROM taxonomy_term_field_data t
+                        WHERE t.tid IN (
+                            SELECT DISTINCT field_role_target_id
+                            FROM paragraph__field_role
+                            WHERE deleted = 0
+                        )
+                        "#,
+                    )
+                    .fetch_all(self.db.pool())
+                    .await
+                })
+                .await?;
+            self.role_names = Some(rows.into_iter().collect());
+        }
+        Ok(self
+            .role_names
+            .as_ref()
+            .and_then(|names| names.get(&role_uid).cloned()))
+    }
+
+    /// Owning club or region of a node, see [`owner::resolve`]
+    pub async fn owner(&mut self, node_nid: u64) -> Result<Option<EventOwner>> {
+        if let Some(owner) = self.owners.get(&node_nid) {
+            return Ok(owner.clone());
+        }
+        let owner = owner::resolve(&self.db, node_nid).await?;
+        self.owners.insert(node_nid, owner.clone());
+        Ok(owner)
+    }
+}
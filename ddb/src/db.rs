@@ -0,0 +1,89 @@
+//! The database handle every fetch function takes.
+//!
+//! [`Db`] carries the connection pool together with the [`RetryPolicy`] for
+//! its queries and the most IDs to bind per `IN (...)` list (see
+//! [`crate::chunked`]). Fetch functions run each statement through [`Db::fetch`],
+//! which retries it as the policy allows and times it (see
+//! [`crate::timing`]), so no export query bypasses either. Streamed exports
+//! go through [`Db::stream`], which times them but can't retry.
+
+use crate::{
+    Error, Result,
+    chunked::DEFAULT_IN_CHUNK_SIZE,
+    retry::{self, RetryPolicy},
+    timing::{self, RowCount},
+};
+use sqlx::MySqlPool;
+use std::num::NonZeroUsize;
+
+/// A connection pool and the retry policy for its queries.
+///
+/// Cloning is cheap; clones share the pool.
+#[derive(Debug, Clone)]
+pub struct Db {
+    pool: MySqlPool,
+    retry: RetryPolicy,
+    in_chunk_size: NonZeroUsize,
+}
+
+impl Db {
+    /// Wrap `pool`, retrying queries with the default [`RetryPolicy`] and
+    /// binding up to 1000 IDs per `IN (...)` list
+    pub fn new(pool: MySqlPool) -> Self {
+        Self {
+            pool,
+            retry: RetryPolicy::default(),
+            in_chunk_size: DEFAULT_IN_CHUNK_SIZE,
+        }
+    }
+
+    /// Retry queries as `retry` allows instead
+    pub fn with_retry_policy(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    /// Bind at most `size` IDs per `IN (...)` list instead; longer ID lists
+    /// are fetched over several queries
+    pub fn with_in_chunk_size(self, size: NonZeroUsize) -> Self {
+        Self {
+            in_chunk_size: size,
+            ..self
+        }
+    }
+
+    /// Most IDs bound per `IN (...)` list
+    pub fn in_chunk_size(&self) -> usize {
+        self.in_chunk_size.get()
+    }
+
+    /// The underlying pool, for statements that aren't exports (e.g. session
+    /// settings) and for streaming rows as they arrive
+    pub fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+
+    /// Run `query` (against [`Db::pool`]), retried and timed. Failures are
+    /// tagged with `entity` as in [`crate::Error::query`].
+    ///
+    /// `query` must build its statement afresh on every call, since a built
+    /// `QueryBuilder` can't be executed twice.
+    pub(crate) async fn fetch<T: RowCount, F: Future<Output = sqlx::Result<T>>>(
+        &self,
+        entity: &'static str,
+        query: impl FnMut() -> F,
+    ) -> Result<T> {
+        timing::timed(entity, retry::retry(&self.retry, entity, query)).await
+    }
+    /// Hand out the rows of `rows` as they arrive, timed like [`Db::fetch`]
+    /// and with failures tagged with `entity` the same way.
+    ///
+    /// Not retried: rows already handed out can't be taken back.
+    pub(crate) fn stream<'a, T: Send + 'a>(
+        &self,
+        entity: &'static str,
+        rows: impl futures::Stream<Item = sqlx::Result<T>> + Send + 'a,
+    ) -> crate::Stream<'a, T> {
+        use futures::{StreamExt, TryStreamExt};
+        timing::timed_stream(entity, rows.map_err(Error::query(entity)).boxed())
+    }
+}
@@ -6,6 +6,21 @@ pub type Result<T = ()> = std::result::Result<T, Error>;
 pub enum Error {
     #[error("database: {0}")]
     Request(#[from] sqlx::Error),
+    #[error("{entity} query failed: {source}")]
+    Query {
+        /// Exported entity (or lookup) whose query failed, e.g. "events"
+        entity: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
 }
 
-impl Error {}
+impl Error {
+    /// Tag a query failure with the entity it was fetching.
+    ///
+    /// Returns a closure for use with `map_err`, on a future or a stream of
+    /// rows alike, e.g. `.fetch_all(pool).map_err(Error::query("events"))`.
+    pub fn query(entity: &'static str) -> impl Fn(sqlx::Error) -> Self {
+        move |source| Self::Query { entity, source }
+    }
+}
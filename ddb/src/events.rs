@@ -1,73 +1,200 @@
 //! Event queries from Drupal database
 //!
 //! Returns all published events with owner resolution via the
-//! microsite join (event → field_club → main_site_club → ssp_club/ssp_region).
+//! microsite join (event → field_club → main_site_club → ssp_club/ssp_region),
+//! shared with other node types via [`crate::owner`].
 
-use crate::{Error, Result};
-use chrono::{NaiveDate, NaiveDateTime};
-use futures::TryFutureExt;
-use sqlx::MySqlPool;
+use crate::{
+    Db, Result, SyncContext,
+    clubs::Club,
+    owner::{self, EventOwner, OWNER_COLUMNS},
+};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::MySql;
+use std::collections::HashMap;
 
 /// Event from Drupal
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct Event {
     pub uid: u64,
     pub title: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub start_date: Option<NaiveDateTime>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub end_date: Option<NaiveDateTime>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// IANA zone the start/end dates are stored in, when the date field has
+    /// a timezone column (see [`Event::start_utc`] for the fallback)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub location_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub address: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub phone: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub website_url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub body: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub registration_url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub registration_label: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub registration_deadline: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub contact_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub contact_email: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub contact_phone: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub latitude: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub longitude: Option<f64>,
+    /// `public://` URI of the attached image or flyer (`field_image`), for
+    /// calendar thumbnails. Always `None` on sites without the field.
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub image_uri: Option<String>,
+    /// Category terms (`field_tags`), e.g. "rally", "social"; empty if none
+    #[sqlx(flatten, try_from = "EventTags")]
+    pub tags: Vec<String>,
     /// nid of the owning ssp_club or ssp_region node
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub owner_uid: Option<u64>,
     /// "ssp_club" | "ssp_region" | NULL (international)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub owner_node_type: Option<String>,
     pub status: bool,
     pub created: i64,
     pub changed: i64,
 }
 
-const FETCH_EVENTS_QUERY: &str = r#"
-    SELECT
+impl Event {
+    /// Owning club or region, `None` for international events
+    pub fn owner(&self) -> Option<EventOwner> {
+        match (self.owner_uid, &self.owner_node_type) {
+            (Some(uid), Some(node_type)) => Some(EventOwner {
+                uid,
+                node_type: node_type.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Slug id for URLs, e.g. `2025-spring-rally-12345` (year of the start date)
+    pub fn stable_id(&self) -> String {
+        crate::slug::stable_id(
+            self.start_date.map(|start| start.year()),
+            &self.title,
+            self.uid,
+        )
+    }
+
+    /// Start date converted to UTC.
+    ///
+    /// Dates are stored as local wall-clock times. Events without a stored
+    /// (or with an unrecognised) timezone are assumed to be in
+    /// [`DEFAULT_TIMEZONE`], the club's home office zone. Times that fall in
+    /// a DST gap return `None`; ambiguous times resolve to the earlier instant.
+    pub fn start_utc(&self) -> Option<DateTime<Utc>> {
+        self.to_utc(self.start_date?)
+    }
+
+    /// End date converted to UTC, as for [`Self::start_utc`]
+    pub fn end_utc(&self) -> Option<DateTime<Utc>> {
+        self.to_utc(self.end_date?)
+    }
+
+    fn to_utc(&self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        self.zone()
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|local| local.with_timezone(&Utc))
+    }
+
+    /// Zone the dates are stored in, [`DEFAULT_TIMEZONE`] when none (or an
+    /// unrecognised one) is stored
+    pub fn zone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(DEFAULT_TIMEZONE)
+    }
+}
+
+#[cfg(test)]
+impl Event {
+    /// Minimal published event for unit tests; set other fields as needed
+    pub(crate) fn test(uid: u64, title: &str) -> Self {
+        Self {
+            uid,
+            title: title.to_string(),
+            start_date: None,
+            end_date: None,
+            timezone: None,
+            description: None,
+            location_name: None,
+            address: None,
+            phone: None,
+            website_url: None,
+            body: None,
+            registration_url: None,
+            registration_label: None,
+            registration_deadline: None,
+            contact_name: None,
+            contact_email: None,
+            contact_phone: None,
+            latitude: None,
+            longitude: None,
+            image_uri: None,
+            tags: vec![],
+            owner_uid: None,
+            owner_node_type: None,
+            status: true,
+            created: 1_700_000_000,
+            changed: 1_700_000_000,
+        }
+    }
+}
+
+/// Zone assumed for events whose date field stores no timezone
+pub const DEFAULT_TIMEZONE: Tz = Tz::America__New_York;
+
+/// `|` separated term names, selected as `tags`
+#[derive(Debug, sqlx::FromRow)]
+struct EventTags {
+    tags: Option<String>,
+}
+
+impl From<EventTags> for Vec<String> {
+    fn from(value: EventTags) -> Self {
+        value
+            .tags
+            .unwrap_or_default()
+            .split('|')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+const EVENT_COLUMNS: &str = r#"
         e.nid AS uid,
         e.title,
         CAST(d.field_date_value AS DATETIME) AS start_date,
         CAST(d.field_date_end_value AS DATETIME) AS end_date,
-        desc_f.field_event_description_value AS description,
+        {timezone} AS timezone,
+        {description} AS description,
         loc.field_event_location_name_value AS location_name,
         addr.field_event_address_value AS address,
         ph.field_event_phone_value AS phone,
         web.field_event_website_uri AS website_url,
-        body.body_value AS body,
+        {body} AS body,
         rl.field_registration_link_uri AS registration_url,
         rl.field_registration_link_title AS registration_label,
         CAST(rdd.field_registration_deadline_value AS DATE) AS registration_deadline,
@@ -76,19 +203,19 @@ const FETCH_EVENTS_QUERY: &str = r#"
         cp.field_contact_phone_value AS contact_phone,
         CAST(lat.field_latitude_value AS DOUBLE) AS latitude,
         CAST(lon.field_longitude_value AS DOUBLE) AS longitude,
-        msc.entity_id AS owner_uid,
-        owner_nd.type AS owner_node_type,
+        {image} AS image_uri,
+        GROUP_CONCAT(DISTINCT tag.name ORDER BY tag.name SEPARATOR '|') AS tags,
         e.status,
         e.created,
-        e.changed
-    FROM node_field_data e
+        e.changed,
+"#;
+
+const EVENT_JOINS: &str = r#"
     LEFT JOIN node__field_date d ON e.nid = d.entity_id AND d.deleted = 0
-    LEFT JOIN node__field_event_description desc_f ON e.nid = desc_f.entity_id AND desc_f.deleted = 0
     LEFT JOIN node__field_event_location_name loc ON e.nid = loc.entity_id AND loc.deleted = 0
     LEFT JOIN node__field_event_address addr ON e.nid = addr.entity_id AND addr.deleted = 0
     LEFT JOIN node__field_event_phone ph ON e.nid = ph.entity_id AND ph.deleted = 0
     LEFT JOIN node__field_event_website web ON e.nid = web.entity_id AND web.deleted = 0
-    LEFT JOIN node__body body ON e.nid = body.entity_id AND body.deleted = 0
     LEFT JOIN node__field_registration_link rl ON e.nid = rl.entity_id AND rl.deleted = 0
     LEFT JOIN node__field_registration_deadline rdd ON e.nid = rdd.entity_id AND rdd.deleted = 0
     LEFT JOIN node__field_contact_name cn ON e.nid = cn.entity_id AND cn.deleted = 0
@@ -97,19 +224,568 @@ const FETCH_EVENTS_QUERY: &str = r#"
     LEFT JOIN node__field_coordinates coord ON e.nid = coord.entity_id AND coord.deleted = 0
     LEFT JOIN paragraph__field_latitude lat ON coord.field_coordinates_target_id = lat.entity_id AND lat.deleted = 0
     LEFT JOIN paragraph__field_longitude lon ON coord.field_coordinates_target_id = lon.entity_id AND lon.deleted = 0
-    LEFT JOIN node__field_club fc ON e.nid = fc.entity_id AND fc.deleted = 0
-    LEFT JOIN node__field_main_site_club msc
-        ON fc.field_club_target_id = msc.field_main_site_club_target_id AND msc.deleted = 0
-    LEFT JOIN node_field_data owner_nd ON msc.entity_id = owner_nd.nid
-    WHERE e.type = 'event'
-      AND e.status = 1
-    GROUP BY e.nid
+    LEFT JOIN node__field_tags tags ON e.nid = tags.entity_id AND tags.deleted = 0
+    LEFT JOIN taxonomy_term_field_data tag ON tags.field_tags_target_id = tag.tid
 "#;
 
+/// Joins for [`Event::image_uri`]: node -> field_image -> media ->
+/// field_media_image -> file, first image only. Left out on sites without
+/// the field, see [`has_image_field`].
+const EVENT_IMAGE_JOINS: &str = r#"
+    LEFT JOIN node__field_image img ON e.nid = img.entity_id AND img.delta = 0 AND img.deleted = 0
+    LEFT JOIN media__field_media_image img_mfi
+        ON img_mfi.entity_id = img.field_image_target_id AND img_mfi.deleted = 0
+    LEFT JOIN file_managed img_file ON img_file.fid = img_mfi.field_media_image_target_id
+"#;
+
+/// Joins for the large text columns, left out of [`summaries`]
+const EVENT_TEXT_JOINS: &str = r#"
+    LEFT JOIN node__field_event_description desc_f ON e.nid = desc_f.entity_id AND desc_f.deleted = 0
+    LEFT JOIN node__body body ON e.nid = body.entity_id AND body.deleted = 0
+"#;
+
+/// Whether `node__field_date` has a timezone column (smart_date style fields
+/// do, core daterange fields don't).
+///
+/// The schema doesn't change while an export runs, so `information_schema`
+/// is only asked once per process.
+async fn has_timezone_column(db: &Db) -> Result<bool> {
+    static HAS_TIMEZONE_COLUMN: tokio::sync::OnceCell<bool> = tokio::sync::OnceCell::const_new();
+
+    HAS_TIMEZONE_COLUMN
+        .get_or_try_init(async || {
+            let count: i64 = db
+                .fetch("events", async || {
+                    sqlx::query_scalar(
+                        "SELECT COUNT(*) FROM information_schema.COLUMNS \
+                         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'node__field_date' \
+                         AND COLUMN_NAME = 'field_date_timezone'",
+                    )
+                    .fetch_one(db.pool())
+                    .await
+                })
+                .await?;
+            Ok(count > 0)
+        })
+        .await
+        .copied()
+}
+
+/// Whether events have a `field_image` (the `node__field_image` table); sites
+/// without it export no [`Event::image_uri`].
+///
+/// Asked once per process, as for [`has_timezone_column`].
+async fn has_image_field(db: &Db) -> Result<bool> {
+    static HAS_IMAGE_FIELD: tokio::sync::OnceCell<bool> = tokio::sync::OnceCell::const_new();
+
+    HAS_IMAGE_FIELD
+        .get_or_try_init(async || {
+            let count: i64 = db
+                .fetch("events", async || {
+                    sqlx::query_scalar(
+                        "SELECT COUNT(*) FROM information_schema.TABLES \
+                         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'node__field_image'",
+                    )
+                    .fetch_one(db.pool())
+                    .await
+                })
+                .await?;
+            Ok(count > 0)
+        })
+        .await
+        .copied()
+}
+
+/// Events query for this site's schema, without the description and body
+/// when `with_text` is false
+async fn fetch_events_sql(db: &Db, with_text: bool) -> Result<String> {
+    let timezone = if has_timezone_column(db).await? {
+        "d.field_date_timezone"
+    } else {
+        "NULL"
+    };
+    Ok(events_sql(timezone, has_image_field(db).await?, with_text))
+}
+
+fn events_sql(timezone: &str, with_image: bool, with_text: bool) -> String {
+    let (description, body, text_joins) = if with_text {
+        (
+            "desc_f.field_event_description_value",
+            "body.body_value",
+            EVENT_TEXT_JOINS,
+        )
+    } else {
+        ("NULL", "NULL", "")
+    };
+    let (image, image_joins) = if with_image {
+        ("CAST(img_file.uri AS CHAR(255))", EVENT_IMAGE_JOINS)
+    } else {
+        ("NULL", "")
+    };
+    let columns = EVENT_COLUMNS
+        .replace("{timezone}", timezone)
+        .replace("{image}", image)
+        .replace("{description}", description)
+        .replace("{body}", body);
+    format!(
+        "SELECT {columns} {OWNER_COLUMNS} FROM node_field_data e {EVENT_JOINS} {image_joins} \
+         {text_joins} {owner_joins} WHERE e.type = 'event' AND e.status = 1",
+        owner_joins = owner::owner_joins("e"),
+    )
+}
+
 /// Fetch all published events from Drupal
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Event>> {
-    sqlx::query_as::<_, Event>(FETCH_EVENTS_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
+pub async fn all(db: &Db) -> Result<Vec<Event>> {
+    let sql = format!("{} GROUP BY e.nid", fetch_events_sql(db, true).await?);
+    db.fetch("events", async || {
+        sqlx::query_as::<_, Event>(&sql).fetch_all(db.pool()).await
+    })
+    .await
+}
+
+/// Fetch all published events without [`Event::description`] and
+/// [`Event::body`], for list views like the calendar grid.
+///
+/// The HTML bodies are most of an event's size; use [`all`] where they are
+/// shown.
+pub async fn summaries(db: &Db) -> Result<Vec<Event>> {
+    let sql = format!("{} GROUP BY e.nid", fetch_events_sql(db, false).await?);
+    db.fetch("events", async || {
+        sqlx::query_as::<_, Event>(&sql).fetch_all(db.pool()).await
+    })
+    .await
+}
+
+/// Number of events [`all`] would return (one per published event node),
+/// counted on the nodes alone without the export's joins
+pub async fn count(db: &Db) -> Result<u64> {
+    db.fetch("events", async || {
+        sqlx::query_scalar(
+            "SELECT CAST(COUNT(*) AS UNSIGNED) FROM node_field_data e \
+             WHERE e.type = 'event' AND e.status = 1",
+        )
+        .fetch_one(db.pool())
+        .await
+    })
+    .await
+}
+
+/// Fetch published events owned by a club or region (ssp_club/ssp_region nid)
+pub async fn for_owner(db: &Db, owner_uid: u64) -> Result<Vec<Event>> {
+    let events = fetch_events_sql(db, true).await?;
+    db.fetch("events", async || {
+        sqlx::QueryBuilder::<MySql>::new(events.as_str())
+            .push(" AND owner_msc.entity_id = ")
+            .push_bind(owner_uid)
+            .push(" GROUP BY e.nid")
+            .build_query_as::<Event>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Fetch published events owned by any of the given club numbers, one query
+/// per [`Db::in_chunk_size`] numbers
+pub async fn for_clubs(db: &Db, club_numbers: &[i64]) -> Result<Vec<Event>> {
+    // An event has a single owning club, so no event matches two chunks
+    let events = fetch_events_sql(db, true).await?;
+    crate::chunked::chunked_in(club_numbers, db.in_chunk_size(), async |chunk| {
+        db.fetch("events", async || {
+            let mut query = sqlx::QueryBuilder::<MySql>::new(events.as_str());
+            query.push(
+                " AND owner_msc.entity_id IN (SELECT club_cn.entity_id FROM node__field_club_number club_cn \
+                 WHERE club_cn.deleted = 0 AND club_cn.field_club_number_value IN (",
+            );
+            let mut separated = query.separated(", ");
+            for number in chunk {
+                separated.push_bind(*number);
+            }
+            query
+                .push(")) GROUP BY e.nid")
+                .build_query_as::<Event>()
+                .fetch_all(db.pool())
+                .await
+        })
         .await
+    })
+    .await
+}
+
+/// Event with the names of its owning region and club, for breadcrumbs
+/// like "Region 3 › Heart of Texas Club › Event"
+#[derive(Debug, serde::Serialize)]
+pub struct EventWithPath {
+    #[serde(flatten)]
+    pub event: Event,
+    /// Region of the owning club, or the owning region itself
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub region_name: Option<String>,
+    /// Owning club (`None` for region-owned and international events)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_name: Option<String>,
+}
+
+/// Fetch all published events with their region/club path
+pub async fn with_hierarchy(db: &Db) -> Result<Vec<EventWithPath>> {
+    let events = all(db).await?;
+    attach_hierarchy(&mut SyncContext::new(db.clone()), events).await
+}
+
+/// Attach the region/club path to already fetched events.
+///
+/// Owners come from the owner-resolution join; club owners are walked one
+/// level further up to their region. International events get an empty path.
+/// Clubs and regions come from `ctx`, so they are only queried once per run.
+pub async fn attach_hierarchy(
+    ctx: &mut SyncContext,
+    events: Vec<Event>,
+) -> Result<Vec<EventWithPath>> {
+    let region_names: HashMap<u64, Option<String>> = ctx
+        .regions()
+        .await?
+        .iter()
+        .map(|region| (region.uid, region.name.clone()))
+        .collect();
+    let clubs: HashMap<u64, &Club> = ctx
+        .clubs()
+        .await?
+        .iter()
+        .map(|club| (club.uid, club))
+        .collect();
+
+    Ok(events
+        .into_iter()
+        .map(|event| with_path(event, &clubs, &region_names))
+        .collect())
+}
+
+fn with_path(
+    event: Event,
+    clubs: &HashMap<u64, &Club>,
+    region_names: &HashMap<u64, Option<String>>,
+) -> EventWithPath {
+    let region_name =
+        |uid: Option<u64>| uid.and_then(|uid| region_names.get(&uid).cloned().flatten());
+    let (region_name, club_name) = match event.owner() {
+        Some(owner) if owner.is_club() => match clubs.get(&owner.uid) {
+            Some(club) => (region_name(club.region), Some(club.name.clone())),
+            None => (None, None),
+        },
+        Some(owner) if owner.is_region() => (region_name(Some(owner.uid)), None),
+        _ => (None, None),
+    };
+    EventWithPath {
+        event,
+        region_name,
+        club_name,
+    }
+}
+
+/// Render events as an iCalendar (RFC 5545) VCALENDAR.
+///
+/// Events with a midnight start (and midnight or missing end) are treated as
+/// all-day and use DATE values; their DTEND is exclusive, so a missing end
+/// becomes a one-day event. Timed events without an end omit DTEND. Times are
+/// emitted as floating local times. Events without a start date are skipped.
+pub fn to_ics(events: &[Event]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Airstream Club International//aci-export//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    for event in events {
+        let Some(start) = event.start_date else {
+            continue;
+        };
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:event-{}@airstreamclub.org", event.uid));
+        if let Some(stamp) = chrono::DateTime::from_timestamp(event.changed, 0) {
+            lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+        }
+        let midnight = chrono::NaiveTime::MIN;
+        let all_day =
+            start.time() == midnight && event.end_date.is_none_or(|end| end.time() == midnight);
+        if all_day {
+            let end = event
+                .end_date
+                .map(|end| end.date())
+                .filter(|end| *end > start.date())
+                .unwrap_or_else(|| start.date() + chrono::Days::new(1));
+            lines.push(format!("DTSTART;VALUE=DATE:{}", start.format("%Y%m%d")));
+            lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+        } else {
+            lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+            if let Some(end) = event.end_date.filter(|end| *end >= start) {
+                lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&event.title)));
+        let location: Vec<&str> = [event.location_name.as_deref(), event.address.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect();
+        if !location.is_empty() {
+            lines.push(format!("LOCATION:{}", ics_escape(&location.join(", "))));
+        }
+        if let Some(url) = &event.website_url {
+            lines.push(format!("URL:{url}"));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| ics_fold(line))
+        .collect::<Vec<_>>()
+        .join("\r\n")
+        + "\r\n"
+}
+
+/// Render events as an RSS 2.0 channel for a single owner (club, region or international).
+///
+/// Each item links to the registration URL, falling back to the event website,
+/// and uses the event body as description. Filter with [`for_owner`] first to
+/// produce one feed per club.
+pub fn to_rss(owner_name: &str, events: &[Event]) -> String {
+    use crate::microsites::escape_html as xml_escape;
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<rss version=\"2.0\">\n<channel>\n");
+    xml.push_str(&format!(
+        "<title>{} Events</title>\n",
+        xml_escape(owner_name)
+    ));
+    xml.push_str("<link>https://airstreamclub.org/</link>\n");
+    xml.push_str(&format!(
+        "<description>Upcoming events from {}</description>\n",
+        xml_escape(owner_name)
+    ));
+    for event in events {
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n", xml_escape(&event.title)));
+        if let Some(link) = event
+            .registration_url
+            .as_ref()
+            .or(event.website_url.as_ref())
+        {
+            xml.push_str(&format!("<link>{}</link>\n", xml_escape(link)));
+        }
+        xml.push_str(&format!(
+            "<guid isPermaLink=\"false\">event-{}@airstreamclub.org</guid>\n",
+            event.uid
+        ));
+        if let Some(created) = chrono::DateTime::from_timestamp(event.created, 0) {
+            xml.push_str(&format!("<pubDate>{}</pubDate>\n", created.to_rfc2822()));
+        }
+        if let Some(body) = &event.body {
+            xml.push_str(&format!(
+                "<description>{}</description>\n",
+                xml_escape(body)
+            ));
+        }
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+/// Escape a TEXT property value
+fn ics_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at 75 octets without splitting UTF-8 characters
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(uid: u64, title: &str) -> Event {
+        Event::test(uid, title)
+    }
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_events_sql_summary_skips_text() {
+        let full = events_sql("NULL", true, true);
+        assert!(full.contains("body.body_value AS body"));
+        assert!(full.contains("JOIN node__body body"));
+
+        let summary = events_sql("NULL", true, false);
+        assert!(summary.contains("NULL AS body"));
+        assert!(summary.contains("NULL AS description"));
+        assert!(!summary.contains("desc_f") && !summary.contains("node__body"));
+    }
+
+    #[test]
+    fn test_events_sql_without_image_field() {
+        let with_image = events_sql("NULL", true, true);
+        assert!(with_image.contains("CAST(img_file.uri AS CHAR(255)) AS image_uri"));
+        assert!(with_image.contains("JOIN node__field_image img"));
+
+        let without = events_sql("NULL", false, true);
+        assert!(without.contains("NULL AS image_uri"));
+        assert!(!without.contains("node__field_image") && !without.contains("img_file"));
+    }
+
+    #[test]
+    fn test_field_joins_exclude_deleted_rows() {
+        use crate::sql_audit::unguarded_field_joins;
+        for (with_image, with_text) in [(true, true), (true, false), (false, true)] {
+            let sql = events_sql("NULL", with_image, with_text);
+            assert_eq!(unguarded_field_joins(&sql), Vec::<String>::new(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let mut timed = event(1, "Spring Rally; Day 1, Potluck");
+        timed.start_date = Some(datetime("2025-04-05 18:00"));
+        timed.end_date = Some(datetime("2025-04-05 21:00"));
+        timed.location_name = Some("Camp Hill".to_string());
+        timed.address = Some("1 Main St".to_string());
+        timed.website_url = Some("https://example.com/rally".to_string());
+
+        let mut all_day = event(2, "Caravan");
+        all_day.start_date = Some(datetime("2025-05-01 00:00"));
+
+        let no_start = event(3, "TBD");
+
+        let ics = to_ics(&[timed, all_day, no_start]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:event-1@airstreamclub.org\r\n"));
+        assert!(ics.contains("DTSTART:20250405T180000\r\nDTEND:20250405T210000\r\n"));
+        assert!(ics.contains("SUMMARY:Spring Rally\\; Day 1\\, Potluck\r\n"));
+        assert!(ics.contains("LOCATION:Camp Hill\\, 1 Main St\r\n"));
+        assert!(ics.contains("URL:https://example.com/rally\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20250501\r\nDTEND;VALUE=DATE:20250502\r\n"));
+        assert!(!ics.contains("event-3@"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    }
+
+    #[test]
+    fn test_to_rss() {
+        let mut rally = event(1, "Fish & Chips <Rally>");
+        rally.registration_url = Some("https://example.com/register?a=1&b=2".to_string());
+        rally.website_url = Some("https://example.com/".to_string());
+        rally.body = Some("<p>Bring a chair</p>".to_string());
+
+        let rss = to_rss("Heart of Texas", &[rally, event(2, "Meeting")]);
+        assert!(rss.contains("<title>Heart of Texas Events</title>"));
+        assert!(rss.contains("<title>Fish &amp; Chips &lt;Rally&gt;</title>"));
+        assert!(rss.contains("<link>https://example.com/register?a=1&amp;b=2</link>"));
+        assert!(rss.contains("<description>&lt;p&gt;Bring a chair&lt;/p&gt;</description>"));
+        assert!(rss.contains("<pubDate>Tue, 14 Nov 2023 22:13:20 +0000</pubDate>"));
+        assert_eq!(rss.matches("<item>").count(), 2);
+    }
+
+    #[test]
+    fn test_ics_fold() {
+        let line = format!("SUMMARY:{}", "x".repeat(100));
+        let folded = ics_fold(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].len(), 75);
+        assert!(parts[1].starts_with(' '));
+    }
+
+    #[test]
+    fn test_with_path() {
+        let club = Club {
+            uid: 10,
+            number: Some(42),
+            name: "Heart of Texas Club".to_string(),
+            region: Some(3),
+            region_name: None,
+            founded: None,
+            active: true,
+        };
+        let clubs = HashMap::from([(10, &club)]);
+        let region_names = HashMap::from([(3, Some("Region 3".to_string()))]);
+        let owned = |owner_uid, node_type: &str| {
+            let mut event = event(1, "Rally");
+            event.owner_uid = Some(owner_uid);
+            event.owner_node_type = Some(node_type.to_string());
+            event
+        };
+
+        let club_event = with_path(owned(10, "ssp_club"), &clubs, &region_names);
+        assert_eq!(club_event.region_name.as_deref(), Some("Region 3"));
+        assert_eq!(club_event.club_name.as_deref(), Some("Heart of Texas Club"));
+
+        let region_event = with_path(owned(3, "ssp_region"), &clubs, &region_names);
+        assert_eq!(region_event.region_name.as_deref(), Some("Region 3"));
+        assert_eq!(region_event.club_name, None);
+
+        let international = with_path(event(2, "Rally"), &clubs, &region_names);
+        assert_eq!(international.region_name, None);
+        assert_eq!(international.club_name, None);
+    }
+
+    #[test]
+    fn test_start_utc() {
+        let mut summer = event(1, "Rally");
+        summer.start_date = Some(datetime("2025-07-04 09:00"));
+        assert_eq!(
+            summer.start_utc().unwrap().naive_utc(),
+            datetime("2025-07-04 13:00")
+        );
+
+        summer.timezone = Some("America/Chicago".to_string());
+        assert_eq!(
+            summer.start_utc().unwrap().naive_utc(),
+            datetime("2025-07-04 14:00")
+        );
+
+        let mut winter = event(2, "Rally");
+        winter.start_date = Some(datetime("2025-01-10 09:00"));
+        winter.timezone = Some("Not/AZone".to_string());
+        assert_eq!(
+            winter.start_utc().unwrap().naive_utc(),
+            datetime("2025-01-10 14:00")
+        );
+
+        // Falls in the spring-forward gap
+        winter.start_date = Some(datetime("2025-03-09 02:30"));
+        assert_eq!(winter.start_utc(), None);
+        assert_eq!(event(3, "Rally").start_utc(), None);
+    }
+
+    #[test]
+    fn test_event_tags() {
+        let tags: Vec<String> = EventTags {
+            tags: Some("rally|social ".to_string()),
+        }
+        .into();
+        assert_eq!(tags, vec!["rally".to_string(), "social".to_string()]);
+
+        let tags: Vec<String> = EventTags { tags: None }.into();
+        assert!(tags.is_empty());
+    }
 }
@@ -0,0 +1,148 @@
+//! Raw field values of a node, for debugging exports that map a field wrong.
+//!
+//! Field tables are discovered through `information_schema`, so fields added
+//! in Drupal show up without a code change.
+
+use crate::{Db, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Every `node__field_*` value of a node, keyed by field name (e.g.
+/// `field_club_number`), one object per delta with the column prefix removed
+/// (`value`, `target_id`, `uri`, ...). Fields without a value are left out.
+pub type NodeFields = BTreeMap<String, Vec<Value>>;
+
+#[derive(Debug, sqlx::FromRow)]
+struct FieldColumn {
+    table_name: String,
+    column_name: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct FieldValueRow {
+    table_name: String,
+    value: String,
+}
+
+/// Fetch all field values of node `nid` (deleted values excluded)
+pub async fn node_fields(db: &Db, nid: u64) -> Result<NodeFields> {
+    let columns: Vec<FieldColumn> = db
+        .fetch("field tables", async || {
+            sqlx::query_as(
+                r#"
+        SELECT
+            CAST(TABLE_NAME AS CHAR) AS table_name,
+            CAST(COLUMN_NAME AS CHAR) AS column_name
+        FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+          AND TABLE_NAME LIKE 'node\_\_field\_%'
+          AND COLUMN_NAME LIKE 'field\_%'
+        ORDER BY TABLE_NAME, ORDINAL_POSITION
+        "#,
+            )
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?;
+
+    let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for column in columns {
+        tables
+            .entry(column.table_name)
+            .or_default()
+            .push(column.column_name);
+    }
+    if tables.is_empty() {
+        return Ok(NodeFields::new());
+    }
+
+    let rows: Vec<FieldValueRow> = db
+        .fetch("field values", async || {
+            // One UNION ALL over every field table, each row's columns as a JSON object
+            let mut query = sqlx::QueryBuilder::new("");
+            for (i, (table, columns)) in tables.iter().enumerate() {
+                if i > 0 {
+                    query.push(" UNION ALL ");
+                }
+                query
+                    .push(field_select(table, columns))
+                    .push(" WHERE entity_id = ")
+                    .push_bind(nid)
+                    .push(" AND deleted = 0");
+            }
+            query.push(" ORDER BY table_name, delta");
+            query.build_query_as().fetch_all(db.pool()).await
+        })
+        .await?;
+
+    let mut fields = NodeFields::new();
+    for row in rows {
+        let field = row.table_name.trim_start_matches("node__").to_string();
+        let value = serde_json::from_str(&row.value).unwrap_or(Value::String(row.value));
+        let value = strip_column_prefix(&field, value);
+        fields.entry(field).or_default().push(value);
+    }
+    Ok(fields)
+}
+
+/// `SELECT` of one field table's value columns as a JSON object
+fn field_select(table: &str, columns: &[String]) -> String {
+    let pairs: Vec<String> = columns
+        .iter()
+        .map(|column| format!("'{column}', {}", quote_ident(column)))
+        .collect();
+    format!(
+        "SELECT '{table}' AS table_name, delta, CAST(JSON_OBJECT({}) AS CHAR) AS value FROM {}",
+        pairs.join(", "),
+        quote_ident(table)
+    )
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// `{"field_date_value": ..}` -> `{"value": ..}` for field `field_date`
+fn strip_column_prefix(field: &str, value: Value) -> Value {
+    let Value::Object(columns) = value else {
+        return value;
+    };
+    let prefix = format!("{field}_");
+    let columns: Map<String, Value> = columns
+        .into_iter()
+        .map(|(column, value)| match column.strip_prefix(&prefix) {
+            Some(short) => (short.to_string(), value),
+            None => (column, value),
+        })
+        .collect();
+    Value::Object(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_field_select() {
+        let columns = vec![
+            "field_date_value".to_string(),
+            "field_date_end_value".to_string(),
+        ];
+        assert_eq!(
+            field_select("node__field_date", &columns),
+            "SELECT 'node__field_date' AS table_name, delta, CAST(JSON_OBJECT(\
+             'field_date_value', `field_date_value`, 'field_date_end_value', `field_date_end_value`\
+             ) AS CHAR) AS value FROM `node__field_date`"
+        );
+    }
+
+    #[test]
+    fn test_strip_column_prefix() {
+        let value = json!({"field_link_uri": "https://example.com", "field_link_title": null});
+        assert_eq!(
+            strip_column_prefix("field_link", value),
+            json!({"uri": "https://example.com", "title": null})
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use crate::{Error, Result, users::User};
+use crate::{Db, Error, Result, users::User};
 use chrono::NaiveDate;
-use sqlx::{MySql, MySqlPool, QueryBuilder};
+use sqlx::{MySql, QueryBuilder};
 
 /// Filter for leadership queries by date
 #[derive(Debug, Clone, Default)]
@@ -20,7 +20,7 @@ pub struct Leadership {
     #[sqlx(flatten, try_from = "RoleFromRow")]
     pub role: Role,
     pub start_date: chrono::NaiveDate,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub end_date: Option<chrono::NaiveDate>,
     #[sqlx(flatten)]
     pub user: User,
@@ -131,85 +131,141 @@ fn fetch_leadership_query<'builder>(
 }
 
 async fn fetch_leadership_for_type(
-    pool: &MySqlPool,
+    db: &Db,
     entity_type: &str,
     entity_id: Option<u64>,
     filter: DateFilter,
 ) -> Result<Vec<Leadership>> {
-    use futures::TryFutureExt;
-
     // Standing committees don't have explicit roles - they use implicit "Chair" role
     let require_role = entity_type != "ssp_standing_committees";
-    let mut query = fetch_leadership_query(&filter, require_role);
 
-    if let Some(id) = entity_id {
-        query.push(" AND entity.nid = ").push_bind(id);
-    }
+    db.fetch("leadership", async || {
+        let mut query = fetch_leadership_query(&filter, require_role);
 
-    query.push(" AND entity.type = ").push_bind(entity_type);
+        if let Some(id) = entity_id {
+            query.push(" AND entity.nid = ").push_bind(id);
+        }
 
-    query
-        .build_query_as::<Leadership>()
-        .fetch_all(pool)
-        .map_err(Error::from)
-        .await
+        query.push(" AND entity.type = ").push_bind(entity_type);
+
+        query
+            .build_query_as::<Leadership>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
-pub async fn for_club(pool: &MySqlPool, uid: u64, filter: DateFilter) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_club", Some(uid), filter).await
+pub async fn for_club(db: &Db, uid: u64, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_club", Some(uid), filter).await
 }
 
-pub async fn for_all_clubs(pool: &MySqlPool, filter: DateFilter) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_club", None, filter).await
+pub async fn for_all_clubs(db: &Db, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_club", None, filter).await
 }
 
-pub async fn for_region(pool: &MySqlPool, uid: u64, filter: DateFilter) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_region", Some(uid), filter).await
+pub async fn for_region(db: &Db, uid: u64, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_region", Some(uid), filter).await
 }
 
-pub async fn for_all_regions(pool: &MySqlPool, filter: DateFilter) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_region", None, filter).await
+pub async fn for_all_regions(db: &Db, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_region", None, filter).await
 }
 
 pub async fn for_club_by_number(
-    pool: &MySqlPool,
+    db: &Db,
     number: i32,
     filter: DateFilter,
 ) -> Result<Vec<Leadership>> {
-    let club = crate::clubs::by_number(pool, number)
+    let club = crate::clubs::by_number(db, number)
         .await?
         .ok_or_else(|| Error::Request(sqlx::Error::RowNotFound))?;
-    for_club(pool, club.uid, filter).await
+    for_club(db, club.uid, filter).await
 }
 
 pub async fn for_region_by_number(
-    pool: &MySqlPool,
+    db: &Db,
     number: i32,
     filter: DateFilter,
 ) -> Result<Vec<Leadership>> {
-    let region = crate::regions::by_number(pool, number)
+    let region = crate::regions::by_number(db, number)
         .await?
         .ok_or_else(|| Error::Request(sqlx::Error::RowNotFound))?;
-    for_region(pool, region.uid, filter).await
+    for_region(db, region.uid, filter).await
 }
 
-pub async fn for_international(pool: &MySqlPool, filter: DateFilter) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_international_leadership", None, filter).await
+pub async fn for_international(db: &Db, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_international_leadership", None, filter).await
 }
 
 pub async fn for_standing_committee(
-    pool: &MySqlPool,
+    db: &Db,
     uid: u64,
     filter: DateFilter,
 ) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_standing_committees", Some(uid), filter).await
+    fetch_leadership_for_type(db, "ssp_standing_committees", Some(uid), filter).await
 }
 
-pub async fn for_all_standing_committees(
-    pool: &MySqlPool,
-    filter: DateFilter,
-) -> Result<Vec<Leadership>> {
-    fetch_leadership_for_type(pool, "ssp_standing_committees", None, filter).await
+pub async fn for_all_standing_committees(db: &Db, filter: DateFilter) -> Result<Vec<Leadership>> {
+    fetch_leadership_for_type(db, "ssp_standing_committees", None, filter).await
+}
+
+/// A current club officer's email, for governance announcements
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct OfficerEmail {
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_number: Option<i64>,
+    pub club_name: String,
+    pub role_name: String,
+    /// First and last name, empty when neither is set
+    pub user_name: String,
+    pub email: String,
+}
+
+/// Email list of current club officers, optionally only those holding
+/// `role` (e.g. `"President"`, matched case-insensitively).
+///
+/// Officers without an email are skipped. Sorted by club number, then role.
+/// Club names and numbers come from `ctx`.
+pub async fn officer_emails(
+    ctx: &mut crate::SyncContext,
+    role: Option<&str>,
+) -> Result<Vec<OfficerEmail>> {
+    let leadership = for_all_clubs(ctx.db(), DateFilter::Current).await?;
+    let clubs = ctx.clubs().await?;
+    Ok(officer_emails_from(leadership, clubs, role))
+}
+
+fn officer_emails_from(
+    leadership: Vec<Leadership>,
+    clubs: &[crate::clubs::Club],
+    role: Option<&str>,
+) -> Vec<OfficerEmail> {
+    let clubs: std::collections::HashMap<u64, &crate::clubs::Club> =
+        clubs.iter().map(|club| (club.uid, club)).collect();
+    let mut emails: Vec<OfficerEmail> = leadership
+        .into_iter()
+        .filter(|officer| role.is_none_or(|role| officer.role.title.eq_ignore_ascii_case(role)))
+        .filter(|officer| !officer.user.email.trim().is_empty())
+        .map(|officer| {
+            let club = clubs.get(&officer.entity_uid);
+            let user_name = officer.user.full_name();
+            OfficerEmail {
+                club_number: club.and_then(|club| club.number),
+                club_name: club.map(|club| club.name.clone()).unwrap_or_default(),
+                role_name: officer.role.title,
+                user_name,
+                email: officer.user.email.trim().to_string(),
+            }
+        })
+        .collect();
+    emails.sort_by(|a, b| {
+        a.club_number
+            .cmp(&b.club_number)
+            .then_with(|| a.role_name.cmp(&b.role_name))
+            .then_with(|| a.user_name.cmp(&b.user_name))
+    });
+    emails
 }
 
 pub mod db {
@@ -237,3 +293,75 @@ pub mod db {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clubs::Club;
+
+    #[test]
+    fn test_leadership_query_selects_every_user_field() {
+        let missing = crate::sql_audit::missing_aliases(FETCH_LEADERSHIP_BASE, &User::columns());
+        assert_eq!(missing, Vec::<&str>::new());
+    }
+
+    fn officer(club_uid: u64, role: &str, email: &str, first_name: Option<&str>) -> Leadership {
+        let mut user = User::test(1, email);
+        user.first_name = first_name.map(String::from);
+        user.last_name = Some("Smith".to_string());
+        Leadership {
+            entity_uid: club_uid,
+            role: Role {
+                uid: 1,
+                title: role.to_string(),
+            },
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            end_date: None,
+            user,
+        }
+    }
+
+    fn club(uid: u64, number: i64) -> Club {
+        Club {
+            uid,
+            number: Some(number),
+            name: format!("Club {number}"),
+            region: None,
+            region_name: None,
+            founded: None,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_officer_emails_from() {
+        let leadership = vec![
+            officer(20, "President", "b@example.com", Some("Bo")),
+            officer(10, "Treasurer", "t@example.com", None),
+            officer(10, "president", " a@example.com ", Some("Al")),
+            officer(10, "President", "", Some("No Email")),
+        ];
+        let clubs = [club(10, 1), club(20, 2)];
+
+        let emails = officer_emails_from(leadership, &clubs, Some("President"));
+        assert_eq!(
+            emails,
+            vec![
+                OfficerEmail {
+                    club_number: Some(1),
+                    club_name: "Club 1".to_string(),
+                    role_name: "president".to_string(),
+                    user_name: "Al Smith".to_string(),
+                    email: "a@example.com".to_string(),
+                },
+                OfficerEmail {
+                    club_number: Some(2),
+                    club_name: "Club 2".to_string(),
+                    role_name: "President".to_string(),
+                    user_name: "Bo Smith".to_string(),
+                    email: "b@example.com".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,19 +1,44 @@
 mod error;
+pub use context::SyncContext;
+pub use db::Db;
 pub use error::{Error, Result};
+pub use limiter::Limiter;
+pub use retry::RetryPolicy;
+pub use shard::Shard;
+pub use ssl::{SslMode, SslOptions};
 
 pub mod addresses;
 pub mod airstreams;
 pub mod brns;
+mod chunked;
 pub mod clubs;
+pub mod context;
+mod db;
 pub mod events;
+pub mod inspect;
 pub mod leadership;
+mod limiter;
 pub mod members;
+pub mod menus;
 pub mod microsites;
+pub mod owner;
+pub mod progress;
 pub mod races;
 pub mod rallies;
 pub mod regions;
+mod retry;
 pub mod roles;
+pub mod seed;
+pub mod ser;
+mod shard;
+pub mod slug;
+#[cfg(test)]
+mod sql_audit;
+mod ssl;
 pub mod standing_committees;
+pub mod taxonomy;
+mod timing;
+pub mod user_data;
 pub mod users;
 
 /// A type alias for `Future` that may return `crate::error::Error`
@@ -22,8 +47,21 @@ pub type Future<'a, T> = futures::future::BoxFuture<'a, Result<T>>;
 /// A type alias for `Stream` that may result in `crate::error::Error`
 pub type Stream<'a, T> = futures::stream::BoxStream<'a, Result<T>>;
 
-pub async fn connect(url: &str) -> Result<sqlx::MySqlPool> {
-    use sqlx::{ConnectOptions, Executor, MySqlPool, mysql::MySqlConnectOptions};
+pub async fn connect(url: &str) -> Result<Db> {
+    connect_with_ssl(url, &SslOptions::default()).await
+}
+
+/// Connect with TLS options that can't be expressed in the URL (CA bundle path)
+pub async fn connect_with_ssl(url: &str, ssl: &SslOptions) -> Result<Db> {
+    connect_limited(url, ssl, None).await
+}
+
+/// [`connect_with_ssl`], optionally rate limiting queries with `limiter`.
+///
+/// Queries are retried with the default [`RetryPolicy`]; see
+/// [`Db::with_retry_policy`].
+pub async fn connect_limited(url: &str, ssl: &SslOptions, limiter: Option<&Limiter>) -> Result<Db> {
+    use sqlx::{ConnectOptions, Executor, mysql::MySqlConnectOptions, mysql::MySqlPoolOptions};
     use std::time::Duration;
 
     // Parse URL and set slow query threshold to 10s (default is 1s)
@@ -31,8 +69,13 @@ pub async fn connect(url: &str) -> Result<sqlx::MySqlPool> {
     let options: MySqlConnectOptions = url
         .parse::<MySqlConnectOptions>()?
         .log_slow_statements(log::LevelFilter::Warn, Duration::from_secs(10));
+    let options = ssl.apply(options);
 
-    let pool = MySqlPool::connect_with(options).await?;
+    let mut pool_options = MySqlPoolOptions::new();
+    if let Some(limiter) = limiter {
+        pool_options = limiter.apply(pool_options);
+    }
+    let pool = pool_options.connect_with(options).await?;
     let _ = pool
         .execute(
             r#"
@@ -41,5 +84,31 @@ pub async fn connect(url: &str) -> Result<sqlx::MySqlPool> {
         "#,
         )
         .await?;
-    Ok(pool)
+    Ok(Db::new(pool))
+}
+
+/// Tables nearly every export reads first
+const PREFETCH_TABLES: &[&str] = &["node_field_data", "users_field_data", "file_managed"];
+
+/// Touch the hot Drupal tables with cheap `LIMIT 1` reads so a cold MySQL
+/// instance (e.g. the first run after a serverless cold start) opens them
+/// before the real export starts.
+///
+/// Returns how long the warm-up took, which is also logged. This is the cost
+/// of warming, not the time it saves the export afterwards.
+pub async fn prefetch(db: &Db) -> Result<std::time::Duration> {
+    let started = std::time::Instant::now();
+    for table in PREFETCH_TABLES {
+        let sql = format!("SELECT 1 FROM {table} LIMIT 1");
+        db.fetch("prefetch", async || {
+            sqlx::query(&sql).fetch_optional(db.pool()).await
+        })
+        .await?;
+    }
+    let elapsed = started.elapsed();
+    log::info!(
+        "warm-up of {} tables took {elapsed:?}",
+        PREFETCH_TABLES.len()
+    );
+    Ok(elapsed)
 }
@@ -0,0 +1,95 @@
+//! Query rate limit for running against the production primary.
+//!
+//! The limiter is applied to the pool rather than to individual fetch
+//! functions: every connection checkout waits for the next free slot, so a
+//! bulk `fetch_all` counts as one query. Clones share the same schedule, so
+//! concurrent exports together stay under the ceiling.
+
+use sqlx::mysql::MySqlPoolOptions;
+use std::{
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+#[derive(Debug, Clone)]
+pub struct Limiter {
+    interval: Duration,
+    /// Earliest instant the next query may start
+    next: Arc<Mutex<Instant>>,
+}
+
+impl Limiter {
+    /// Allow at most `qps` queries per second, evenly spaced
+    pub fn per_second(qps: NonZeroU32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / qps.get(),
+            next: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Wait for the next free slot
+    pub async fn acquire(&self) {
+        let slot = self.reserve(Instant::now());
+        tokio::time::sleep_until(slot).await;
+    }
+
+    /// Claim the next slot at or after `now`
+    fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let slot = (*next).max(now);
+        *next = slot + self.interval;
+        slot
+    }
+
+    /// Rate limit every connection checkout from a pool built with `options`.
+    ///
+    /// Reused connections go through `before_acquire` and new ones through
+    /// `after_connect`, so each checkout waits exactly once.
+    pub fn apply(&self, options: MySqlPoolOptions) -> MySqlPoolOptions {
+        let on_acquire = self.clone();
+        let on_connect = self.clone();
+        options
+            .before_acquire(move |_conn, _meta| {
+                let limiter = on_acquire.clone();
+                Box::pin(async move {
+                    limiter.acquire().await;
+                    Ok(true)
+                })
+            })
+            .after_connect(move |_conn, _meta| {
+                let limiter = on_connect.clone();
+                Box::pin(async move {
+                    limiter.acquire().await;
+                    Ok(())
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve_spaces_slots() {
+        let limiter = Limiter::per_second(NonZeroU32::new(4).unwrap());
+        let now = Instant::now();
+        let slots: Vec<Duration> = (0..3)
+            .map(|_| limiter.reserve(now).saturating_duration_since(now))
+            .collect();
+        assert_eq!(
+            slots,
+            vec![
+                Duration::ZERO,
+                Duration::from_millis(250),
+                Duration::from_millis(500)
+            ]
+        );
+
+        // After an idle period the next query runs immediately
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve(later), later);
+    }
+}
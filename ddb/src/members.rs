@@ -1,44 +1,128 @@
-use crate::{Result, clubs, clubs::Club, users::User};
+use crate::{
+    Db, Result, Shard, clubs,
+    clubs::Club,
+    progress::{self, Progress},
+    users::User,
+};
 use chrono::NaiveDate;
 use itertools::Itertools;
-use sqlx::{MySql, MySqlPool};
+use sqlx::MySql;
 use std::{collections::HashMap, fmt};
 
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Member>> {
-    let all = fetch_members_query()
-        .push(" AND paragraphs_item_field_data.parent_field_name = 'field_home_club'")
-        .build_query_as::<Member>()
-        .fetch_all(pool)
+pub async fn all(db: &Db) -> Result<Vec<Member>> {
+    all_with_progress(db, None).await
+}
+
+/// [`all`], reporting the rows received to `progress` as they stream in
+pub async fn all_with_progress(db: &Db, progress: Progress<'_>) -> Result<Vec<Member>> {
+    let all = db
+        .fetch("members", async || {
+            progress::collect(
+                "members",
+                fetch_members_query()
+                    .build_query_as::<Member>()
+                    .fetch(db.pool()),
+                progress,
+            )
+            .await
+        })
         .await?;
-    Ok(dedupe_members(all))
+    Ok(home_club_members(all))
+}
+
+/// Number of distinct members behind [`all`] (or [`all_in_shard`] with
+/// `shard`), without fetching them.
+///
+/// Counts the users with a current home club membership, under the same
+/// conditions as the export but without its member and club detail joins.
+/// Members sharing an email are only merged after fetching, so this can be
+/// slightly higher than the length of the export.
+pub async fn count(db: &Db, shard: Option<Shard>) -> Result<u64> {
+    db.fetch("members", async || {
+        let mut query = sqlx::QueryBuilder::new(COUNT_HOME_CLUB_MEMBERS_QUERY);
+        if let Some(shard) = shard {
+            shard.push_filter(&mut query, "users_field_data.uid");
+        }
+        query.build_query_scalar().fetch_one(db.pool()).await
+    })
+    .await
+}
+
+/// [`all_with_progress`] limited to the members in `shard`. Duplicates of a
+/// member all have the same uid, so they land in the same shard and are
+/// still merged.
+pub async fn all_in_shard(db: &Db, shard: Shard, progress: Progress<'_>) -> Result<Vec<Member>> {
+    let all = db
+        .fetch("members", async || {
+            let mut query = fetch_members_query();
+            shard.push_filter(&mut query, "users_field_data.uid");
+            progress::collect(
+                "members",
+                query.build_query_as::<Member>().fetch(db.pool()),
+                progress,
+            )
+            .await
+        })
+        .await?;
+    Ok(home_club_members(all))
 }
 
-pub async fn by_club(pool: &MySqlPool, uid: u64) -> Result<Vec<Member>> {
-    let all = fetch_club_members_query()
-        .build_query_as::<Member>()
-        .bind(Some(uid))
-        .bind(Some(uid))
-        .bind(None::<u64>)
-        .fetch_all(pool)
+pub async fn by_club(db: &Db, uid: u64) -> Result<Vec<Member>> {
+    let all = db
+        .fetch("members", async || {
+            fetch_club_members_query()
+                .build_query_as::<Member>()
+                .bind(Some(uid))
+                .bind(Some(uid))
+                .bind(None::<u64>)
+                .fetch_all(db.pool())
+                .await
+        })
         .await?;
 
     Ok(dedupe_members(all))
 }
 
-pub async fn by_region(pool: &MySqlPool, uid: u64) -> Result<Vec<Member>> {
-    let all = fetch_club_members_query()
-        .build_query_as::<Member>()
-        .bind(None::<u64>)
-        .bind(None::<u64>)
-        .bind(Some(uid))
-        .fetch_all(pool)
+pub async fn by_region(db: &Db, uid: u64) -> Result<Vec<Member>> {
+    let all = db
+        .fetch("members", async || {
+            fetch_club_members_query()
+                .build_query_as::<Member>()
+                .bind(None::<u64>)
+                .bind(None::<u64>)
+                .bind(Some(uid))
+                .fetch_all(db.pool())
+                .await
+        })
         .await?;
 
     Ok(dedupe_members(all))
 }
 
-/// Remove affiliates in the given members list that are also regualr members
+/// Collapse the per-club rows of each user into one member, then remove
+/// affiliates in the given members list that are also regualr members.
+///
+/// A user in several clubs comes back once per club; the regular (home club)
+/// row is kept and the numbers of all the user's clubs are collected into
+/// [`Member::club_numbers`], home club first.
 pub fn dedupe_members(members: Vec<Member>) -> Vec<Member> {
+    dedupe_merged(merge_club_rows(members))
+}
+
+/// The members behind the rows of every membership: users with a home club,
+/// with all of their clubs in [`Member::club_numbers`]. Users who are only
+/// affiliate or intraclub members of some club are not members themselves.
+fn home_club_members(rows: Vec<Member>) -> Vec<Member> {
+    dedupe_merged(
+        merge_club_rows(rows)
+            .into_iter()
+            .filter(|member| member.member_type == MemberType::Regular)
+            .collect(),
+    )
+}
+
+/// [`dedupe_members`] for members already merged per uid
+fn dedupe_merged(members: Vec<Member>) -> Vec<Member> {
     let (regulars, mut affiliates): (Vec<Member>, Vec<Member>) = members
         .into_iter()
         .partition(|member| member.member_type != MemberType::Affiliate);
@@ -54,29 +138,205 @@ pub fn dedupe_members(members: Vec<Member>) -> Vec<Member> {
     member_map.into_values().collect()
 }
 
-pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<Member>> {
-    let member = fetch_members_query()
-        .push("AND paragraphs_item_field_data.parent_field_name = 'field_home_club'")
-        .push("AND users_field_data.uid = ")
-        .push_bind(uid)
-        .build_query_as::<Member>()
-        .fetch_optional(pool)
+/// One member per user uid, with the club numbers of all of the user's rows:
+/// home club first, then intraclub and affiliate clubs
+fn merge_club_rows(rows: Vec<Member>) -> Vec<Member> {
+    let mut by_uid: HashMap<u64, Vec<Member>> = HashMap::new();
+    for row in rows {
+        by_uid.entry(row.primary.uid).or_default().push(row);
+    }
+    by_uid
+        .into_values()
+        .map(|mut rows| {
+            rows.sort_by_key(|row| {
+                (
+                    row.member_type != MemberType::Regular,
+                    row.member_type == MemberType::Affiliate,
+                    row.local_club.number,
+                )
+            });
+            let club_numbers: Vec<i64> = rows
+                .iter()
+                .filter_map(|row| row.local_club.number)
+                .unique()
+                .collect();
+            let mut member = rows.swap_remove(0);
+            member.club_numbers = club_numbers;
+            member
+        })
+        .collect()
+}
+
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<Member>> {
+    let member = db
+        .fetch("members", async || {
+            fetch_members_query()
+                .push("AND paragraphs_item_field_data.parent_field_name = 'field_home_club'")
+                .push("AND users_field_data.uid = ")
+                .push_bind(uid)
+                .build_query_as::<Member>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(member)
 }
 
-pub async fn by_email(pool: &MySqlPool, email: &str) -> Result<Option<Member>> {
-    let member = fetch_members_query()
-        .push("AND users_field_data.mail = ")
-        .push_bind(email)
-        .build_query_as::<Member>()
-        .fetch_optional(pool)
+pub async fn by_email(db: &Db, email: &str) -> Result<Option<Member>> {
+    let member = db
+        .fetch("members", async || {
+            fetch_members_query()
+                .push("AND users_field_data.mail = ")
+                .push_bind(email)
+                .build_query_as::<Member>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(member)
 }
 
+// ========== Member Directory ==========
+
+/// Denormalized member directory row: contact details, mailing address,
+/// current clubs and BRNs in one record
+#[derive(Debug, serde::Serialize)]
+pub struct MemberRecord {
+    pub uid: u64,
+    pub email: String,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub last_name: Option<String>,
+    pub member_class: MemberClass,
+    pub member_type: MemberType,
+    pub member_status: MemberStatus,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub partner: Option<User>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub expiration_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub member_since: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub address: Option<Address>,
+    /// Current club memberships, home club first
+    pub clubs: Vec<MemberClub>,
+    pub brns: Vec<String>,
+}
+
+/// Current membership in a club, as listed in a [`MemberRecord`]
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct MemberClub {
+    #[serde(skip)]
+    user_uid: u64,
+    pub club_uid: u64,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_number: Option<i64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_name: Option<String>,
+    #[sqlx(try_from = "String")]
+    pub member_type: MemberType,
+}
+
+/// Fetch the member directory: every current member (as in [`all`]) with
+/// their mailing address, all current club memberships and BRNs.
+///
+/// Members are merged by email across the whole table first, so the queries
+/// all complete before the first record; each record is only assembled as
+/// the iterator reaches it.
+pub async fn directory(db: &Db) -> Result<impl Iterator<Item = MemberRecord> + use<>> {
+    let members = all(db).await?;
+    let mut addresses = mailing_address::for_members(db, &members).await?;
+    let clubs: Vec<MemberClub> = db
+        .fetch("member clubs", async || {
+            sqlx::query_as(FETCH_CURRENT_CLUBS_QUERY)
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+    let mut clubs = clubs_by_user(clubs);
+
+    Ok(members.into_iter().map(move |member| MemberRecord {
+        uid: member.primary.uid,
+        address: addresses.remove(&member.primary.uid),
+        clubs: clubs.remove(&member.primary.uid).unwrap_or_default(),
+        email: member.primary.email,
+        first_name: member.primary.first_name,
+        last_name: member.primary.last_name,
+        member_class: member.member_class,
+        member_type: member.member_type,
+        member_status: member.member_status,
+        partner: member.partner,
+        expiration_date: member.expiration_date,
+        member_since: member.member_since,
+        brns: member.brns,
+    }))
+}
+
+/// Group club memberships by user, home club first then by club number
+fn clubs_by_user(clubs: Vec<MemberClub>) -> HashMap<u64, Vec<MemberClub>> {
+    let mut by_user: HashMap<u64, Vec<MemberClub>> = HashMap::new();
+    for club in clubs {
+        let user = by_user.entry(club.user_uid).or_default();
+        if !user
+            .iter()
+            .any(|existing| existing.club_uid == club.club_uid)
+        {
+            user.push(club);
+        }
+    }
+    for user in by_user.values_mut() {
+        user.sort_by_key(|club| (club.member_type != MemberType::Regular, club.club_number));
+    }
+    by_user
+}
+
+/// Currently active membership paragraphs (joined, not yet left) with the
+/// user field that references them, one row per user and club
+const FETCH_CURRENT_CLUBS_QUERY: &str = r#"
+SELECT DISTINCT
+    CAST(p.parent_id AS UNSIGNED) AS user_uid,
+    pc.field_club_target_id AS club_uid,
+    CAST(cnum.field_club_number_value AS SIGNED) AS club_number,
+    club.title AS club_name,
+    CASE
+        WHEN uhc.entity_id IS NOT NULL THEN 'regular'
+        WHEN uic.entity_id IS NOT NULL THEN 'intraclub'
+        ELSE 'affiliate'
+    END AS member_type
+FROM paragraphs_item_field_data p
+INNER JOIN paragraph__field_club pc
+    ON pc.entity_id = p.id
+    AND pc.deleted = '0'
+INNER JOIN paragraph__field_join_date fjd
+    ON fjd.entity_id = p.id
+    AND fjd.deleted = '0'
+LEFT JOIN paragraph__field_leave_date fld
+    ON fld.entity_id = p.id
+    AND fld.deleted = '0'
+LEFT JOIN node_field_data club
+    ON club.nid = pc.field_club_target_id
+LEFT JOIN node__field_club_number cnum
+    ON cnum.entity_id = pc.field_club_target_id
+    AND cnum.deleted = '0'
+LEFT JOIN user__field_home_club uhc
+    ON uhc.field_home_club_target_id = p.id
+    AND uhc.deleted = '0'
+LEFT JOIN user__field_memberships uac
+    ON uac.field_memberships_target_id = p.id
+    AND uac.deleted = '0'
+LEFT JOIN user__field_intraclub_memberships uic
+    ON uic.field_intraclub_memberships_target_id = p.id
+    AND uic.deleted = '0'
+WHERE p.status = '1'
+    AND p.type = 'membership'
+    AND DATE(fjd.field_join_date_value) <= CURRENT_DATE
+    AND (fld.field_leave_date_value IS NULL OR DATE(fld.field_leave_date_value) >= CURRENT_DATE)
+    AND (uhc.entity_id IS NOT NULL OR uac.entity_id IS NOT NULL OR uic.entity_id IS NOT NULL)
+"#;
+
 // ========== Membership History (for portal sync) ==========
 
 /// Single membership period (one paragraph from DDB)
@@ -102,11 +362,27 @@ pub struct MembershipPeriod {
 /// Fetch all membership periods (full history, no date filtering)
 /// Unlike `all()` which returns current members only, this returns every
 /// membership paragraph for portal history sync
-pub async fn history_all(pool: &MySqlPool) -> Result<Vec<MembershipPeriod>> {
-    sqlx::query_as::<_, MembershipPeriod>(FETCH_MEMBERSHIP_HISTORY_QUERY)
-        .fetch_all(pool)
-        .await
-        .map_err(Into::into)
+pub async fn history_all(db: &Db) -> Result<Vec<MembershipPeriod>> {
+    db.fetch("membership history", async || {
+        sqlx::query_as::<_, MembershipPeriod>(FETCH_MEMBERSHIP_HISTORY_QUERY)
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Fetch the membership periods of one user, in any club
+pub async fn history_for_user(db: &Db, user_uid: u64) -> Result<Vec<MembershipPeriod>> {
+    db.fetch("membership history", async || {
+        sqlx::QueryBuilder::new(FETCH_MEMBERSHIP_HISTORY_QUERY)
+            .push(" WHERE am.user_uid = ")
+            .push_bind(user_uid)
+            .push(" ORDER BY am.join_date")
+            .build_query_as::<MembershipPeriod>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
 // ========== International Membership History (for portal sync) ==========
@@ -129,13 +405,15 @@ pub struct InternationalMembershipPeriod {
 }
 
 /// Fetch all international membership periods (full history, no date filtering)
-pub async fn international_history_all(
-    pool: &MySqlPool,
-) -> Result<Vec<InternationalMembershipPeriod>> {
-    sqlx::query_as::<_, InternationalMembershipPeriod>(FETCH_INTERNATIONAL_MEMBERSHIP_HISTORY_QUERY)
-        .fetch_all(pool)
+pub async fn international_history_all(db: &Db) -> Result<Vec<InternationalMembershipPeriod>> {
+    db.fetch("international membership history", async || {
+        sqlx::query_as::<_, InternationalMembershipPeriod>(
+            FETCH_INTERNATIONAL_MEMBERSHIP_HISTORY_QUERY,
+        )
+        .fetch_all(db.pool())
         .await
-        .map_err(Into::into)
+    })
+    .await
 }
 
 /// Query for international membership history
@@ -306,7 +584,16 @@ const FETCH_ALL_MEMBERS_QUERY: &str = r#"
         brns.brns_values AS brns,
 
         CAST(alldata.membership_expire AS DATE) as expiration_date,
-        CAST(alldata.membership_join_year AS DATE) as join_date
+        CAST(alldata.membership_join_year AS DATE) as join_date,
+        COALESCE(
+            (SELECT MIN(DATE(ifjd.field_join_date_value))
+             FROM paragraphs_item_field_data ip
+             INNER JOIN paragraph__field_join_date ifjd ON ifjd.entity_id = ip.id AND ifjd.deleted = '0'
+             WHERE ip.parent_id = users_field_data.uid
+                AND ip.type = 'ssp_international_membership'
+                AND ip.status = '1'),
+            DATE(FROM_UNIXTIME(users_field_data.created))
+        ) AS member_since
 
     FROM
     	paragraphs_item_field_data
@@ -357,6 +644,39 @@ const FETCH_ALL_MEMBERS_QUERY: &str = r#"
     AND(user_is_primary_member.field_primary_member_target_id IS NULL)
 "#;
 
+/// The conditions of [`FETCH_ALL_MEMBERS_QUERY`] for home club memberships,
+/// over the membership paragraphs and the tables those conditions need
+const COUNT_HOME_CLUB_MEMBERS_QUERY: &str = r#"
+    SELECT CAST(COUNT(DISTINCT users_field_data.uid) AS UNSIGNED)
+    FROM paragraphs_item_field_data membership
+    INNER JOIN paragraph__field_club club ON club.entity_id = membership.id
+        AND club.deleted = '0'
+    INNER JOIN node_field_data club_node ON club_node.nid = club.field_club_target_id
+    INNER JOIN paragraph__field_join_date join_date ON join_date.entity_id = membership.id
+        AND join_date.deleted = '0'
+    INNER JOIN paragraph__field_leave_date leave_date ON leave_date.entity_id = membership.id
+        AND leave_date.deleted = '0'
+    INNER JOIN user__field_home_club home_club ON home_club.field_home_club_target_id = membership.id
+        AND home_club.deleted = '0'
+    INNER JOIN users_field_data ON users_field_data.uid = membership.parent_id
+    INNER JOIN z_member_search_main alldata ON alldata.user_id = users_field_data.uid
+    WHERE membership.status = '1'
+        AND membership.type = 'membership'
+        AND membership.parent_field_name = 'field_home_club'
+        AND alldata.personal_status_id IN ('947', '951', '1099')
+        AND CAST(leave_date.field_leave_date_value AS DATE) >= DATE_SUB(NOW(), INTERVAL 1 YEAR)
+        AND CAST(join_date.field_join_date_value AS DATE) <= NOW()
+        AND EXISTS (
+            SELECT 1 FROM ssp_membership_international_membership international
+            WHERE international.user_id = users_field_data.uid
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM user__field_primary_member primary_member
+            WHERE primary_member.entity_id = users_field_data.uid
+            AND primary_member.field_primary_member_target_id IS NOT NULL
+        )
+"#;
+
 fn fetch_members_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
     sqlx::QueryBuilder::new(FETCH_ALL_MEMBERS_QUERY)
 }
@@ -495,6 +815,16 @@ SELECT
   md.personal_status_id                        AS member_status,
   earliest_join.earliest_join_date             AS join_date,
   flags.latest_expiration_date                 AS expiration_date,
+  COALESCE(
+    (SELECT MIN(DATE(ifjd.field_join_date_value))
+     FROM paragraphs_item_field_data ip
+     JOIN paragraph__field_join_date ifjd
+       ON ifjd.entity_id = ip.id AND ifjd.deleted = '0'
+     WHERE ip.parent_id = u.uid
+       AND ip.type = 'ssp_international_membership'
+       AND ip.status = '1'),
+    DATE(FROM_UNIXTIME(u.created))
+  )                                            AS member_since,
 
   /* ===================== CLUB FIELDS ===================== */
   CAST(cnum.field_club_number_value AS SIGNED) AS club_number,
@@ -562,32 +892,44 @@ fn fetch_club_members_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
 pub mod mailing_address {
     use super::*;
 
-    pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<Address>> {
-        let member = fetch_mailing_address_query()
-            .push("AND user__field_address.entity_id = ")
-            .push_bind(uid)
-            .build_query_as::<Address>()
-            .fetch_optional(pool)
+    pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<Address>> {
+        let member = db
+            .fetch("mailing addresses", async || {
+                fetch_mailing_address_query()
+                    .push("AND user__field_address.entity_id = ")
+                    .push_bind(uid)
+                    .build_query_as::<Address>()
+                    .fetch_optional(db.pool())
+                    .await
+            })
             .await?;
         Ok(member)
     }
 
     pub async fn by_uids<I: IntoIterator<Item = u64>>(
-        pool: &MySqlPool,
+        db: &Db,
         uids: I,
     ) -> Result<HashMap<u64, Address>> {
-        let mut builder = fetch_mailing_address_query();
-        let mut seperated = builder
-            .push("AND user__field_address.entity_id IN (")
-            .separated(", ");
-        for value in uids {
-            seperated.push_bind(value);
-        }
-        seperated.push_unseparated(") ");
-        let members: HashMap<u64, Address> = builder
-            .build_query_as::<Address>()
-            .fetch_all(pool)
-            .await?
+        let uids: Vec<u64> = uids.into_iter().collect();
+        let addresses = crate::chunked::chunked_in(&uids, db.in_chunk_size(), async |chunk| {
+            db.fetch("mailing addresses", async || {
+                let mut builder = fetch_mailing_address_query();
+                let mut seperated = builder
+                    .push("AND user__field_address.entity_id IN (")
+                    .separated(", ");
+                for value in chunk {
+                    seperated.push_bind(*value);
+                }
+                seperated.push_unseparated(") ");
+                builder
+                    .build_query_as::<Address>()
+                    .fetch_all(db.pool())
+                    .await
+            })
+            .await
+        })
+        .await?;
+        let members: HashMap<u64, Address> = addresses
             .into_iter()
             .filter_map(|address| address.user_id.map(|user_id| (user_id, address)))
             .collect();
@@ -596,16 +938,20 @@ pub mod mailing_address {
 
     /// Get addresses for given members primary user ids
     pub async fn for_members(
-        pool: &MySqlPool,
+        db: &Db,
         members: impl IntoIterator<Item = &Member>,
     ) -> Result<HashMap<u64, Address>> {
-        by_uids(pool, members.into_iter().map(|member| member.primary.uid)).await
+        by_uids(db, members.into_iter().map(|member| member.primary.uid)).await
     }
 
-    pub async fn all(pool: &MySqlPool) -> Result<Vec<Address>> {
-        let members = fetch_mailing_address_query()
-            .build_query_as::<Address>()
-            .fetch_all(pool)
+    pub async fn all(db: &Db) -> Result<Vec<Address>> {
+        let members = db
+            .fetch("mailing addresses", async || {
+                fetch_mailing_address_query()
+                    .build_query_as::<Address>()
+                    .fetch_all(db.pool())
+                    .await
+            })
             .await?;
         Ok(members)
     }
@@ -757,19 +1103,35 @@ pub struct Member {
     #[sqlx(flatten)]
     pub primary: User,
     #[sqlx(flatten, try_from = "PartnerUser")]
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub partner: Option<User>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub expiration_date: Option<chrono::NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub join_date: Option<chrono::NaiveDate>,
+    /// Start of ACI membership: earliest international membership join date,
+    /// falling back to the account creation date
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub member_since: Option<chrono::NaiveDate>,
     #[sqlx(flatten, try_from = "LocalClub")]
     pub local_club: Club,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// Numbers of every club the member was listed in, home club first; see
+    /// [`dedupe_members`]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_empty")]
+    pub club_numbers: Vec<i64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_empty")]
     #[sqlx(flatten, try_from = "Brns")]
     pub brns: Vec<String>,
 }
 
+impl Member {
+    /// Completed years of membership as of `on`, for milestone awards
+    pub fn tenure_years(&self, on: NaiveDate) -> Option<u32> {
+        self.member_since.and_then(|since| on.years_since(since))
+    }
+}
+
 pub mod db {
     use super::*;
     use ::db as app_db;
@@ -956,8 +1318,8 @@ pub mod mailchimp {
         ]
         .into_iter()
         .filter_map(|value| value.transpose())
-        .chain(address_to_values(address, merge_fields).into_iter())
-        .chain(club_to_values(&member.local_club, merge_fields).into_iter())
+        .chain(address_to_values(address, merge_fields))
+        .chain(club_to_values(&member.local_club, merge_fields))
         .collect::<mc::Result<Vec<mc::merge_fields::MergeFieldValue>>>()?;
         Ok(mc::members::Member {
             id: mc::members::member_id(&user.email),
@@ -1003,17 +1365,17 @@ pub mod mailchimp {
 
 #[derive(Debug, sqlx::FromRow, serde::Serialize, Clone)]
 pub struct Address {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub user_id: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub street_address: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub street_address_2: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub zip_code: Option<String>,
     pub city: Option<String>,
     pub state: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub country: Option<String>,
 }
 
@@ -1023,9 +1385,9 @@ struct PartnerUser {
     partner_email: Option<String>,
     partner_first_name: Option<String>,
     partner_last_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     partner_birthday: Option<chrono::NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     partner_last_login: Option<chrono::NaiveDate>,
 }
 
@@ -1051,6 +1413,7 @@ impl From<PartnerUser> for Option<User> {
                 military_status: None,
                 first_responder_status: None,
                 active: true, // Partners inherit active status from primary
+                secondary_emails: vec![],
             })
         } else {
             None
@@ -1074,23 +1437,156 @@ impl From<LocalClub> for Club {
             number: value.club_number,
             name: value.club_name.unwrap_or_default(),
             region: value.club_region_uid,
+            region_name: None,
+            founded: None,
             active: true, // LocalClub doesn't track status, default to active
         }
     }
 }
 
+/// Comma separated `v_brns.brns_values`, selected as `brns`
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
-struct Brns {
+pub(crate) struct Brns {
     brns: Option<String>,
 }
 
 impl From<Brns> for Vec<String> {
     fn from(value: Brns) -> Self {
+        // v_brns may list the same number twice (e.g. "07569, 07569")
         value
             .brns
             .unwrap_or_default()
             .split(",")
-            .map(|v| v.trim().to_string())
+            .map(crate::brns::normalize)
+            .filter(|v| !v.is_empty())
+            .unique()
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_member_queries_select_every_user_field() {
+        for sql in [FETCH_ALL_MEMBERS_QUERY, FETCH_CLUB_MEMBERS_QUERY] {
+            let missing = crate::sql_audit::missing_aliases(sql, &User::columns());
+            assert_eq!(missing, Vec::<&str>::new(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn test_brns_dedupes_comma_list() {
+        let brns: Vec<String> = Brns {
+            brns: Some("07569, 07569,123 , 07569".to_string()),
+        }
+        .into();
+        assert_eq!(brns, vec!["07569".to_string(), "123".to_string()]);
+
+        let brns: Vec<String> = Brns { brns: None }.into();
+        assert!(brns.is_empty());
+    }
+
+    fn member_club(
+        user_uid: u64,
+        club_uid: u64,
+        number: i64,
+        member_type: MemberType,
+    ) -> MemberClub {
+        MemberClub {
+            user_uid,
+            club_uid,
+            club_number: Some(number),
+            club_name: None,
+            member_type,
+        }
+    }
+
+    #[test]
+    fn test_clubs_by_user_home_club_first() {
+        let by_user = clubs_by_user(vec![
+            member_club(1, 30, 3, MemberType::Affiliate),
+            member_club(1, 20, 9, MemberType::Regular),
+            member_club(1, 10, 1, MemberType::Affiliate),
+            member_club(1, 10, 1, MemberType::Affiliate),
+            member_club(2, 10, 1, MemberType::Regular),
+        ]);
+        let clubs: Vec<u64> = by_user[&1].iter().map(|club| club.club_uid).collect();
+        assert_eq!(clubs, vec![20, 10, 30]);
+        assert_eq!(by_user[&2].len(), 1);
+    }
+
+    fn member(uid: u64, email: &str, club_number: i64, member_type: MemberType) -> Member {
+        Member {
+            member_class: MemberClass::Regular,
+            member_type,
+            member_status: MemberStatus::Current,
+            primary: User::test(uid, email),
+            partner: None,
+            expiration_date: None,
+            join_date: None,
+            member_since: None,
+            local_club: Club {
+                uid: club_number as u64 * 10,
+                number: Some(club_number),
+                name: String::new(),
+                region: None,
+                region_name: None,
+                founded: None,
+                active: true,
+            },
+            club_numbers: vec![],
+            brns: vec![],
+        }
+    }
+
+    #[test]
+    fn test_dedupe_members_merges_clubs() {
+        let members = dedupe_members(vec![
+            member(1, "a@example.com", 7, MemberType::Affiliate),
+            member(1, "a@example.com", 3, MemberType::Regular),
+            member(1, "a@example.com", 5, MemberType::Affiliate),
+            member(2, "b@example.com", 4, MemberType::Affiliate),
+            member(2, "b@example.com", 4, MemberType::Affiliate),
+        ]);
+        assert_eq!(members.len(), 2);
+
+        let a = members.iter().find(|m| m.primary.uid == 1).unwrap();
+        assert_eq!(a.member_type, MemberType::Regular);
+        assert_eq!(a.local_club.number, Some(3));
+        assert_eq!(a.club_numbers, vec![3, 5, 7]);
+
+        let b = members.iter().find(|m| m.primary.uid == 2).unwrap();
+        assert_eq!(b.club_numbers, vec![4]);
+    }
+
+    #[test]
+    fn test_home_club_members_lists_every_club() {
+        let members = home_club_members(vec![
+            member(1, "a@example.com", 9, MemberType::Affiliate),
+            member(1, "a@example.com", 2, MemberType::Intraclub),
+            member(1, "a@example.com", 12, MemberType::Regular),
+            member(2, "b@example.com", 4, MemberType::Affiliate),
+            member(3, "c@example.com", 6, MemberType::Intraclub),
+        ]);
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].primary.uid, 1);
+        assert_eq!(members[0].member_type, MemberType::Regular);
+        assert_eq!(members[0].local_club.number, Some(12));
+        assert_eq!(members[0].club_numbers, vec![12, 2, 9]);
+    }
+
+    #[test]
+    fn test_tenure_years() {
+        let mut member = member(1, "a@example.com", 12, MemberType::Regular);
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(member.tenure_years(date(2025, 6, 15)), None);
+
+        member.member_since = Some(date(2000, 6, 15));
+        assert_eq!(member.tenure_years(date(2025, 6, 15)), Some(25));
+        assert_eq!(member.tenure_years(date(2025, 6, 14)), Some(24));
+        assert_eq!(member.tenure_years(date(2000, 6, 15)), Some(0));
+        assert_eq!(member.tenure_years(date(1999, 6, 15)), None);
+    }
+}
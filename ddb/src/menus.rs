@@ -0,0 +1,314 @@
+//! Drupal menus (`menu_link_content`) as nested trees.
+//!
+//! Works for any menu: the main site's `main` and `footer` menus as well as
+//! the shared `microsites` menu, see [`crate::microsites::menu`].
+
+use crate::{Db, Result};
+use std::collections::HashMap;
+
+/// A menu link with its nested children
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct MenuItem {
+    /// Menu link ID
+    pub id: u64,
+    /// Menu label
+    pub title: String,
+    pub weight: i32,
+    /// Disabled links are hidden in Drupal's navigation
+    pub enabled: bool,
+    /// Node the link points to
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub target_nid: Option<u64>,
+    /// Link URI when it doesn't point to a node (external or internal path)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_empty")]
+    pub children: Vec<MenuItem>,
+}
+
+/// A flat menu link as stored in `menu_link_content_data`
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct MenuLinkRow {
+    pub(crate) id: u64,
+    pub(crate) uuid: String,
+    /// `menu_link_content:<uuid>` of the parent link, empty for top-level links
+    pub(crate) parent: Option<String>,
+    pub(crate) title: String,
+    pub(crate) weight: i32,
+    pub(crate) enabled: bool,
+    pub(crate) link_uri: String,
+}
+
+impl MenuLinkRow {
+    /// The `parent` value of this link's children
+    pub(crate) fn parent_ref(&self) -> String {
+        format!("menu_link_content:{}", self.uuid)
+    }
+}
+
+/// Fetch a whole menu (e.g. `main`, `footer`) as a nested tree.
+///
+/// Top-level links are returned with their children, ordered by weight then
+/// title, including disabled links.
+pub async fn tree(db: &Db, menu_name: &str) -> Result<Vec<MenuItem>> {
+    let links = links(db, menu_name).await?;
+    Ok(build_menu_tree(links, ""))
+}
+
+/// Fetch the flat links of a menu
+pub(crate) async fn links(db: &Db, menu_name: &str) -> Result<Vec<MenuLinkRow>> {
+    db.fetch("menu", async || {
+        // UUID is stored as VARBINARY in MySQL, so it is cast to CHAR
+        sqlx::query_as(
+            r#"
+            SELECT
+                mld.id,
+                CAST(mlc.uuid AS CHAR(36)) as uuid,
+                mld.parent,
+                mld.title,
+                mld.weight,
+                mld.enabled = 1 as enabled,
+                mld.link__uri as link_uri
+            FROM menu_link_content_data mld
+            JOIN menu_link_content mlc ON mlc.id = mld.id
+            WHERE mld.menu_name = ?
+            "#,
+        )
+        .bind(menu_name)
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
+
+/// Nest menu links below the `root` parent reference (`""` for the top level).
+///
+/// Links whose `parent` chain loops back on itself would never be reached
+/// from the top, or would repeat the root link inside its own subtree; see
+/// [`break_cycles`].
+pub(crate) fn build_menu_tree(mut rows: Vec<MenuLinkRow>, root: &str) -> Vec<MenuItem> {
+    break_cycles(&mut rows, root);
+    let mut by_parent: HashMap<String, Vec<MenuLinkRow>> = HashMap::new();
+    for row in rows {
+        by_parent
+            .entry(row.parent.clone().unwrap_or_default())
+            .or_default()
+            .push(row);
+    }
+    menu_children(&mut by_parent, root)
+}
+
+/// Break the `parent` cycles that show up in the tree below `root`, with a
+/// warning naming the links in each cycle.
+///
+/// For the whole menu (`root` is `""`) that is every cycle: its lowest-ID
+/// link moves to the top level, and the rest of the cycle and anything below
+/// it then hang off that link as usual.
+///
+/// Below a link, the only cycle that can show up is one through that root
+/// link itself, which would otherwise be listed again under its own
+/// descendants. The root link is detached from its parent instead, so the
+/// cycle hangs below `root` starting from the root link's child. Cycles
+/// elsewhere in the menu are left for the builds that contain them.
+fn break_cycles(rows: &mut [MenuLinkRow], root: &str) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        OnPath,
+        Done,
+    }
+
+    let by_ref: HashMap<String, usize> = rows
+        .iter()
+        .enumerate()
+        .map(|(index, row)| (row.parent_ref(), index))
+        .collect();
+    let parent = |index: usize| {
+        rows[index]
+            .parent
+            .as_deref()
+            .and_then(|parent| by_ref.get(parent))
+            .copied()
+    };
+
+    let mut visits = vec![Visit::New; rows.len()];
+    let mut cycles = Vec::new();
+    for start in 0..rows.len() {
+        let mut path = Vec::new();
+        let mut next = Some(start);
+        while let Some(index) = next {
+            match visits[index] {
+                Visit::Done => break,
+                Visit::OnPath => {
+                    let first = path.iter().position(|&i| i == index).unwrap_or_default();
+                    cycles.push(path[first..].to_vec());
+                    break;
+                }
+                Visit::New => {
+                    visits[index] = Visit::OnPath;
+                    path.push(index);
+                    next = parent(index);
+                }
+            }
+        }
+        for index in path {
+            visits[index] = Visit::Done;
+        }
+    }
+
+    for cycle in cycles {
+        let head = if root.is_empty() {
+            cycle.iter().min_by_key(|&&index| rows[index].id)
+        } else {
+            cycle
+                .iter()
+                .find(|&&index| rows[index].parent_ref() == root)
+        };
+        let Some(&head) = head else {
+            continue;
+        };
+        let ids: Vec<String> = cycle
+            .iter()
+            .map(|&index| rows[index].id.to_string())
+            .collect();
+        if root.is_empty() {
+            log::warn!(
+                "menu links {} form a parent cycle; moving link {} to the top level",
+                ids.join(" -> "),
+                rows[head].id
+            );
+        } else {
+            log::warn!(
+                "menu links {} form a parent cycle; listing them below link {} only once",
+                ids.join(" -> "),
+                rows[head].id
+            );
+        }
+        rows[head].parent = None;
+    }
+}
+
+fn menu_children(by_parent: &mut HashMap<String, Vec<MenuLinkRow>>, parent: &str) -> Vec<MenuItem> {
+    let mut rows = by_parent.remove(parent).unwrap_or_default();
+    rows.sort_by(|a, b| a.weight.cmp(&b.weight).then_with(|| a.title.cmp(&b.title)));
+    rows.into_iter()
+        .map(|row| {
+            let children = menu_children(by_parent, &row.parent_ref());
+            let target_nid = link_target_nid(&row.link_uri);
+            MenuItem {
+                id: row.id,
+                title: row.title,
+                weight: row.weight,
+                enabled: row.enabled,
+                url: target_nid.is_none().then_some(row.link_uri),
+                target_nid,
+                children,
+            }
+        })
+        .collect()
+}
+
+/// Node ID of an `entity:node/<nid>` or `internal:/node/<nid>` link URI
+fn link_target_nid(uri: &str) -> Option<u64> {
+    uri.strip_prefix("entity:node/")
+        .or_else(|| uri.strip_prefix("internal:/node/"))
+        .and_then(|nid| nid.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn menu_row(id: u64, parent: Option<u64>, title: &str, weight: i32, uri: &str) -> MenuLinkRow {
+        MenuLinkRow {
+            id,
+            uuid: format!("uuid-{id}"),
+            parent: parent.map(|parent| format!("menu_link_content:uuid-{parent}")),
+            title: title.to_string(),
+            weight,
+            enabled: true,
+            link_uri: uri.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_build_menu_tree() {
+        let rows = vec![
+            menu_row(1, None, "Home", 0, "entity:node/100"),
+            menu_row(2, Some(1), "Rallies", 5, "entity:node/101"),
+            menu_row(3, Some(1), "About", 0, "internal:/node/102"),
+            menu_row(4, Some(2), "Spring", 0, "https://example.com/spring"),
+            menu_row(5, Some(9), "Other club", 0, "entity:node/200"),
+        ];
+        let tree = build_menu_tree(rows, "menu_link_content:uuid-1");
+
+        let titles: Vec<&str> = tree.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["About", "Rallies"]);
+        assert_eq!(tree[0].target_nid, Some(102));
+        assert_eq!(tree[1].children.len(), 1);
+        let spring = &tree[1].children[0];
+        assert_eq!(spring.target_nid, None);
+        assert_eq!(spring.url.as_deref(), Some("https://example.com/spring"));
+    }
+
+    #[test]
+    fn test_build_menu_tree_breaks_cycles() {
+        let rows = || {
+            vec![
+                menu_row(1, None, "Home", 0, "internal:/"),
+                menu_row(2, Some(3), "Rallies", 1, "entity:node/10"),
+                menu_row(3, Some(2), "Spring", 0, "entity:node/11"),
+                menu_row(4, Some(3), "Schedule", 0, "entity:node/12"),
+                menu_row(5, Some(5), "Loop", 2, "entity:node/13"),
+            ]
+        };
+        let tree = build_menu_tree(rows(), "");
+        let titles: Vec<&str> = tree.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Home", "Rallies", "Loop"]);
+
+        let spring = &tree[1].children[0];
+        assert_eq!(spring.id, 3);
+        assert_eq!(spring.children[0].id, 4);
+        assert!(tree[2].children.is_empty());
+
+        // Cycles elsewhere in the menu don't show up below another root
+        assert!(build_menu_tree(rows(), "menu_link_content:uuid-1").is_empty());
+    }
+
+    #[test]
+    fn test_build_menu_tree_breaks_cycles_through_root() {
+        let rows = || {
+            vec![
+                menu_row(1, Some(3), "Home", 0, "entity:node/10"),
+                menu_row(2, Some(1), "Rallies", 0, "entity:node/11"),
+                menu_row(3, Some(2), "Spring", 0, "entity:node/12"),
+                menu_row(4, Some(5), "Other", 0, "entity:node/13"),
+                menu_row(5, Some(4), "Club", 0, "entity:node/14"),
+            ]
+        };
+        let tree = build_menu_tree(rows(), "menu_link_content:uuid-1");
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].id, 2);
+        assert_eq!(tree[0].children.len(), 1);
+        assert_eq!(tree[0].children[0].id, 3);
+        assert!(tree[0].children[0].children.is_empty());
+
+        let tree = build_menu_tree(rows(), "");
+        let ids: Vec<u64> = tree.iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_build_menu_tree_top_level() {
+        let rows = vec![
+            menu_row(1, None, "Home", 0, "internal:/"),
+            menu_row(2, Some(1), "About", 0, "entity:node/5"),
+            menu_row(3, None, "Contact", -1, "entity:node/6"),
+        ];
+        let tree = build_menu_tree(rows, "");
+        let titles: Vec<&str> = tree.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, vec!["Contact", "Home"]);
+        assert_eq!(tree[1].url.as_deref(), Some("internal:/"));
+        assert_eq!(tree[1].children[0].target_nid, Some(5));
+    }
+}
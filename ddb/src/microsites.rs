@@ -5,15 +5,25 @@
 //! - Menu structure
 //! - Page body content (HTML)
 
-use crate::{Error, Result};
-use sqlx::MySqlPool;
+pub use crate::menus::MenuItem;
+use crate::{Db, Result, menus};
+use sqlx::MySql;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// `(ssp_club nid, microsite_homepage nid)` pairs whose titles don't match:
+/// - Boondocking Streamers (club) -> Boondockers Airstream Club (homepage)
+/// - Vintage Airstream Club (club) -> Vintage Airstream Club (VAC) (homepage)
+///
+/// Use [`unmatched_clubs`] to find clubs that need a new entry here.
+const MANUAL_HOMEPAGE_OVERRIDES: &str = "(51008, 55629), (47596, 50698)";
 
 /// A club with its microsite homepage.
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct ClubMicrosite {
     /// Club node ID (Drupal)
     pub club_nid: u64,
     /// Club number (business identifier) - None for intraclubs
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub club_number: Option<i64>,
     /// Club name from ssp_club node
     pub club_name: String,
@@ -21,49 +31,165 @@ pub struct ClubMicrosite {
     pub homepage_nid: u64,
     /// Whether this is an intraclub (no club number)
     pub is_intraclub: bool,
+    /// ssp_club nid of the club an intraclub is affiliated with, see
+    /// [`PARENT_CLUB_COLUMN`]. Always None for regular clubs.
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub parent_club_nid: Option<u64>,
 }
 
+/// Parent club of the intraclub aliased `club`, selected as `parent_club_nid`.
+///
+/// An intraclub's ssp_club node references the microsite_homepage of the club
+/// it belongs to through `field_club`, the same link content nodes use for
+/// their owner (see [`crate::owner`]); the parent is the other ssp_club whose
+/// `field_main_site_club` is that homepage. Intraclubs without the reference
+/// (and interest groups affiliated with no club) get NULL.
+const PARENT_CLUB_COLUMN: &str = r#"
+                (
+                    SELECT MIN(parent_msc.entity_id)
+                    FROM node__field_club parent_fc
+                    JOIN node__field_main_site_club parent_msc
+                        ON parent_msc.field_main_site_club_target_id = parent_fc.field_club_target_id
+                        AND parent_msc.bundle = 'ssp_club'
+                        AND parent_msc.entity_id <> club.nid
+                        AND parent_msc.deleted = 0
+                    WHERE parent_fc.entity_id = club.nid
+                    AND parent_fc.deleted = 0
+                    AND cn.field_club_number_value IS NULL
+                ) as parent_club_nid
+"#;
+
 /// Fetch all clubs that have microsites.
 ///
 /// Matches ssp_club nodes to microsite_homepage nodes by title.
 /// Includes both regular clubs (with club_number) and intraclubs (without).
 /// Also includes manual overrides for clubs where titles don't match.
-pub async fn clubs_with_microsites(pool: &MySqlPool) -> Result<Vec<ClubMicrosite>> {
-    sqlx::query_as::<_, ClubMicrosite>(
-        r#"
-        SELECT
-            club.nid as club_nid,
-            cn.field_club_number_value as club_number,
-            club.title as club_name,
-            hp.nid as homepage_nid,
-            cn.field_club_number_value IS NULL as is_intraclub
-        FROM node_field_data hp
-        JOIN node_field_data club ON club.title = hp.title AND club.type = 'ssp_club'
-        LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid
-        WHERE hp.type = 'microsite_homepage'
+pub async fn clubs_with_microsites(db: &Db) -> Result<Vec<ClubMicrosite>> {
+    clubs_with_microsites_filtered(db, true).await
+}
 
-        UNION
+/// Fetch clubs that have microsites, optionally leaving out intraclubs
+/// (e.g. for the public club directory).
+pub async fn clubs_with_microsites_filtered(
+    db: &Db,
+    include_intraclubs: bool,
+) -> Result<Vec<ClubMicrosite>> {
+    db.fetch("microsite clubs", async || {
+        let mut query = clubs_with_microsites_query();
+        if !include_intraclubs {
+            query.push(" WHERE NOT m.is_intraclub");
+        }
+        query
+            .push(" ORDER BY is_intraclub, club_number, club_name")
+            .build_query_as::<ClubMicrosite>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
 
-        -- Manual overrides for clubs where homepage title doesn't match club title
-        -- Boondocking Streamers (club) -> Boondockers Airstream Club (homepage)
-        -- Vintage Airstream Club (club) -> Vintage Airstream Club (VAC) (homepage)
-        SELECT
-            club.nid as club_nid,
-            cn.field_club_number_value as club_number,
-            club.title as club_name,
-            hp.nid as homepage_nid,
-            cn.field_club_number_value IS NULL as is_intraclub
-        FROM node_field_data club
-        JOIN node_field_data hp ON (club.nid, hp.nid) IN ((51008, 55629), (47596, 50698))
-        LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid
-        WHERE club.type = 'ssp_club' AND hp.type = 'microsite_homepage'
+/// Fetch the clubs with microsites whose club number is in `club_numbers`.
+///
+/// Intraclubs have no club number and are never matched.
+pub async fn clubs_with_microsites_in(db: &Db, club_numbers: &[i64]) -> Result<Vec<ClubMicrosite>> {
+    db.fetch("microsite clubs", async || {
+        let mut query = clubs_with_microsites_query();
+        query.push(" WHERE m.club_number IN (");
+        let mut separated = query.separated(", ");
+        for number in club_numbers {
+            separated.push_bind(*number);
+        }
+        query
+            .push(") ORDER BY is_intraclub, club_number, club_name")
+            .build_query_as::<ClubMicrosite>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
 
-        ORDER BY is_intraclub, club_number, club_name
+fn clubs_with_microsites_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
+    sqlx::QueryBuilder::new(format!(
+        r#"
+        SELECT m.* FROM (
+            SELECT
+                club.nid as club_nid,
+                cn.field_club_number_value as club_number,
+                club.title as club_name,
+                hp.nid as homepage_nid,
+                cn.field_club_number_value IS NULL as is_intraclub,
+                {PARENT_CLUB_COLUMN}
+            FROM node_field_data hp
+            JOIN node_field_data club ON club.title = hp.title AND club.type = 'ssp_club'
+            LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid AND cn.deleted = 0
+            WHERE hp.type = 'microsite_homepage'
+
+            UNION
+
+            -- Manual overrides for clubs where homepage title doesn't match club title
+            SELECT
+                club.nid as club_nid,
+                cn.field_club_number_value as club_number,
+                club.title as club_name,
+                hp.nid as homepage_nid,
+                cn.field_club_number_value IS NULL as is_intraclub,
+                {PARENT_CLUB_COLUMN}
+            FROM node_field_data club
+            JOIN node_field_data hp ON (club.nid, hp.nid) IN ({MANUAL_HOMEPAGE_OVERRIDES})
+            LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid AND cn.deleted = 0
+            WHERE club.type = 'ssp_club' AND hp.type = 'microsite_homepage'
+        ) m
         "#,
-    )
-    .fetch_all(pool)
-    .await
-    .map_err(Error::from)
+    ))
+}
+
+/// An ssp_club node without a matching microsite homepage
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct UnmatchedClub {
+    pub club_nid: u64,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_number: Option<i64>,
+    pub club_name: String,
+    /// Whether the club node is published
+    pub status: bool,
+}
+
+/// Fetch clubs whose title matches no microsite_homepage title.
+///
+/// These are the clubs [`clubs_with_microsites`] can only pick up through
+/// the manual overrides. With `apply_overrides`, clubs already covered by
+/// an override are left out, so anything listed needs a new mapping (or has
+/// no microsite); without it the overridden clubs are listed too, to audit
+/// whether each override is still needed.
+pub async fn unmatched_clubs(db: &Db, apply_overrides: bool) -> Result<Vec<UnmatchedClub>> {
+    db.fetch("unmatched clubs", async || {
+        let mut query = sqlx::QueryBuilder::new(
+            r#"
+            SELECT
+                club.nid as club_nid,
+                CAST(cn.field_club_number_value AS SIGNED) as club_number,
+                club.title as club_name,
+                club.status = 1 as status
+            FROM node_field_data club
+            LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid AND cn.deleted = 0
+            WHERE club.type = 'ssp_club'
+            AND NOT EXISTS (
+                SELECT 1 FROM node_field_data hp
+                WHERE hp.type = 'microsite_homepage' AND hp.title = club.title
+            )
+            "#,
+        );
+        if apply_overrides {
+            query.push(format!(
+                " AND NOT EXISTS (SELECT 1 FROM node_field_data hp \
+                 WHERE hp.type = 'microsite_homepage' AND (club.nid, hp.nid) IN ({MANUAL_HOMEPAGE_OVERRIDES}))"
+            ));
+        }
+        query
+        .push(" ORDER BY club_number, club_name")
+        .build_query_as::<UnmatchedClub>().fetch_all(db.pool()).await
+    })
+        .await
 }
 
 /// Club slug from Drupal path alias.
@@ -80,9 +206,10 @@ pub struct ClubSlug {
 /// Returns the Drupal path alias for each club's microsite homepage.
 /// Maps by joining ssp_club → microsite_homepage (by title) → path_alias.
 /// Also includes manual overrides for clubs where titles don't match.
-pub async fn club_slugs(pool: &MySqlPool) -> Result<Vec<ClubSlug>> {
-    sqlx::query_as::<_, ClubSlug>(
-        r#"
+pub async fn club_slugs(db: &Db) -> Result<Vec<ClubSlug>> {
+    db.fetch("club slugs", async || {
+        sqlx::query_as::<_, ClubSlug>(&format!(
+            r#"
         SELECT club.nid as club_nid, TRIM(LEADING '/' FROM pa.alias) as slug
         FROM node_field_data club
         JOIN node_field_data hp ON hp.title = club.title AND hp.type = 'microsite_homepage'
@@ -94,18 +221,78 @@ pub async fn club_slugs(pool: &MySqlPool) -> Result<Vec<ClubSlug>> {
         -- Manual overrides for clubs where homepage title doesn't match club title
         SELECT club.nid as club_nid, TRIM(LEADING '/' FROM pa.alias) as slug
         FROM node_field_data club
-        JOIN node_field_data hp ON (club.nid, hp.nid) IN ((51008, 55629), (47596, 50698))
+        JOIN node_field_data hp ON (club.nid, hp.nid) IN ({MANUAL_HOMEPAGE_OVERRIDES})
         JOIN path_alias pa ON pa.path = CONCAT('/node/', hp.nid)
         WHERE club.type = 'ssp_club' AND hp.type = 'microsite_homepage'
         "#,
-    )
-    .fetch_all(pool)
+        ))
+        .fetch_all(db.pool())
+        .await
+    })
     .await
-    .map_err(Error::from)
+}
+
+/// A club slug the new site's router can't use as is
+#[derive(Debug, PartialEq, serde::Serialize)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum SlugIssue {
+    /// The same slug, ignoring case, belongs to more than one club
+    Duplicate { slug: String, club_nids: Vec<u64> },
+    /// The slug has characters other than lowercase ASCII letters, digits and
+    /// hyphens, or is empty
+    InvalidCharacters {
+        club_nid: u64,
+        slug: String,
+        /// [`crate::slug::slugify`] of the slug
+        suggestion: String,
+    },
+}
+
+/// Check [`club_slugs`] for slugs shared by several clubs and slugs that
+/// aren't URL-safe, so conflicts can be fixed in Drupal before migration
+pub async fn validate_slugs(db: &Db) -> Result<Vec<SlugIssue>> {
+    let slugs = club_slugs(db).await?;
+    Ok(slug_issues(&slugs))
+}
+
+/// Duplicates (ordered by slug) followed by invalid slugs (ordered by club)
+fn slug_issues(slugs: &[ClubSlug]) -> Vec<SlugIssue> {
+    let mut by_slug: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    for slug in slugs {
+        by_slug
+            .entry(slug.slug.to_lowercase())
+            .or_default()
+            .insert(slug.club_nid);
+    }
+    let duplicates = by_slug
+        .into_iter()
+        .filter(|(_, club_nids)| club_nids.len() > 1)
+        .map(|(slug, club_nids)| SlugIssue::Duplicate {
+            slug,
+            club_nids: club_nids.into_iter().collect(),
+        });
+
+    let url_safe = |slug: &str| {
+        !slug.is_empty()
+            && slug
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    };
+    let mut invalid: Vec<&ClubSlug> = slugs.iter().filter(|slug| !url_safe(&slug.slug)).collect();
+    invalid.sort_by_key(|slug| (slug.club_nid, &slug.slug));
+    let invalid = invalid
+        .into_iter()
+        .map(|slug| SlugIssue::InvalidCharacters {
+            club_nid: slug.club_nid,
+            slug: slug.slug.clone(),
+            suggestion: crate::slug::slugify(&slug.slug),
+        });
+
+    duplicates.chain(invalid).collect()
 }
 
 /// A microsite page with its content and menu metadata.
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub struct MicrositePage {
     /// Node ID
     pub nid: u64,
@@ -115,18 +302,84 @@ pub struct MicrositePage {
     pub body_html: String,
     /// Whether page is published
     pub status: bool,
+    /// Unix timestamp of node creation
+    pub created: i64,
+    /// Unix timestamp of the last edit, for "last updated" and delta syncs
+    pub changed: i64,
     /// Menu item ID (if in menu)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub menu_id: Option<u64>,
     /// Menu title (may differ from node title)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub menu_title: Option<String>,
     /// Menu weight for ordering
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub menu_weight: Option<i32>,
     /// Parent menu item UUID (for nesting)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub menu_parent: Option<String>,
+    /// Node ID the parent menu item links to (`None` for the homepage)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub parent_nid: Option<u64>,
     /// Hero banner image (public:// URI)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub hero_image: Option<String>,
     /// Navigation/thumbnail image (public:// URI)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub nav_image: Option<String>,
+    /// User who saved the current revision, or the page's owner when the
+    /// revision doesn't record one
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub last_author_uid: Option<u64>,
+    /// That user's first and last name, else their username
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub last_author_name: Option<String>,
+    /// The fields `body_html` was merged from, with
+    /// [`PageOptions::body_sources`]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub body_sources: Option<BodySources>,
+}
+
+/// A page's body fields before they are merged into
+/// [`MicrositePage::body_html`], in merge order
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct BodySources {
+    /// `node__field_summary`
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub summary: Option<String>,
+    /// `node__body`
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub body: Option<String>,
+    /// `node__field_body`
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub field_body: Option<String>,
+    /// Rendered `field_featured_pages` paragraphs
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub featured: Option<String>,
+}
+
+impl MicrositePage {
+    /// Number of words in the body text, ignoring markup.
+    ///
+    /// Tags are removed wholesale, so attribute values (link targets, image
+    /// sources, classes) are not counted.
+    pub fn word_count(&self) -> usize {
+        use regex::Regex;
+        use std::sync::LazyLock;
+
+        static TAG_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"<[^>]*>").expect("Invalid tag regex"));
+
+        TAG_RE
+            .replace_all(&self.body_html, " ")
+            .split_whitespace()
+            .count()
+    }
+
+    /// Estimated reading time at 200 words per minute (rounded down)
+    pub fn reading_time_minutes(&self) -> usize {
+        self.word_count() / 200
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -140,14 +393,19 @@ struct PageRow {
     /// Custom field_body (different from node body, used by ~58 pages)
     field_body_value: Option<String>,
     status: i8,
+    created: i64,
+    changed: i64,
     menu_id: Option<u64>,
     menu_title: Option<String>,
     menu_weight: Option<i32>,
     menu_parent: Option<String>,
+    parent_nid: Option<u64>,
     /// Hero banner image file URI (public://...)
     hero_image_uri: Option<String>,
     /// Navigation image file URI (public://...)
     nav_image_uri: Option<String>,
+    last_author_uid: Option<u64>,
+    last_author_name: Option<String>,
 }
 
 /// A featured page paragraph with headline, content, optional button, and optional image.
@@ -158,6 +416,7 @@ struct FeaturedPageRow {
     button_uri: Option<String>,
     button_title: Option<String>,
     image_uri: Option<String>,
+    image_alt: Option<String>,
 }
 
 impl From<PageRow> for MicrositePage {
@@ -186,58 +445,125 @@ impl From<PageRow> for MicrositePage {
             title,
             body_html,
             status: row.status == 1,
+            created: row.created,
+            changed: row.changed,
             menu_id: row.menu_id,
             menu_title: row.menu_title,
             menu_weight: row.menu_weight,
             menu_parent: row.menu_parent,
+            parent_nid: row.parent_nid,
             hero_image: row.hero_image_uri,
             nav_image: row.nav_image_uri,
+            last_author_uid: row.last_author_uid,
+            last_author_name: row.last_author_name,
+            body_sources: None,
         }
     }
 }
 
+/// `last_author_uid` and `last_author_name` from [`PAGE_FIELD_JOINS`]. The
+/// author is that of the default revision, also with
+/// [`PageOptions::latest_published_revision`].
+const PAGE_AUTHOR_COLUMNS: &str = r#"
+    author.uid as last_author_uid,
+    COALESCE(
+        NULLIF(TRIM(CONCAT_WS(' ', author_fn.field_first_name_value, author_ln.field_last_name_value)), ''),
+        NULLIF(author.name, '')
+    ) as last_author_name
+"#;
+
+/// Title, body and image fields and the last author of a microsite page `n`.
+///
+/// Every field join is limited to `deleted = 0`: Drupal keeps the rows of
+/// deleted fields next to the live ones, and without the guard a page could
+/// come back twice or with a stale value, e.g. a hero image removed long ago.
+const PAGE_FIELD_JOINS: &str = r#"
+    LEFT JOIN node__field_page_title pt ON pt.entity_id = n.nid AND pt.deleted = 0
+    LEFT JOIN node__body b ON b.entity_id = n.nid AND b.deleted = 0
+    LEFT JOIN node__field_summary s ON s.entity_id = n.nid AND s.deleted = 0
+    LEFT JOIN node__field_body fb ON fb.entity_id = n.nid AND fb.deleted = 0
+    -- Hero banner image: node -> field_hero_banner_image -> media -> field_media_image -> file
+    LEFT JOIN node__field_hero_banner_image hbi ON hbi.entity_id = n.nid AND hbi.deleted = 0
+    LEFT JOIN media__field_media_image hero_mfi
+        ON hero_mfi.entity_id = hbi.field_hero_banner_image_target_id AND hero_mfi.deleted = 0
+    LEFT JOIN file_managed hero_file ON hero_file.fid = hero_mfi.field_media_image_target_id
+    -- Navigation image: node -> field_navigatio_ -> media -> field_media_image -> file
+    LEFT JOIN node__field_navigatio_ nav ON nav.entity_id = n.nid AND nav.deleted = 0
+    LEFT JOIN media__field_media_image nav_mfi
+        ON nav_mfi.entity_id = nav.field_navigatio__target_id AND nav_mfi.deleted = 0
+    LEFT JOIN file_managed nav_file ON nav_file.fid = nav_mfi.field_media_image_target_id
+    -- Last author: whoever saved the current revision, else the node owner
+    LEFT JOIN node_revision rev ON rev.vid = n.vid
+    LEFT JOIN users_field_data author
+        ON author.uid = COALESCE(rev.revision_uid, n.uid) AND author.uid <> 0
+    LEFT JOIN user__field_first_name author_fn
+        ON author_fn.entity_id = author.uid AND author_fn.deleted = 0
+    LEFT JOIN user__field_last_name author_ln
+        ON author_ln.entity_id = author.uid AND author_ln.deleted = 0
+"#;
+
+/// Paragraphs of `node__field_featured_pages fp`, see [`featured_page_rows`]
+const FEATURED_PAGES_QUERY: &str = r#"
+    SELECT
+        fh.field_headline_value as headline,
+        fst.field_summary_text_2_value as summary_text_2,
+        pb.field_button_uri as button_uri,
+        pb.field_button_title as button_title,
+        CAST(img_file.uri AS CHAR(255)) as image_uri,
+        img_mfi.field_media_image_alt as image_alt
+    FROM node__field_featured_pages fp
+    LEFT JOIN paragraph__field_headline fh
+        ON fh.entity_id = fp.field_featured_pages_target_id AND fh.deleted = 0
+    LEFT JOIN paragraph__field_summary_text_2 fst
+        ON fst.entity_id = fp.field_featured_pages_target_id AND fst.deleted = 0
+    LEFT JOIN paragraph__field_button pb
+        ON pb.entity_id = fp.field_featured_pages_target_id AND pb.deleted = 0
+    -- Image: paragraph -> field_image -> media -> field_media_image -> file
+    LEFT JOIN paragraph__field_image pimg
+        ON pimg.entity_id = fp.field_featured_pages_target_id AND pimg.deleted = 0
+    LEFT JOIN media__field_media_image img_mfi
+        ON img_mfi.entity_id = pimg.field_image_target_id AND img_mfi.deleted = 0
+    LEFT JOIN file_managed img_file ON img_file.fid = img_mfi.field_media_image_target_id
+    WHERE fp.deleted = 0 AND fp.entity_id = ?
+    ORDER BY fp.delta, pb.delta
+"#;
+
 /// Fetch featured pages content for a node.
 ///
 /// Some pages store content in `field_featured_pages` paragraphs instead of the body.
-/// Each paragraph has a headline, summary_text_2 field, optional button link, and optional image.
-async fn featured_pages_content(pool: &MySqlPool, nid: u64) -> Result<String> {
-    let rows: Vec<FeaturedPageRow> = sqlx::query_as(
-        r#"
-        SELECT
-            fh.field_headline_value as headline,
-            fst.field_summary_text_2_value as summary_text_2,
-            pb.field_button_uri as button_uri,
-            pb.field_button_title as button_title,
-            CAST(img_file.uri AS CHAR(255)) as image_uri
-        FROM node__field_featured_pages fp
-        LEFT JOIN paragraph__field_headline fh ON fh.entity_id = fp.field_featured_pages_target_id
-        LEFT JOIN paragraph__field_summary_text_2 fst ON fst.entity_id = fp.field_featured_pages_target_id
-        LEFT JOIN paragraph__field_button pb ON pb.entity_id = fp.field_featured_pages_target_id
-        -- Image: paragraph -> field_image -> media -> field_media_image -> file
-        LEFT JOIN paragraph__field_image pimg ON pimg.entity_id = fp.field_featured_pages_target_id
-        LEFT JOIN media__field_media_image img_mfi ON img_mfi.entity_id = pimg.field_image_target_id
-        LEFT JOIN file_managed img_file ON img_file.fid = img_mfi.field_media_image_target_id
-        WHERE fp.entity_id = ?
-        ORDER BY fp.delta
-        "#,
-    )
-    .bind(nid)
-    .fetch_all(pool)
-    .await?;
+/// Each paragraph has a headline, summary_text_2 field, optional button link, and optional image
+/// (with alt text from the media image field).
+async fn featured_pages_content(db: &Db, nid: u64) -> Result<String> {
+    Ok(render_featured_pages(featured_page_rows(db, nid).await?))
+}
 
-    if rows.is_empty() {
-        return Ok(String::new());
-    }
+/// Featured page paragraphs of a node, in display order
+async fn featured_page_rows(db: &Db, nid: u64) -> Result<Vec<FeaturedPageRow>> {
+    db.fetch("featured pages", async || {
+        sqlx::query_as(FEATURED_PAGES_QUERY)
+            .bind(nid)
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
 
+/// Render featured page paragraphs as HTML.
+///
+/// Headlines and button titles are plain text and get escaped; URIs are
+/// attribute-escaped. `summary_text_2` is already HTML and is kept as-is.
+fn render_featured_pages(rows: Vec<FeaturedPageRow>) -> String {
     let mut html = String::new();
     for row in rows {
         // Render image if present (before headline)
         if let Some(uri) = row.image_uri {
             // Convert public:// URI to Drupal path
-            let src = uri.replace("public://", "/sites/default/files/");
-            html.push_str(&format!("<p><img src=\"{src}\" alt=\"\"></p>\n"));
+            let src = escape_attr(&uri.replace("public://", "/sites/default/files/"));
+            let alt = escape_attr(row.image_alt.as_deref().unwrap_or_default());
+            html.push_str(&format!("<p><img src=\"{src}\" alt=\"{alt}\"></p>\n"));
         }
         if let Some(headline) = row.headline {
+            let headline = escape_html(&headline);
             html.push_str(&format!("<h3>{headline}</h3>\n"));
         }
         if let Some(content) = row.summary_text_2 {
@@ -246,23 +572,128 @@ async fn featured_pages_content(pool: &MySqlPool, nid: u64) -> Result<String> {
         }
         // Render button as a link if present
         if let Some(uri) = row.button_uri {
-            let title = row.button_title.unwrap_or_else(|| uri.clone());
+            let title = escape_html(row.button_title.as_deref().unwrap_or(&uri));
+            let uri = escape_attr(&uri);
             html.push_str(&format!("<p><a href=\"{uri}\">{title}</a></p>\n"));
         }
     }
+    html
+}
 
-    Ok(html)
+/// Escape text for use as HTML element content.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape text for use inside a double- or single-quoted HTML attribute.
+pub fn escape_attr(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// UTF-8 punctuation that was decoded as Windows-1252 and re-encoded, paired
+/// with the intended character. The third byte of `”` (0x9D) has no
+/// Windows-1252 mapping and survives as the C1 control U+009D.
+const MOJIBAKE: &[(&str, &str)] = &[
+    ("â€™", "\u{2019}"),
+    ("â€˜", "\u{2018}"),
+    ("â€œ", "\u{201C}"),
+    ("â€\u{9D}", "\u{201D}"),
+    ("â€”", "\u{2014}"),
+    ("â€“", "\u{2013}"),
+    ("â€¦", "\u{2026}"),
+    ("â€¢", "\u{2022}"),
+    ("Â\u{A0}", "\u{A0}"),
+    ("Ã©", "é"),
+];
+
+/// Fix common Windows-1252-in-UTF-8 mojibake, e.g. `donâ€™t` becomes
+/// `don’t`.
+///
+/// Some bodies were saved with CP-1252 bytes into a utf8mb4 column, turning
+/// smart quotes and dashes into three-character sequences. Only the known
+/// sequences in [`MOJIBAKE`] are replaced; other text is left untouched.
+pub fn repair_encoding(html: &str) -> String {
+    let mut repaired = html.to_string();
+    for (broken, fixed) in MOJIBAKE {
+        if repaired.contains(broken) {
+            repaired = repaired.replace(broken, fixed);
+        }
+    }
+    repaired
+}
+
+/// Fetch the menu below a club's homepage link without any page content.
+///
+/// Returns the homepage link's children (nested), ordered by weight then
+/// title, including disabled links. Empty if the homepage has no menu link.
+pub async fn menu(db: &Db, homepage_nid: u64) -> Result<Vec<MenuItem>> {
+    let links = menus::links(db, "microsites").await?;
+    let homepage_uri = format!("entity:node/{homepage_nid}");
+    let Some(root) = links.iter().find(|link| link.link_uri == homepage_uri) else {
+        return Ok(vec![]);
+    };
+    let root = root.parent_ref();
+    Ok(menus::build_menu_tree(links, &root))
 }
 
 /// Fetch all pages for a club's microsite.
 ///
 /// Includes the homepage and all pages in its menu tree.
 /// Uses menu structure for discovery (more reliable than field_club references).
-pub async fn pages_for_club(pool: &MySqlPool, homepage_nid: u64) -> Result<Vec<MicrositePage>> {
+pub async fn pages_for_club(db: &Db, homepage_nid: u64) -> Result<Vec<MicrositePage>> {
+    pages_for_club_opts(db, homepage_nid, &PageOptions::default()).await
+}
+
+/// Options for [`pages_for_club_opts`]
+#[derive(Debug, Clone, Default)]
+pub struct PageOptions {
+    /// Follow disabled menu links as well, so unpublished (`status = 0`) pages
+    /// that editors are still preparing show up alongside live content.
+    /// Drafts are flagged via [`MicrositePage::status`].
+    pub include_unpublished: bool,
+    /// Take the body from the latest *published* revision when it differs
+    /// from the default revision in `node_field_data`. Some pages were edited
+    /// into published revisions that never became the default revision.
+    pub latest_published_revision: bool,
+    /// Fix Windows-1252 mojibake in titles and bodies, see [`repair_encoding`]
+    pub repair_encoding: bool,
+    /// Also return the separate body fields as [`MicrositePage::body_sources`],
+    /// to trace duplicated or out-of-order content back to its field
+    pub body_sources: bool,
+}
+
+/// Fetch all pages for a club's microsite with [`PageOptions`].
+pub async fn pages_for_club_opts(
+    db: &Db,
+    homepage_nid: u64,
+    options: &PageOptions,
+) -> Result<Vec<MicrositePage>> {
     // First get the homepage's menu UUID for finding child pages
     // UUID is stored as VARBINARY in MySQL, so we cast it to CHAR
-    let homepage_uuid: Option<String> = sqlx::query_scalar(
-        r#"
+    let homepage_uuid: Option<String> = db
+        .fetch("microsite menu", async || {
+            sqlx::query_scalar(
+                r#"
         SELECT CAST(mlc.uuid AS CHAR(36))
         FROM menu_link_content mlc
         JOIN menu_link_content_data mld ON mld.id = mlc.id
@@ -270,14 +701,18 @@ pub async fn pages_for_club(pool: &MySqlPool, homepage_nid: u64) -> Result<Vec<M
         AND mld.menu_name = 'microsites'
         LIMIT 1
         "#,
-    )
-    .bind(homepage_nid)
-    .fetch_optional(pool)
-    .await?;
+            )
+            .bind(homepage_nid)
+            .fetch_optional(db.pool())
+            .await
+        })
+        .await?;
 
     // Fetch homepage
-    let homepage: Option<PageRow> = sqlx::query_as(
-        r#"
+    let homepage: Option<PageRow> = db
+        .fetch("microsite homepage", async || {
+            sqlx::query_as(&format!(
+                r#"
         SELECT
             n.nid,
             n.title,
@@ -286,50 +721,30 @@ pub async fn pages_for_club(pool: &MySqlPool, homepage_nid: u64) -> Result<Vec<M
             s.field_summary_value as summary_value,
             fb.field_body_value,
             n.status,
+            n.created,
+            n.changed,
             mld.id as menu_id,
             mld.title as menu_title,
             mld.weight as menu_weight,
             mld.parent as menu_parent,
+            NULL as parent_nid,
             CAST(hero_file.uri AS CHAR(255)) as hero_image_uri,
-            CAST(nav_file.uri AS CHAR(255)) as nav_image_uri
+            CAST(nav_file.uri AS CHAR(255)) as nav_image_uri,
+            {PAGE_AUTHOR_COLUMNS}
         FROM node_field_data n
-        LEFT JOIN node__field_page_title pt ON pt.entity_id = n.nid
-        LEFT JOIN node__body b ON b.entity_id = n.nid
-        LEFT JOIN node__field_summary s ON s.entity_id = n.nid
-        LEFT JOIN node__field_body fb ON fb.entity_id = n.nid
         LEFT JOIN menu_link_content_data mld ON mld.link__uri = CONCAT('entity:node/', n.nid)
             AND mld.menu_name = 'microsites'
-        -- Hero banner image: node -> field_hero_banner_image -> media -> field_media_image -> file
-        LEFT JOIN node__field_hero_banner_image hbi ON hbi.entity_id = n.nid
-        LEFT JOIN media__field_media_image hero_mfi ON hero_mfi.entity_id = hbi.field_hero_banner_image_target_id
-        LEFT JOIN file_managed hero_file ON hero_file.fid = hero_mfi.field_media_image_target_id
-        -- Navigation image: node -> field_navigatio_ -> media -> field_media_image -> file
-        LEFT JOIN node__field_navigatio_ nav ON nav.entity_id = n.nid
-        LEFT JOIN media__field_media_image nav_mfi ON nav_mfi.entity_id = nav.field_navigatio__target_id
-        LEFT JOIN file_managed nav_file ON nav_file.fid = nav_mfi.field_media_image_target_id
+        {PAGE_FIELD_JOINS}
         WHERE n.nid = ?
-        "#,
-    )
-    .bind(homepage_nid)
-    .fetch_optional(pool)
-    .await?;
-
-    let mut pages: Vec<MicrositePage> = Vec::new();
-
-    if let Some(hp) = homepage {
-        let mut page: MicrositePage = hp.into();
-        // Append featured pages content if any
-        let featured = featured_pages_content(pool, page.nid).await?;
-        if !featured.is_empty() {
-            if page.body_html.is_empty() {
-                page.body_html = featured;
-            } else {
-                page.body_html.push_str("\n\n");
-                page.body_html.push_str(&featured);
-            }
-        }
-        pages.push(page);
-    }
+        "#
+            ))
+            .bind(homepage_nid)
+            .fetch_optional(db.pool())
+            .await
+        })
+        .await?;
+
+    let mut rows: Vec<PageRow> = homepage.into_iter().collect();
 
     // If homepage has a menu entry, find all child pages via menu structure
     if let Some(uuid) = homepage_uuid {
@@ -337,83 +752,432 @@ pub async fn pages_for_club(pool: &MySqlPool, homepage_nid: u64) -> Result<Vec<M
 
         // Fetch all pages that are children of the homepage in the menu
         // This catches all node types (microsite_content, microsite_lander_new, etc.)
-        let content_pages: Vec<PageRow> = sqlx::query_as(
-            r#"
-            SELECT
-                n.nid,
-                n.title,
-                pt.field_page_title_value as page_title,
-                b.body_value,
-                s.field_summary_value as summary_value,
-                fb.field_body_value,
-                n.status,
-                mld.id as menu_id,
-                mld.title as menu_title,
-                mld.weight as menu_weight,
-                mld.parent as menu_parent,
-                CAST(hero_file.uri AS CHAR(255)) as hero_image_uri,
-                CAST(nav_file.uri AS CHAR(255)) as nav_image_uri
-            FROM menu_link_content_data mld
-            JOIN node_field_data n ON mld.link__uri = CONCAT('entity:node/', n.nid)
-            LEFT JOIN node__field_page_title pt ON pt.entity_id = n.nid
-            LEFT JOIN node__body b ON b.entity_id = n.nid
-            LEFT JOIN node__field_summary s ON s.entity_id = n.nid
-            LEFT JOIN node__field_body fb ON fb.entity_id = n.nid
-            -- Hero banner image: node -> field_hero_banner_image -> media -> field_media_image -> file
-            LEFT JOIN node__field_hero_banner_image hbi ON hbi.entity_id = n.nid
-            LEFT JOIN media__field_media_image hero_mfi ON hero_mfi.entity_id = hbi.field_hero_banner_image_target_id
-            LEFT JOIN file_managed hero_file ON hero_file.fid = hero_mfi.field_media_image_target_id
-            -- Navigation image: node -> field_navigatio_ -> media -> field_media_image -> file
-            LEFT JOIN node__field_navigatio_ nav ON nav.entity_id = n.nid
-            LEFT JOIN media__field_media_image nav_mfi ON nav_mfi.entity_id = nav.field_navigatio__target_id
-            LEFT JOIN file_managed nav_file ON nav_file.fid = nav_mfi.field_media_image_target_id
-            WHERE mld.menu_name = 'microsites'
-            AND mld.parent = ?
-            AND mld.enabled = 1
-            ORDER BY mld.weight, n.title
-            "#,
-        )
-        .bind(&parent_ref)
-        .fetch_all(pool)
-        .await?;
+        let content_pages: Vec<PageRow> = db
+            .fetch("microsite pages", async || {
+                let mut query = sqlx::QueryBuilder::new(format!(
+                    r#"
+                    SELECT
+                        n.nid,
+                        n.title,
+                        pt.field_page_title_value as page_title,
+                        b.body_value,
+                        s.field_summary_value as summary_value,
+                        fb.field_body_value,
+                        n.status,
+                        n.created,
+                        n.changed,
+                        mld.id as menu_id,
+                        mld.title as menu_title,
+                        mld.weight as menu_weight,
+                        mld.parent as menu_parent,
+                        CAST(SUBSTRING(parent_mld.link__uri, LENGTH('entity:node/') + 1) AS UNSIGNED) as parent_nid,
+                        CAST(hero_file.uri AS CHAR(255)) as hero_image_uri,
+                        CAST(nav_file.uri AS CHAR(255)) as nav_image_uri,
+                        {PAGE_AUTHOR_COLUMNS}
+                    FROM menu_link_content_data mld
+                    JOIN node_field_data n ON mld.link__uri = CONCAT('entity:node/', n.nid)
+                    -- Parent menu link (menu_link_content:UUID) -> the node it links to
+                    LEFT JOIN menu_link_content parent_mlc
+                        ON mld.parent = CONCAT('menu_link_content:', CAST(parent_mlc.uuid AS CHAR(36)))
+                    LEFT JOIN menu_link_content_data parent_mld
+                        ON parent_mld.id = parent_mlc.id AND parent_mld.link__uri LIKE 'entity:node/%'
+                    {PAGE_FIELD_JOINS}
+                    WHERE mld.menu_name = 'microsites'
+                    AND mld.parent = "#
+                ));
+                query.push_bind(&parent_ref);
+                if !options.include_unpublished {
+                    query.push(" AND mld.enabled = 1");
+                }
+                query.push(" ORDER BY mld.weight, n.title");
+                query.build_query_as().fetch_all(db.pool()).await
+            })
+            .await?;
+        rows.extend(content_pages);
+    }
+
+    if options.latest_published_revision {
+        let nids: Vec<u64> = rows.iter().map(|row| row.nid).collect();
+        let mut revisions = published_revision_bodies(db, &nids).await?;
+        for row in &mut rows {
+            if let Some(revision) = revisions.remove(&row.nid) {
+                row.body_value = revision.body_value;
+                row.summary_value = revision.summary_value;
+                row.field_body_value = revision.field_body_value;
+            }
+        }
+    }
 
-        for row in content_pages {
-            let mut page: MicrositePage = row.into();
-            // Append featured pages content if any
-            let featured = featured_pages_content(pool, page.nid).await?;
-            if !featured.is_empty() {
-                if page.body_html.is_empty() {
-                    page.body_html = featured;
-                } else {
-                    page.body_html.push_str("\n\n");
-                    page.body_html.push_str(&featured);
+    let mut pages = with_featured_content(db, rows, options.body_sources).await?;
+    if options.repair_encoding {
+        for page in &mut pages {
+            page.title = repair_encoding(&page.title);
+            page.body_html = repair_encoding(&page.body_html);
+            if let Some(sources) = &mut page.body_sources {
+                for source in [
+                    &mut sources.summary,
+                    &mut sources.body,
+                    &mut sources.field_body,
+                    &mut sources.featured,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    *source = repair_encoding(source);
                 }
             }
-            pages.push(page);
         }
     }
+    Ok(pages)
+}
+
+/// Convert page rows, appending any featured pages content to the body
+async fn with_featured_content(
+    db: &Db,
+    rows: Vec<PageRow>,
+    body_sources: bool,
+) -> Result<Vec<MicrositePage>> {
+    let mut pages: Vec<MicrositePage> = Vec::with_capacity(rows.len());
+    for row in rows {
+        let featured = featured_pages_content(db, row.nid).await?;
+        pages.push(page_from_row(row, featured, body_sources));
+    }
 
     Ok(pages)
 }
 
+/// Convert a page row, appending the rendered featured pages (if any) to the
+/// body and keeping the separate fields when `body_sources` is set
+fn page_from_row(row: PageRow, featured: String, body_sources: bool) -> MicrositePage {
+    let sources = body_sources.then(|| BodySources {
+        summary: row.summary_value.clone(),
+        body: row.body_value.clone(),
+        field_body: row.field_body_value.clone(),
+        featured: (!featured.is_empty()).then(|| featured.clone()),
+    });
+    let mut page: MicrositePage = row.into();
+    page.body_sources = sources;
+    if !featured.is_empty() {
+        if page.body_html.is_empty() {
+            page.body_html = featured;
+        } else {
+            page.body_html.push_str("\n\n");
+            page.body_html.push_str(&featured);
+        }
+    }
+    page
+}
+
+/// Microsite pages that reference the club's homepage (`field_club`) but
+/// have no enabled link in the microsites menu.
+///
+/// [`pages_for_club`] discovers pages through the menu, so these would
+/// otherwise be dropped silently; editors decide whether to migrate them.
+/// Menu fields are always `None`.
+pub async fn orphan_pages(db: &Db, homepage_nid: u64) -> Result<Vec<MicrositePage>> {
+    let rows: Vec<PageRow> = db
+        .fetch("orphan microsite pages", async || {
+            sqlx::query_as(&format!(
+                r#"
+        SELECT
+            n.nid,
+            n.title,
+            pt.field_page_title_value as page_title,
+            b.body_value,
+            s.field_summary_value as summary_value,
+            fb.field_body_value,
+            n.status,
+            n.created,
+            n.changed,
+            NULL as menu_id,
+            NULL as menu_title,
+            NULL as menu_weight,
+            NULL as menu_parent,
+            NULL as parent_nid,
+            CAST(hero_file.uri AS CHAR(255)) as hero_image_uri,
+            CAST(nav_file.uri AS CHAR(255)) as nav_image_uri,
+            {PAGE_AUTHOR_COLUMNS}
+        FROM node_field_data n
+        JOIN node__field_club fc ON fc.entity_id = n.nid AND fc.deleted = 0
+        {PAGE_FIELD_JOINS}
+        WHERE fc.field_club_target_id = ?
+        AND n.nid <> fc.field_club_target_id
+        AND (n.type = 'microsite_content' OR n.type LIKE 'microsite\_lander%')
+        AND NOT EXISTS (
+            SELECT 1 FROM menu_link_content_data mld
+            WHERE mld.link__uri = CONCAT('entity:node/', n.nid)
+            AND mld.menu_name = 'microsites'
+            AND mld.enabled = 1
+        )
+        ORDER BY n.title
+        "#
+            ))
+            .bind(homepage_nid)
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?;
+
+    with_featured_content(db, rows, false).await
+}
+
+/// Body fields of a page's latest published revision
+#[derive(Debug, sqlx::FromRow)]
+struct RevisionBodyRow {
+    nid: u64,
+    body_value: Option<String>,
+    summary_value: Option<String>,
+    field_body_value: Option<String>,
+}
+
+/// Body fields from the latest published revision of each node, for nodes
+/// whose latest published revision is not the default revision.
+async fn published_revision_bodies(db: &Db, nids: &[u64]) -> Result<HashMap<u64, RevisionBodyRow>> {
+    if nids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<RevisionBodyRow> = db
+        .fetch("page revisions", async || {
+            let mut query = sqlx::QueryBuilder::new(
+                r#"
+            SELECT
+                r.nid,
+                b.body_value,
+                s.field_summary_value as summary_value,
+                fb.field_body_value
+            FROM node_field_revision r
+            JOIN node_field_data n ON n.nid = r.nid
+            LEFT JOIN node_revision__body b ON b.revision_id = r.vid AND b.deleted = 0
+            LEFT JOIN node_revision__field_summary s ON s.revision_id = r.vid AND s.deleted = 0
+            LEFT JOIN node_revision__field_body fb ON fb.revision_id = r.vid AND fb.deleted = 0
+            WHERE r.status = 1
+            AND r.vid <> n.vid
+            AND r.vid = (
+                SELECT MAX(latest.vid) FROM node_field_revision latest
+                WHERE latest.nid = r.nid AND latest.status = 1
+            )
+            AND r.nid IN ("#,
+            );
+            let mut separated = query.separated(", ");
+            for nid in nids {
+                separated.push_bind(*nid);
+            }
+            query.push(")");
+            query.build_query_as().fetch_all(db.pool()).await
+        })
+        .await?;
+
+    Ok(rows.into_iter().map(|row| (row.nid, row)).collect())
+}
+
 /// Extract media URLs from HTML content.
 ///
 /// Finds all `/sites/default/files/` URLs that need to be downloaded.
 pub fn extract_media_urls(html: &str) -> Vec<String> {
+    extract_media_refs(html)
+        .into_iter()
+        .map(|media| media.url)
+        .collect()
+}
+
+/// Attribute a media URL was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaAttr {
+    Src,
+    Href,
+}
+
+/// A media URL in HTML content and where it is
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MediaMatch {
+    pub url: String,
+    /// Byte offset of the URL's first character, inside the quotes
+    pub start: usize,
+    /// Byte offset just past the URL, so `&html[start..end] == url`
+    pub end: usize,
+    pub attr: MediaAttr,
+}
+
+/// Like [`extract_media_urls`], with the position of each URL so it can be
+/// highlighted or replaced in place. Matches are in document order.
+pub fn extract_media_refs(html: &str) -> Vec<MediaMatch> {
     use regex::Regex;
     use std::sync::LazyLock;
 
     static MEDIA_RE: LazyLock<Regex> = LazyLock::new(|| {
-        Regex::new(r#"(?:src|href)=["']([^"']*?/sites/default/files/[^"']+)["']"#)
+        Regex::new(r#"(src|href)=["']([^"']*?/sites/default/files/[^"']+)["']"#)
             .expect("Invalid media regex")
     });
 
     MEDIA_RE
         .captures_iter(html)
-        .filter_map(|cap| cap.get(1).map(|m| m.as_str().to_string()))
+        .map(|cap| {
+            let url = cap.get(2).expect("URL group always participates");
+            MediaMatch {
+                url: url.as_str().to_string(),
+                start: url.start(),
+                end: url.end(),
+                attr: match &cap[1] {
+                    "src" => MediaAttr::Src,
+                    _ => MediaAttr::Href,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Matches `href`s pointing at a node: `entity:node/N`, `internal:/node/N`
+/// or `/node/N`, optionally followed by a query string or fragment.
+fn node_link_re() -> &'static regex::Regex {
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    static NODE_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"href=(["'])(?:entity:node/|internal:/node/|/node/)(\d+)([?#][^"']*)?["']"#)
+            .expect("Invalid node link regex")
+    });
+    &NODE_LINK_RE
+}
+
+/// Rewrite node links in HTML to the target node's path alias.
+///
+/// Links to nodes without an alias are left unchanged and logged as
+/// warnings so they can be fixed by hand.
+pub async fn resolve_node_links(db: &Db, html: &str) -> Result<String> {
+    let nids: Vec<u64> = node_link_re()
+        .captures_iter(html)
+        .filter_map(|cap| cap[2].parse().ok())
+        .collect::<HashSet<u64>>()
+        .into_iter()
+        .collect();
+    if nids.is_empty() {
+        return Ok(html.to_string());
+    }
+
+    let aliases: HashMap<u64, String> = db
+        .fetch("path aliases", async || {
+            let mut query = sqlx::QueryBuilder::new(
+                "SELECT CAST(SUBSTRING(path, LENGTH('/node/') + 1) AS UNSIGNED) AS nid, alias \
+             FROM path_alias WHERE path IN (",
+            );
+            let mut separated = query.separated(", ");
+            for nid in &nids {
+                separated.push_bind(format!("/node/{nid}"));
+            }
+            // Newest alias wins when a node has several
+            query.push(") ORDER BY id");
+            query
+                .build_query_as::<(u64, String)>()
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?
+        .into_iter()
+        .collect();
+
+    let (html, unresolved) = rewrite_node_links(html, &aliases);
+    for nid in unresolved {
+        log::warn!("no path alias for linked node {nid}; link left as-is");
+    }
+    Ok(html)
+}
+
+/// Replace node links that have an alias, returning the rewritten HTML and
+/// the nids that had none
+fn rewrite_node_links(html: &str, aliases: &HashMap<u64, String>) -> (String, Vec<u64>) {
+    let mut unresolved = Vec::new();
+    let html = node_link_re().replace_all(html, |cap: &regex::Captures| {
+        let quote = &cap[1];
+        let alias = cap[2].parse().ok().and_then(|nid| {
+            let alias = aliases.get(&nid);
+            if alias.is_none() && !unresolved.contains(&nid) {
+                unresolved.push(nid);
+            }
+            alias
+        });
+        match alias {
+            Some(alias) => {
+                let suffix = cap.get(3).map_or("", |m| m.as_str());
+                format!("href={quote}{alias}{suffix}{quote}")
+            }
+            None => cap[0].to_string(),
+        }
+    });
+    (html.into_owned(), unresolved)
+}
+
+/// Media referenced by a set of pages: body URLs plus hero and navigation
+/// images as `/sites/default/files/` paths, de-duplicated in first-seen order.
+pub fn collect_page_media(pages: &[MicrositePage]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    pages
+        .iter()
+        .flat_map(|page| {
+            let images = [&page.hero_image, &page.nav_image]
+                .into_iter()
+                .flatten()
+                .filter_map(|uri| drupal_uri_to_path(uri));
+            extract_media_urls(&page.body_html)
+                .into_iter()
+                .chain(images)
+        })
+        .filter(|url| seen.insert(url.clone()))
         .collect()
 }
 
+/// Absolute URL of a media URL found in page content, e.g.
+/// `https://airstreamclub.org/sites/default/files/logo.png` for
+/// `/sites/default/files/logo.png` and base URL `https://airstreamclub.org/`.
+///
+/// Absolute URLs are returned unchanged and protocol-relative ones get
+/// `https:`. Relative paths without a leading slash are taken as relative to
+/// the site root, which is how Drupal's editor stores them.
+pub fn resolve_media_url(base_url: &str, url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return url.to_string();
+    }
+    if url.starts_with("//") {
+        return format!("https:{url}");
+    }
+    let base_url = base_url.trim_end_matches('/');
+    let path = url.trim_start_matches('/');
+    format!("{base_url}/{path}")
+}
+
+/// Map a media URL (absolute or relative) back to its `public://` URI.
+///
+/// Strips any query string or fragment and percent-decodes the path so it
+/// matches `file_managed.uri`.
+pub fn media_url_to_uri(url: &str) -> Option<String> {
+    let (_, path) = url.split_once("/sites/default/files/")?;
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    Some(format!("public://{}", percent_decode(path)))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 /// Homepage assets (banner image, logo, etc.)
 #[derive(Debug, Default)]
 pub struct HomepageAssets {
@@ -426,53 +1190,58 @@ pub struct HomepageAssets {
 }
 
 /// Fetch homepage assets (banner image, logo, etc.) for a microsite.
-pub async fn homepage_assets(pool: &MySqlPool, homepage_nid: u64) -> Result<HomepageAssets> {
+pub async fn homepage_assets(db: &Db, homepage_nid: u64) -> Result<HomepageAssets> {
     // Get banner image via: field_desktop_banner_image -> media -> field_media_image -> file
     // CAST is needed because utf8mb4_bin collation is interpreted as VARBINARY by sqlx
-    let banner_image: Option<String> = sqlx::query_scalar(
+    let banner_image: Option<String> = db.fetch("homepage banner", async || sqlx::query_scalar(
         r#"
         SELECT CAST(f.uri AS CHAR(255))
         FROM node__field_desktop_banner_image dbi
         JOIN media__field_media_image mfi ON mfi.entity_id = dbi.field_desktop_banner_image_target_id
+            AND mfi.deleted = 0
         JOIN file_managed f ON f.fid = mfi.field_media_image_target_id
-        WHERE dbi.entity_id = ?
+        WHERE dbi.entity_id = ? AND dbi.deleted = 0
         LIMIT 1
         "#,
     )
-    .bind(homepage_nid)
-    .fetch_optional(pool)
-    .await?;
+    .bind(homepage_nid).fetch_optional(db.pool()).await).await?;
 
     // Get logo image via: media with field_club pointing to homepage AND name contains 'logo'
     // Pick the first one by media ID (oldest upload)
-    let logo_image: Option<String> = sqlx::query_scalar(
-        r#"
+    let logo_image: Option<String> = db
+        .fetch("homepage logo", async || {
+            sqlx::query_scalar(
+                r#"
         SELECT CAST(f.uri AS CHAR(255))
         FROM media__field_club mfc
         JOIN media_field_data m ON m.mid = mfc.entity_id
-        JOIN media__field_media_image mfi ON mfi.entity_id = m.mid
+        JOIN media__field_media_image mfi ON mfi.entity_id = m.mid AND mfi.deleted = 0
         JOIN file_managed f ON f.fid = mfi.field_media_image_target_id
-        WHERE mfc.field_club_target_id = ?
+        WHERE mfc.field_club_target_id = ? AND mfc.deleted = 0
         AND m.name LIKE '%logo%'
         ORDER BY m.mid
         LIMIT 1
         "#,
-    )
-    .bind(homepage_nid)
-    .fetch_optional(pool)
-    .await?;
+            )
+            .bind(homepage_nid)
+            .fetch_optional(db.pool())
+            .await
+        })
+        .await?;
 
     // Get Facebook URL from social media paragraphs (field_social_media_new)
     // or from button field if it points to facebook.com
-    let facebook_url: Option<String> = sqlx::query_scalar(
-        r#"
+    let facebook_url: Option<String> = db
+        .fetch("homepage facebook", async || {
+            sqlx::query_scalar(
+                r#"
         SELECT url FROM (
             -- From social_media_new paragraph reference
             SELECT sml.field_social_media_link_uri as url
             FROM node__field_social_media_new smn
             JOIN paragraph__field_social_media_link sml
-                ON sml.entity_id = smn.field_social_media_new_target_id
-            WHERE smn.entity_id = ?
+                ON sml.entity_id = smn.field_social_media_new_target_id AND sml.deleted = 0
+            WHERE smn.entity_id = ? AND smn.deleted = 0
             AND sml.field_social_media_link_uri LIKE '%facebook.com%'
             LIMIT 1
         ) social
@@ -481,17 +1250,19 @@ pub async fn homepage_assets(pool: &MySqlPool, homepage_nid: u64) -> Result<Home
             -- From button field (only if pointing to facebook)
             SELECT field_button_uri as url
             FROM node__field_button
-            WHERE entity_id = ?
+            WHERE entity_id = ? AND deleted = 0
             AND field_button_uri LIKE '%facebook.com%'
             LIMIT 1
         ) button
         LIMIT 1
         "#,
-    )
-    .bind(homepage_nid)
-    .bind(homepage_nid)
-    .fetch_optional(pool)
-    .await?;
+            )
+            .bind(homepage_nid)
+            .bind(homepage_nid)
+            .fetch_optional(db.pool())
+            .await
+        })
+        .await?;
 
     Ok(HomepageAssets {
         banner_image,
@@ -500,6 +1271,292 @@ pub async fn homepage_assets(pool: &MySqlPool, homepage_nid: u64) -> Result<Home
     })
 }
 
+/// Homepage hero and intro split into the slots the new site template renders
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct HomepageLayout {
+    /// Desktop banner image (public:// URI)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub hero_image: Option<String>,
+    /// Headline of the first featured paragraph (plain text)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub headline: Option<String>,
+    /// Intro text of the first featured paragraph (HTML)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub intro_html: Option<String>,
+    /// Button of the first featured paragraph
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub cta: Option<CallToAction>,
+    /// Buttons of every featured paragraph, in display order, for mapping to
+    /// button components instead of parsing them out of the page HTML
+    pub ctas: Vec<CallToAction>,
+}
+
+/// Button link; the label falls back to the URL when the button has no title
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CallToAction {
+    pub label: String,
+    pub url: String,
+}
+
+impl CallToAction {
+    fn from_row(row: &FeaturedPageRow) -> Option<Self> {
+        let url = row.button_uri.clone()?;
+        Some(Self {
+            label: row.button_title.clone().unwrap_or_else(|| url.clone()),
+            url,
+        })
+    }
+}
+
+/// Fetch the structured hero/intro of a microsite homepage.
+///
+/// The hero image comes from [`homepage_assets`]; headline, intro and call to
+/// action come from the first `field_featured_pages` paragraph, which is how
+/// club homepages lay out their intro. `ctas` has the buttons of all of them.
+pub async fn homepage_layout(db: &Db, homepage_nid: u64) -> Result<HomepageLayout> {
+    let assets = homepage_assets(db, homepage_nid).await?;
+    let rows = featured_page_rows(db, homepage_nid).await?;
+    Ok(layout_from(assets, rows))
+}
+
+fn layout_from(assets: HomepageAssets, rows: Vec<FeaturedPageRow>) -> HomepageLayout {
+    let ctas = rows.iter().filter_map(CallToAction::from_row).collect();
+    let Some(intro) = rows.into_iter().next() else {
+        return HomepageLayout {
+            hero_image: assets.banner_image,
+            ..Default::default()
+        };
+    };
+    HomepageLayout {
+        hero_image: assets.banner_image,
+        cta: CallToAction::from_row(&intro),
+        headline: intro.headline,
+        intro_html: intro.summary_text_2,
+        ctas,
+    }
+}
+
+/// A managed file from Drupal's file_managed table.
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct FileInfo {
+    /// File ID
+    pub fid: u64,
+    /// File URI (public://...)
+    pub uri: String,
+    pub filename: String,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub filemime: Option<String>,
+    /// Size in bytes
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub filesize: Option<u64>,
+    /// Unix timestamp of the upload
+    pub created: i64,
+    /// Unix timestamp of the last modification
+    pub changed: i64,
+}
+
+/// Fetch public files uploaded or modified since a unix timestamp.
+///
+/// Filters on `file_managed.changed`, which Drupal sets to `created` on upload
+/// and bumps whenever the file entity is replaced or re-saved, so it covers
+/// both new and modified files.
+pub async fn changed_media_since(db: &Db, since: i64) -> Result<Vec<FileInfo>> {
+    db.fetch("media files", async || {
+        sqlx::query_as::<_, FileInfo>(
+            r#"
+        SELECT
+            f.fid,
+            CAST(f.uri AS CHAR(255)) AS uri,
+            f.filename,
+            f.filemime,
+            f.filesize,
+            f.created,
+            f.changed
+        FROM file_managed f
+        WHERE f.uri LIKE 'public://%'
+          AND f.changed >= ?
+        ORDER BY f.changed, f.fid
+        "#,
+        )
+        .bind(since)
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
+
+/// Fetch managed files by `public://` URI (for sizes and mime types).
+///
+/// URIs without a `file_managed` row are simply absent from the result.
+pub async fn files_by_uri(db: &Db, uris: &[String]) -> Result<Vec<FileInfo>> {
+    crate::chunked::chunked_in(uris, db.in_chunk_size(), async |chunk| {
+        db.fetch("media files", async || {
+            let mut query = sqlx::QueryBuilder::new(
+                r#"
+                SELECT
+                    f.fid,
+                    CAST(f.uri AS CHAR(255)) AS uri,
+                    f.filename,
+                    f.filemime,
+                    f.filesize,
+                    f.created,
+                    f.changed
+                FROM file_managed f
+                WHERE f.uri IN ("#,
+            );
+            let mut separated = query.separated(", ");
+            for uri in chunk {
+                separated.push_bind(uri);
+            }
+            query.push(")");
+            query
+                .build_query_as::<FileInfo>()
+                .fetch_all(db.pool())
+                .await
+        })
+        .await
+    })
+    .await
+}
+
+/// A club microsite with all of its pages
+#[derive(Debug)]
+pub struct ClubPages {
+    pub club: ClubMicrosite,
+    pub pages: Vec<MicrositePage>,
+}
+
+/// Fetch the pages of every club microsite.
+pub async fn all_pages(db: &Db, options: &PageOptions) -> Result<Vec<ClubPages>> {
+    let mut all = Vec::new();
+    for club in clubs_with_microsites(db).await? {
+        let pages = pages_for_club_opts(db, club.homepage_nid, options).await?;
+        all.push(ClubPages { club, pages });
+    }
+    Ok(all)
+}
+
+/// A downloadable file (PDF, Word document, ...) linked from a club's pages
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct Document {
+    /// Link text of the first link to the file, else its file name
+    pub title: String,
+    /// `/sites/default/files/` path
+    pub url: String,
+    /// `public://` URI
+    pub uri: String,
+    /// `None` when the file has no `file_managed` row
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub filemime: Option<String>,
+    /// Size in bytes
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub filesize: Option<u64>,
+    /// Pages linking to the file
+    pub page_nids: Vec<u64>,
+}
+
+/// Extensions treated as images when a file has no mime type on record
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "svg", "bmp", "tif", "tiff",
+];
+
+/// Fetch the documents linked from a club's published pages, sorted by title.
+///
+/// Every `<a href>` to `/sites/default/files/` counts, except links to
+/// images (by `file_managed` mime type, else by extension).
+pub async fn documents(db: &Db, homepage_nid: u64) -> Result<Vec<Document>> {
+    let pages = pages_for_club(db, homepage_nid).await?;
+    let uris: Vec<String> = pages
+        .iter()
+        .flat_map(|page| document_links(&page.body_html))
+        .filter_map(|(url, _)| media_url_to_uri(&url))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let files = files_by_uri(db, &uris).await?;
+    Ok(documents_from(&pages, files))
+}
+
+/// `(url, link text)` of every file link in `html`; markup in the link text
+/// is dropped
+fn document_links(html: &str) -> Vec<(String, String)> {
+    use regex::Regex;
+    use std::sync::LazyLock;
+
+    static LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r#"(?is)<a\s[^>]*href=["']([^"']*?/sites/default/files/[^"']+)["'][^>]*>(.*?)</a>"#,
+        )
+        .expect("Invalid document link regex")
+    });
+    static TAG_RE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"<[^>]*>").expect("Invalid tag regex"));
+
+    LINK_RE
+        .captures_iter(html)
+        .map(|cap| {
+            let text = TAG_RE.replace_all(&cap[2], " ");
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (cap[1].to_string(), text)
+        })
+        .collect()
+}
+
+fn documents_from(pages: &[MicrositePage], files: Vec<FileInfo>) -> Vec<Document> {
+    let mut files: HashMap<String, FileInfo> = files
+        .into_iter()
+        .map(|file| (file.uri.clone(), file))
+        .collect();
+    let mut documents: Vec<Document> = Vec::new();
+    let mut by_uri: HashMap<String, usize> = HashMap::new();
+    for page in pages {
+        for (url, text) in document_links(&page.body_html) {
+            let Some(uri) = media_url_to_uri(&url) else {
+                continue;
+            };
+            if let Some(&index) = by_uri.get(&uri) {
+                let page_nids = &mut documents[index].page_nids;
+                if !page_nids.contains(&page.nid) {
+                    page_nids.push(page.nid);
+                }
+                continue;
+            }
+            let file = files.remove(&uri);
+            let is_image = match file.as_ref().and_then(|file| file.filemime.as_deref()) {
+                Some(mime) => mime.starts_with("image/"),
+                None => uri.rsplit_once('.').is_some_and(|(_, ext)| {
+                    IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+                }),
+            };
+            if is_image {
+                continue;
+            }
+            let filename = file.as_ref().map(|file| file.filename.clone());
+            let title = if text.is_empty() {
+                filename
+                    .clone()
+                    .unwrap_or_else(|| uri.rsplit('/').next().unwrap_or_default().to_string())
+            } else {
+                text
+            };
+            by_uri.insert(uri.clone(), documents.len());
+            documents.push(Document {
+                title,
+                url: drupal_uri_to_path(&uri).unwrap_or(url),
+                filemime: file.as_ref().and_then(|file| file.filemime.clone()),
+                filesize: file.as_ref().and_then(|file| file.filesize),
+                filename,
+                uri,
+                page_nids: vec![page.nid],
+            });
+        }
+    }
+    documents.sort_by_cached_key(|document| document.title.to_lowercase());
+    documents
+}
+
 /// Convert a Drupal public:// URI to a /sites/default/files/ path.
 pub fn drupal_uri_to_path(uri: &str) -> Option<String> {
     uri.strip_prefix("public://")
@@ -510,6 +1567,94 @@ pub fn drupal_uri_to_path(uri: &str) -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_slug_issues() {
+        let slug = |club_nid: u64, slug: &str| ClubSlug {
+            club_nid,
+            slug: slug.to_string(),
+        };
+        let slugs = [
+            slug(1, "rocky-mountain"),
+            slug(2, "Rocky-Mountain"),
+            slug(3, "lone-star"),
+            slug(3, "lone-star"),
+            slug(4, "Wally Byam Club/"),
+            slug(5, "club-42"),
+        ];
+        assert_eq!(
+            slug_issues(&slugs),
+            vec![
+                SlugIssue::Duplicate {
+                    slug: "rocky-mountain".to_string(),
+                    club_nids: vec![1, 2],
+                },
+                SlugIssue::InvalidCharacters {
+                    club_nid: 2,
+                    slug: "Rocky-Mountain".to_string(),
+                    suggestion: "rocky-mountain".to_string(),
+                },
+                SlugIssue::InvalidCharacters {
+                    club_nid: 4,
+                    slug: "Wally Byam Club/".to_string(),
+                    suggestion: "wally-byam-club".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_joins_exclude_deleted_rows() {
+        use crate::sql_audit::unguarded_field_joins;
+        let clubs = clubs_with_microsites_query();
+        for sql in [PAGE_FIELD_JOINS, FEATURED_PAGES_QUERY, clubs.sql()] {
+            assert_eq!(unguarded_field_joins(sql), Vec::<String>::new(), "{sql}");
+        }
+    }
+
+    fn page(nid: u64, body_html: &str) -> MicrositePage {
+        let mut page: MicrositePage = page_row(None, None, None).into();
+        page.nid = nid;
+        page.body_html = body_html.to_string();
+        page
+    }
+
+    #[test]
+    fn test_documents_from() {
+        let pages = [
+            page(
+                1,
+                r#"<p><a href="/sites/default/files/bylaws%202024.pdf"><strong>Club
+                Bylaws</strong></a> and <a href="/sites/default/files/photo.jpg">a photo</a></p>
+                <img src="/sites/default/files/banner.png">"#,
+            ),
+            page(
+                2,
+                r#"<a href="https://airstreamclub.org/sites/default/files/bylaws%202024.pdf">here</a>
+                <a href='/sites/default/files/news/2024-05.docx'></a>"#,
+            ),
+        ];
+        let files = vec![FileInfo {
+            fid: 9,
+            uri: "public://bylaws 2024.pdf".to_string(),
+            filename: "bylaws 2024.pdf".to_string(),
+            filemime: Some("application/pdf".to_string()),
+            filesize: Some(1024),
+            created: 0,
+            changed: 0,
+        }];
+
+        let documents = documents_from(&pages, files);
+        assert_eq!(documents.len(), 2);
+        let bylaws = &documents[1];
+        assert_eq!(bylaws.title, "Club Bylaws");
+        assert_eq!(bylaws.url, "/sites/default/files/bylaws 2024.pdf");
+        assert_eq!(bylaws.filesize, Some(1024));
+        assert_eq!(bylaws.page_nids, vec![1, 2]);
+        let newsletter = &documents[0];
+        assert_eq!(newsletter.title, "2024-05.docx");
+        assert_eq!(newsletter.filename, None);
+    }
+
     #[test]
     fn test_extract_media_urls() {
         let html = r#"
@@ -524,6 +1669,28 @@ mod tests {
         assert!(urls.contains(&"/sites/default/files/docs/manual.pdf".to_string()));
     }
 
+    #[test]
+    fn test_extract_media_refs() {
+        let html = r#"<p><img src="/sites/default/files/a.png"> café <a href='https://airstreamclub.org/sites/default/files/b%20c.pdf'>b</a></p>"#;
+        let refs = extract_media_refs(html);
+        assert_eq!(
+            refs.iter()
+                .map(|media| (media.attr, &html[media.start..media.end]))
+                .collect::<Vec<_>>(),
+            vec![
+                (MediaAttr::Src, "/sites/default/files/a.png"),
+                (
+                    MediaAttr::Href,
+                    "https://airstreamclub.org/sites/default/files/b%20c.pdf"
+                ),
+            ]
+        );
+        assert!(
+            refs.iter()
+                .all(|media| html[media.start..media.end] == media.url)
+        );
+    }
+
     #[test]
     fn test_extract_media_urls_empty() {
         let html = "<p>No media here</p>";
@@ -531,6 +1698,154 @@ mod tests {
         assert!(urls.is_empty());
     }
 
+    #[test]
+    fn test_render_featured_pages_escapes_text() {
+        let rows = vec![FeaturedPageRow {
+            headline: Some("A & B <tag>".to_string()),
+            summary_text_2: Some("<p>Already <em>HTML</em></p>".to_string()),
+            button_uri: Some("/events?club=1&page=\"2\"".to_string()),
+            button_title: Some("Rallies & <b>more</b>".to_string()),
+            image_uri: None,
+            image_alt: None,
+        }];
+
+        let html = render_featured_pages(rows);
+        assert!(html.contains("<h3>A &amp; B &lt;tag&gt;</h3>"));
+        assert!(html.contains("<p>Already <em>HTML</em></p>"));
+        assert!(html.contains(
+            "<a href=\"/events?club=1&amp;page=&quot;2&quot;\">Rallies &amp; &lt;b&gt;more&lt;/b&gt;</a>"
+        ));
+    }
+
+    #[test]
+    fn test_render_featured_pages_image_alt() {
+        let row = |alt: Option<&str>| FeaturedPageRow {
+            headline: None,
+            summary_text_2: None,
+            button_uri: None,
+            button_title: None,
+            image_uri: Some("public://2025-06/rally.jpg".to_string()),
+            image_alt: alt.map(str::to_string),
+        };
+
+        let html = render_featured_pages(vec![row(Some("Rally \"group\" photo")), row(None)]);
+        assert!(html.contains(
+            "<img src=\"/sites/default/files/2025-06/rally.jpg\" alt=\"Rally &quot;group&quot; photo\">"
+        ));
+        assert!(html.contains("<img src=\"/sites/default/files/2025-06/rally.jpg\" alt=\"\">"));
+    }
+
+    #[test]
+    fn test_word_count_ignores_markup() {
+        let page = MicrositePage {
+            nid: 1,
+            title: "Rally".to_string(),
+            body_html: r#"<p>Join us at <a href="https://example.com/some long url">the rally</a></p><img src="/sites/default/files/a.jpg" alt="group photo">"#
+                .to_string(),
+            status: true,
+            created: 1_700_000_000,
+            changed: 1_700_000_000,
+            menu_id: None,
+            menu_title: None,
+            menu_weight: None,
+            menu_parent: None,
+            parent_nid: None,
+            hero_image: None,
+            nav_image: None,
+            last_author_uid: None,
+            last_author_name: None,
+            body_sources: None,
+        };
+        assert_eq!(page.word_count(), 5);
+        assert_eq!(page.reading_time_minutes(), 0);
+    }
+
+    fn page_row(summary: Option<&str>, body: Option<&str>, field_body: Option<&str>) -> PageRow {
+        PageRow {
+            nid: 1,
+            title: "About".to_string(),
+            page_title: None,
+            body_value: body.map(String::from),
+            summary_value: summary.map(String::from),
+            field_body_value: field_body.map(String::from),
+            status: 1,
+            created: 0,
+            changed: 0,
+            menu_id: None,
+            menu_title: None,
+            menu_weight: None,
+            menu_parent: None,
+            parent_nid: None,
+            hero_image_uri: None,
+            nav_image_uri: None,
+            last_author_uid: None,
+            last_author_name: None,
+        }
+    }
+
+    #[test]
+    fn test_page_from_row_body_sources() {
+        let row = || page_row(Some("<p>Intro</p>"), Some("<p>Body</p>"), None);
+        let featured = "<h3>Rallies</h3>\n".to_string();
+
+        let merged = page_from_row(row(), featured.clone(), false);
+        assert_eq!(
+            merged.body_html,
+            "<p>Intro</p>\n\n<p>Body</p>\n\n<h3>Rallies</h3>\n"
+        );
+        assert_eq!(merged.body_sources, None);
+
+        let page = page_from_row(row(), featured, true);
+        assert_eq!(page.body_html, merged.body_html);
+        assert_eq!(
+            page.body_sources,
+            Some(BodySources {
+                summary: Some("<p>Intro</p>".to_string()),
+                body: Some("<p>Body</p>".to_string()),
+                field_body: None,
+                featured: Some("<h3>Rallies</h3>\n".to_string()),
+            })
+        );
+        let page = page_from_row(row(), String::new(), true);
+        assert_eq!(page.body_sources.unwrap().featured, None);
+    }
+
+    #[test]
+    fn test_resolve_media_url() {
+        let base = "https://airstreamclub.org/";
+        assert_eq!(
+            resolve_media_url(base, "/sites/default/files/logo.png"),
+            "https://airstreamclub.org/sites/default/files/logo.png"
+        );
+        assert_eq!(
+            resolve_media_url("https://airstreamclub.org", "sites/default/files/a b.jpg"),
+            "https://airstreamclub.org/sites/default/files/a b.jpg"
+        );
+        assert_eq!(
+            resolve_media_url(base, "http://old.example.org/sites/default/files/x.pdf"),
+            "http://old.example.org/sites/default/files/x.pdf"
+        );
+        assert_eq!(
+            resolve_media_url(base, "//cdn.example.org/sites/default/files/x.pdf"),
+            "https://cdn.example.org/sites/default/files/x.pdf"
+        );
+    }
+
+    #[test]
+    fn test_media_url_to_uri() {
+        assert_eq!(
+            media_url_to_uri(
+                "https://airstreamclub.org/sites/default/files/2025-06/Rally%20Map.pdf?itok=x1"
+            ),
+            Some("public://2025-06/Rally Map.pdf".to_string())
+        );
+        assert_eq!(
+            media_url_to_uri("/sites/default/files/images/photo.jpg#top"),
+            Some("public://images/photo.jpg".to_string())
+        );
+        assert_eq!(media_url_to_uri("https://example.com/photo.jpg"), None);
+    }
+
     #[test]
     fn test_drupal_uri_to_path() {
         assert_eq!(
@@ -540,4 +1855,79 @@ mod tests {
         assert_eq!(drupal_uri_to_path("private://secret.pdf"), None);
         assert_eq!(drupal_uri_to_path("not-a-uri"), None);
     }
+
+    #[test]
+    fn test_layout_from_first_featured_paragraph() {
+        let assets = HomepageAssets {
+            banner_image: Some("public://banner.jpg".to_string()),
+            ..Default::default()
+        };
+        let row = |headline: &str, button_title: Option<&str>| FeaturedPageRow {
+            headline: Some(headline.to_string()),
+            summary_text_2: Some("<p>Welcome</p>".to_string()),
+            button_uri: Some("https://example.com/join".to_string()),
+            button_title: button_title.map(str::to_string),
+            image_uri: None,
+            image_alt: None,
+        };
+        let mut no_button = row("Third", None);
+        no_button.button_uri = None;
+
+        let layout = layout_from(
+            assets,
+            vec![
+                row("Hello", None),
+                row("Second", Some("Rallies")),
+                no_button,
+            ],
+        );
+        let join = CallToAction {
+            label: "https://example.com/join".to_string(),
+            url: "https://example.com/join".to_string(),
+        };
+        assert_eq!(
+            layout,
+            HomepageLayout {
+                hero_image: Some("public://banner.jpg".to_string()),
+                headline: Some("Hello".to_string()),
+                intro_html: Some("<p>Welcome</p>".to_string()),
+                cta: Some(join.clone()),
+                ctas: vec![
+                    join,
+                    CallToAction {
+                        label: "Rallies".to_string(),
+                        url: "https://example.com/join".to_string(),
+                    },
+                ],
+            }
+        );
+
+        let empty = layout_from(HomepageAssets::default(), vec![]);
+        assert_eq!(empty, HomepageLayout::default());
+    }
+
+    #[test]
+    fn test_rewrite_node_links() {
+        let aliases = HashMap::from([(12, "/rallies/spring".to_string())]);
+        let html = r#"<a href="entity:node/12">a</a> <a href='/node/12#map'>b</a> <a href="/node/99">c</a> <a href="/node/123">d</a>"#;
+        let (rewritten, unresolved) = rewrite_node_links(html, &aliases);
+        assert_eq!(
+            rewritten,
+            r#"<a href="/rallies/spring">a</a> <a href='/rallies/spring#map'>b</a> <a href="/node/99">c</a> <a href="/node/123">d</a>"#
+        );
+        assert_eq!(unresolved, vec![99, 123]);
+    }
+
+    #[test]
+    fn test_repair_encoding() {
+        assert_eq!(repair_encoding("donâ€™t"), "don\u{2019}t");
+        assert_eq!(
+            repair_encoding("<p>â€œHiâ€\u{9D} â€” cafÃ©â€¦</p>"),
+            "<p>\u{201C}Hi\u{201D} \u{2014} café\u{2026}</p>"
+        );
+        assert_eq!(
+            repair_encoding("don\u{2019}t — fine"),
+            "don\u{2019}t — fine"
+        );
+    }
 }
@@ -0,0 +1,85 @@
+//! Owner resolution for nodes attached to a club or region microsite.
+//!
+//! Content nodes (events, news posts, ...) reference a microsite_homepage via
+//! `field_club`. The owning ssp_club/ssp_region node links to that same
+//! homepage through `field_main_site_club`, so the owner is found by joining
+//! node → field_club → main_site_club → node_field_data.
+
+use crate::{Db, Result};
+
+/// Owning club or region of a node
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow, serde::Serialize)]
+pub struct EventOwner {
+    /// nid of the owning ssp_club or ssp_region node
+    pub uid: u64,
+    /// "ssp_club" | "ssp_region"
+    pub node_type: String,
+}
+
+impl EventOwner {
+    pub fn is_club(&self) -> bool {
+        self.node_type == "ssp_club"
+    }
+
+    pub fn is_region(&self) -> bool {
+        self.node_type == "ssp_region"
+    }
+}
+
+/// Columns selected by [`owner_joins`], named to match `owner_uid` / `owner_node_type`
+pub(crate) const OWNER_COLUMNS: &str = r#"
+        owner_msc.entity_id AS owner_uid,
+        owner_nd.type AS owner_node_type
+"#;
+
+/// Owner resolution joins for the node table aliased as `node_alias`.
+///
+/// Nodes without a `field_club` (international content) resolve to NULL owner
+/// columns. Use together with [`OWNER_COLUMNS`].
+pub(crate) fn owner_joins(node_alias: &str) -> String {
+    format!(
+        r#"
+    LEFT JOIN node__field_club owner_fc ON {node_alias}.nid = owner_fc.entity_id AND owner_fc.deleted = 0
+    LEFT JOIN node__field_main_site_club owner_msc
+        ON owner_fc.field_club_target_id = owner_msc.field_main_site_club_target_id AND owner_msc.deleted = 0
+    LEFT JOIN node_field_data owner_nd ON owner_msc.entity_id = owner_nd.nid
+"#
+    )
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OwnerRow {
+    owner_uid: Option<u64>,
+    owner_node_type: Option<String>,
+}
+
+impl From<OwnerRow> for Option<EventOwner> {
+    fn from(value: OwnerRow) -> Self {
+        match (value.owner_uid, value.owner_node_type) {
+            (Some(uid), Some(node_type)) => Some(EventOwner { uid, node_type }),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve the owning club or region of a node.
+///
+/// Returns `None` for international content (no `field_club`) or when the
+/// referenced microsite has no owning ssp_club/ssp_region.
+pub async fn resolve(db: &Db, node_nid: u64) -> Result<Option<EventOwner>> {
+    let row: Option<OwnerRow> = db
+        .fetch("owner", async || {
+            sqlx::QueryBuilder::new(format!(
+                "SELECT {OWNER_COLUMNS} FROM node_field_data n {joins} WHERE n.nid = ",
+                joins = owner_joins("n")
+            ))
+            .push_bind(node_nid)
+            .push(" LIMIT 1")
+            .build_query_as::<OwnerRow>()
+            .fetch_optional(db.pool())
+            .await
+        })
+        .await?;
+
+    Ok(row.and_then(Into::into))
+}
@@ -0,0 +1,79 @@
+//! Progress reporting for the bulk exports.
+//!
+//! The largest pulls (100K+ users) take minutes, so the `*_with_progress`
+//! variants stream their rows and call back every [`REPORT_EVERY`] rows and
+//! once more when done. Callers render the events however they like; the
+//! CLI shows a spinner.
+
+use futures::{Stream, TryStreamExt};
+use std::time::{Duration, Instant};
+
+/// Rows between two progress events
+pub const REPORT_EVERY: usize = 1000;
+
+/// Progress of one bulk fetch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Entity being fetched, e.g. `"users"`
+    pub entity: &'static str,
+    /// Rows received so far
+    pub rows: usize,
+    /// Time since the query started
+    pub elapsed: Duration,
+    /// Whether this is the final event for the fetch
+    pub done: bool,
+}
+
+/// Callback receiving [`ProgressEvent`]s
+pub type Progress<'a> = Option<&'a (dyn Fn(ProgressEvent) + Sync)>;
+
+/// Collect `rows`, reporting to `progress` along the way
+pub(crate) async fn collect<T>(
+    entity: &'static str,
+    rows: impl Stream<Item = sqlx::Result<T>>,
+    progress: Progress<'_>,
+) -> sqlx::Result<Vec<T>> {
+    let started = Instant::now();
+    let report = |rows: usize, done: bool| {
+        if let Some(progress) = progress {
+            progress(ProgressEvent {
+                entity,
+                rows,
+                elapsed: started.elapsed(),
+                done,
+            });
+        }
+    };
+
+    let mut rows = std::pin::pin!(rows);
+    let mut collected = Vec::new();
+    while let Some(row) = rows.try_next().await? {
+        collected.push(row);
+        if collected.len() % REPORT_EVERY == 0 {
+            report(collected.len(), false);
+        }
+    }
+    report(collected.len(), true);
+    Ok(collected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_collect_reports_progress() {
+        let events = Mutex::new(vec![]);
+        let record = |event: ProgressEvent| events.lock().unwrap().push((event.rows, event.done));
+        let rows = futures::stream::iter((0..2500).map(Ok));
+
+        let collected = collect("users", rows, Some(&record)).await.unwrap();
+
+        assert_eq!(collected.len(), 2500);
+        assert_eq!(
+            events.into_inner().unwrap(),
+            vec![(1000, false), (2000, false), (2500, true)]
+        );
+    }
+}
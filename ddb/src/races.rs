@@ -1,6 +1,4 @@
-use crate::{Error, Result};
-use futures::TryFutureExt;
-use sqlx::MySqlPool;
+use crate::{Db, Result};
 
 /// Race taxonomy term from Drupal (vocabulary: ssp_race)
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
@@ -11,16 +9,18 @@ pub struct Race {
 }
 
 /// Fetch all race taxonomy terms from Drupal
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Race>> {
-    sqlx::query_as::<_, Race>(
-        r#"
+pub async fn all(db: &Db) -> Result<Vec<Race>> {
+    db.fetch("races", async || {
+        sqlx::query_as::<_, Race>(
+            r#"
         SELECT tid AS uid, name
         FROM taxonomy_term_field_data
         WHERE vid = 'ssp_race'
         ORDER BY tid
         "#,
-    )
-    .fetch_all(pool)
-    .map_err(Error::from)
+        )
+        .fetch_all(db.pool())
+        .await
+    })
     .await
 }
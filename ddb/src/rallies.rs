@@ -1,34 +1,120 @@
-use crate::{Error, Result};
-use chrono::NaiveDate;
-use futures::TryFutureExt;
-use sqlx::MySqlPool;
+use crate::{Db, Result};
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use std::collections::BTreeMap;
 
 /// International rally from Drupal
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct InternationalRally {
     pub uid: u64,
     pub title: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub location: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub start_date: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub early_registration_date: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub registration_end_date: Option<NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Prices parsed from the `*_raw` fields, see [`parse_price`]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub adult_price_cents: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub youth_price_cents: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub child_price_cents: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub campsite_price_cents: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub lifetime_member_discount_cents: Option<i32>,
+    /// Price fields as stored; usually decimals, but text such as `"$149"`
+    /// or `"149.00 "` also occurs
+    #[serde(skip)]
+    pub adult_price_raw: Option<String>,
+    #[serde(skip)]
+    pub youth_price_raw: Option<String>,
+    #[serde(skip)]
+    pub child_price_raw: Option<String>,
+    #[serde(skip)]
+    pub campsite_price_raw: Option<String>,
+    #[serde(skip)]
+    pub lifetime_member_discount_raw: Option<String>,
     pub status: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Rally year parsed from [`Self::year_raw`], see [`parse_year`]
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub year: Option<i16>,
+    /// `field_year` as stored; free text such as `"2025 "` or `"TBD"`
+    #[serde(skip)]
+    pub year_raw: Option<String>,
+}
+
+/// Amount in US cents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct Money(pub i32);
+
+impl Money {
+    pub fn cents(self) -> i32 {
+        self.0
+    }
+}
+
+/// Who a rally fee is being calculated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendeeKind {
+    Adult,
+    /// Adult who holds a lifetime membership
+    LifetimeMember,
+    Youth,
+    Child,
+}
+
+impl InternationalRally {
+    /// Slug id for URLs, e.g. `2025-international-rally-12345`
+    pub fn stable_id(&self) -> String {
+        let year = self
+            .year
+            .map(i32::from)
+            .or_else(|| self.start_date.map(|start| start.year()));
+        crate::slug::stable_id(year, &self.title, self.uid)
+    }
+
+    /// Fee for one attendee registering on `as_of`.
+    ///
+    /// Drupal stores a single price per attendee kind; the early registration
+    /// date only governs the lifetime-member discount:
+    /// - Registrations after `registration_end_date` are closed (`None`).
+    /// - Lifetime members pay the adult price, less
+    ///   `lifetime_member_discount_cents` when registering on or before
+    ///   `early_registration_date` (or at any time if no cutoff is set).
+    /// - The discount is the only reduction, so nothing stacks; it never takes
+    ///   the fee below zero.
+    /// - Returns `None` when the rally has no price for the attendee kind.
+    pub fn price_for(&self, attendee: AttendeeKind, as_of: NaiveDate) -> Option<Money> {
+        if self.registration_end_date.is_some_and(|end| as_of > end) {
+            return None;
+        }
+        let price = match attendee {
+            AttendeeKind::Adult | AttendeeKind::LifetimeMember => self.adult_price_cents,
+            AttendeeKind::Youth => self.youth_price_cents,
+            AttendeeKind::Child => self.child_price_cents,
+        }?;
+        let early = self
+            .early_registration_date
+            .is_none_or(|cutoff| as_of <= cutoff);
+        let discount = match attendee {
+            AttendeeKind::LifetimeMember if early => {
+                self.lifetime_member_discount_cents.unwrap_or(0)
+            }
+            _ => 0,
+        };
+        Some(Money((price - discount).max(0)))
+    }
 }
 
 /// Rally registration from Drupal
@@ -39,9 +125,9 @@ pub struct RallyRegistration {
     pub user_uid: u64,
     pub partner_attending: bool,
     pub first_time_attendee: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub amount_paid_cents: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub amount_due_cents: Option<i32>,
     pub created: i64,
 }
@@ -54,13 +140,13 @@ const FETCH_RALLIES_QUERY: &str = r#"
         CAST(sd.field_start_date_value AS DATE) AS start_date,
         CAST(erd.field_early_registration_date_value AS DATE) AS early_registration_date,
         CAST(red.field_registration_end_date_value AS DATE) AS registration_end_date,
-        CAST(ap.field_adult_price_value * 100 AS SIGNED) AS adult_price_cents,
-        CAST(yp.field_youth_price_value * 100 AS SIGNED) AS youth_price_cents,
-        CAST(cp.field_child_price_value * 100 AS SIGNED) AS child_price_cents,
-        CAST(csp.field_campsite_price_value * 100 AS SIGNED) AS campsite_price_cents,
-        CAST(lmd.field_lifetime_member_discount_value * 100 AS SIGNED) AS lifetime_member_discount_cents,
+        CAST(ap.field_adult_price_value AS CHAR) AS adult_price_raw,
+        CAST(yp.field_youth_price_value AS CHAR) AS youth_price_raw,
+        CAST(cp.field_child_price_value AS CHAR) AS child_price_raw,
+        CAST(csp.field_campsite_price_value AS CHAR) AS campsite_price_raw,
+        CAST(lmd.field_lifetime_member_discount_value AS CHAR) AS lifetime_member_discount_raw,
         nd.status,
-        CAST(y.field_year_value AS SIGNED) AS year
+        CAST(y.field_year_value AS CHAR) AS year_raw
     FROM node_field_data nd
     LEFT JOIN node__field_location loc ON loc.entity_id = nd.nid AND loc.deleted = 0
     LEFT JOIN node__field_start_date sd ON sd.entity_id = nd.nid AND sd.deleted = 0
@@ -76,11 +162,156 @@ const FETCH_RALLIES_QUERY: &str = r#"
 "#;
 
 /// Fetch all international rallies from Drupal
-pub async fn all_rallies(pool: &MySqlPool) -> Result<Vec<InternationalRally>> {
-    sqlx::query_as::<_, InternationalRally>(FETCH_RALLIES_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
-        .await
+pub async fn all_rallies(db: &Db) -> Result<Vec<InternationalRally>> {
+    let mut rallies = db
+        .fetch("rallies", async || {
+            sqlx::query_as::<_, InternationalRally>(FETCH_RALLIES_QUERY)
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+    parse_raw_fields(&mut rallies);
+    Ok(rallies)
+}
+
+/// Fetch rallies that are current or still to come on `as_of`, ordered by
+/// start date.
+///
+/// A rally is included when it starts on or after `as_of`, or when its
+/// registration is still open. Rallies without a start date are excluded.
+pub async fn upcoming(db: &Db, as_of: NaiveDate) -> Result<Vec<InternationalRally>> {
+    let query = format!(
+        "{FETCH_RALLIES_QUERY} \
+         AND sd.field_start_date_value IS NOT NULL \
+         AND (CAST(sd.field_start_date_value AS DATE) >= ? \
+              OR CAST(red.field_registration_end_date_value AS DATE) >= ?) \
+         ORDER BY start_date, nd.nid"
+    );
+    let mut rallies = db
+        .fetch("upcoming rallies", async || {
+            sqlx::query_as::<_, InternationalRally>(&query)
+                .bind(as_of)
+                .bind(as_of)
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+    parse_raw_fields(&mut rallies);
+    Ok(rallies)
+}
+
+/// Fetch one international rally by node ID
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<InternationalRally>> {
+    let mut rally = db
+        .fetch("rallies", async || {
+            sqlx::QueryBuilder::new(FETCH_RALLIES_QUERY)
+                .push(" AND nd.nid = ")
+                .push_bind(uid)
+                .build_query_as::<InternationalRally>()
+                .fetch_optional(db.pool())
+                .await
+        })
+        .await?;
+    parse_raw_fields(rally.as_mut_slice());
+    Ok(rally)
+}
+
+/// Fill in [`InternationalRally::year`] and the prices from the raw field
+/// text, logging the rallies whose values can't be parsed
+fn parse_raw_fields(rallies: &mut [InternationalRally]) {
+    for rally in rallies {
+        let prices = [
+            (
+                "adult price",
+                &rally.adult_price_raw,
+                &mut rally.adult_price_cents,
+            ),
+            (
+                "youth price",
+                &rally.youth_price_raw,
+                &mut rally.youth_price_cents,
+            ),
+            (
+                "child price",
+                &rally.child_price_raw,
+                &mut rally.child_price_cents,
+            ),
+            (
+                "campsite price",
+                &rally.campsite_price_raw,
+                &mut rally.campsite_price_cents,
+            ),
+            (
+                "lifetime member discount",
+                &rally.lifetime_member_discount_raw,
+                &mut rally.lifetime_member_discount_cents,
+            ),
+        ];
+        for (field, raw, cents) in prices {
+            let Some(raw) = raw.as_deref().filter(|raw| !raw.trim().is_empty()) else {
+                continue;
+            };
+            *cents = parse_price(raw);
+            if cents.is_none() {
+                log::warn!("rally {}: unparseable {field} {raw:?}", rally.uid);
+            }
+        }
+
+        let Some(raw) = rally
+            .year_raw
+            .as_deref()
+            .filter(|raw| !raw.trim().is_empty())
+        else {
+            continue;
+        };
+        rally.year = parse_year(raw);
+        if rally.year.is_none() {
+            log::warn!("rally {}: unparseable year {raw:?}", rally.uid);
+        }
+    }
+}
+
+/// Parse a free-text rally year: surrounding whitespace is ignored and the
+/// leading digits are taken (`"2025 "` and `"2025 (tentative)"` are 2025).
+/// Anything without a plausible four digit year, like `"TBD"`, is `None`.
+pub fn parse_year(raw: &str) -> Option<i16> {
+    let raw = raw.trim();
+    let digits = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(raw, |end| &raw[..end]);
+    digits
+        .parse()
+        .ok()
+        .filter(|year| (1900..=2999).contains(year))
+}
+
+/// Parse a price as typed into a text field into cents: a leading `$`,
+/// thousands separators and whitespace are ignored, and up to two decimals
+/// are allowed (`"$1,149.5"` is 114950). Anything else, including negative
+/// amounts, is `None`.
+pub fn parse_price(raw: &str) -> Option<i32> {
+    let cleaned: String = raw
+        .trim()
+        .trim_start_matches('$')
+        .chars()
+        .filter(|c| *c != ',' && !c.is_whitespace())
+        .collect();
+    let (dollars, fraction) = cleaned.split_once('.').unwrap_or((&cleaned, ""));
+    let all_digits = |text: &str| text.bytes().all(|b| b.is_ascii_digit());
+    if dollars.is_empty() && fraction.is_empty()
+        || !all_digits(dollars)
+        || !all_digits(fraction)
+        || fraction.len() > 2
+    {
+        return None;
+    }
+    let dollars: i32 = if dollars.is_empty() {
+        0
+    } else {
+        dollars.parse().ok()?
+    };
+    let cents: i32 = format!("{fraction:0<2}").parse().ok()?;
+    dollars.checked_mul(100)?.checked_add(cents)
 }
 
 const FETCH_REGISTRATIONS_QUERY: &str = r#"
@@ -104,9 +335,449 @@ const FETCH_REGISTRATIONS_QUERY: &str = r#"
 "#;
 
 /// Fetch all rally registrations from Drupal
-pub async fn all_registrations(pool: &MySqlPool) -> Result<Vec<RallyRegistration>> {
-    sqlx::query_as::<_, RallyRegistration>(FETCH_REGISTRATIONS_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
+pub async fn all_registrations(db: &Db) -> Result<Vec<RallyRegistration>> {
+    db.fetch("rally registrations", async || {
+        sqlx::query_as::<_, RallyRegistration>(FETCH_REGISTRATIONS_QUERY)
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Fetch the rally registrations of one user, oldest first
+pub async fn registrations_by_user(db: &Db, user_uid: u64) -> Result<Vec<RallyRegistration>> {
+    db.fetch("rally registrations", async || {
+        sqlx::QueryBuilder::new(FETCH_REGISTRATIONS_QUERY)
+            .push(" AND fur.field_user_registered_target_id = ")
+            .push_bind(user_uid)
+            .push(" ORDER BY nd.created, nd.nid")
+            .build_query_as::<RallyRegistration>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Registration desk roster entry: a registration joined to its user
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct RallyAttendee {
+    pub registration_uid: u64,
+    pub user_uid: u64,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_empty")]
+    #[sqlx(flatten, try_from = "crate::members::Brns")]
+    pub brns: Vec<String>,
+    /// Home club
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_number: Option<i64>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub club_name: Option<String>,
+    /// Second attendee on the registration
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub partner_first_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub partner_last_name: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub amount_paid_cents: Option<i32>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub amount_due_cents: Option<i32>,
+    /// Amount still owed: `amount_due` (the registration total) less
+    /// `amount_paid`, never negative
+    pub balance_due_cents: i32,
+}
+
+/// Fetch the attendee roster for a rally, ordered by last name.
+///
+/// Joins each registration to the registered user's name, BRNs and home club,
+/// plus the second attendee's name from the registration itself.
+pub async fn roster(db: &Db, rally_uid: u64) -> Result<Vec<RallyAttendee>> {
+    db.fetch("rally roster", async || {
+        sqlx::query_as::<_, RallyAttendee>(
+            r#"
+            SELECT
+                nd.nid AS registration_uid,
+                fur.field_user_registered_target_id AS user_uid,
+                ufn.field_first_name_value AS first_name,
+                uln.field_last_name_value AS last_name,
+                brns.brns_values AS brns,
+                CAST(cn.field_club_number_value AS SIGNED) AS club_number,
+                club.title AS club_name,
+                a2fn.field_attendee_2_first_name_value AS partner_first_name,
+                a2ln.field_attendee_2_last_name_value AS partner_last_name,
+                CAST(fap.field_amount_paid_value * 100 AS SIGNED) AS amount_paid_cents,
+                CAST(fad.field_amount_due_value * 100 AS SIGNED) AS amount_due_cents,
+                CAST(GREATEST(
+                    COALESCE(fad.field_amount_due_value, 0) - COALESCE(fap.field_amount_paid_value, 0),
+                    0
+                ) * 100 AS SIGNED) AS balance_due_cents
+            FROM node_field_data nd
+            JOIN node__field_rally fr ON fr.entity_id = nd.nid AND fr.deleted = 0
+            JOIN node__field_user_registered fur ON fur.entity_id = nd.nid AND fur.deleted = 0
+            LEFT JOIN user__field_first_name ufn ON ufn.entity_id = fur.field_user_registered_target_id AND ufn.deleted = 0
+            LEFT JOIN user__field_last_name uln ON uln.entity_id = fur.field_user_registered_target_id AND uln.deleted = 0
+            LEFT JOIN v_brns brns ON brns.user_id = fur.field_user_registered_target_id
+            LEFT JOIN user__field_home_club uhc ON uhc.entity_id = fur.field_user_registered_target_id AND uhc.deleted = 0
+            LEFT JOIN paragraph__field_club pc ON pc.entity_id = uhc.field_home_club_target_id AND pc.deleted = 0
+            LEFT JOIN node_field_data club ON club.nid = pc.field_club_target_id
+            LEFT JOIN node__field_club_number cn ON cn.entity_id = club.nid AND cn.deleted = 0
+            LEFT JOIN node__field_attendee_2_first_name a2fn ON a2fn.entity_id = nd.nid AND a2fn.deleted = 0
+            LEFT JOIN node__field_attendee_2_last_name a2ln ON a2ln.entity_id = nd.nid AND a2ln.deleted = 0
+            LEFT JOIN node__field_amount_paid fap ON fap.entity_id = nd.nid AND fap.deleted = 0
+            LEFT JOIN node__field_amount_due fad ON fad.entity_id = nd.nid AND fad.deleted = 0
+            WHERE nd.type = 'rally_registration'
+              AND fr.field_rally_target_id = ?
+            GROUP BY nd.nid
+            ORDER BY last_name, first_name
+            "#,
+        )
+        .bind(rally_uid)
+        .fetch_all(db.pool())
         .await
+    })
+    .await
+}
+
+/// Payment reconciliation worksheet for one rally, see [`financials`]
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct RallyFinancials {
+    pub rally_uid: u64,
+    pub registrations: usize,
+    /// Attendee fees at the rally's prices on each registration date
+    pub expected_cents: i32,
+    /// Sum of `amount_due`, the registration totals
+    pub billed_cents: i32,
+    /// Sum of `amount_paid`
+    pub collected_cents: i32,
+    /// `amount_due` less `amount_paid` per registration (never negative),
+    /// summed; matches the roster's balance due
+    pub outstanding_cents: i32,
+    pub by_attendee: Vec<AttendeeFinancials>,
+}
+
+/// Expected fees for one kind of attendee
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct AttendeeFinancials {
+    pub attendee: AttendeeKind,
+    pub count: usize,
+    pub expected_cents: i32,
+    /// Attendees without a fee: the rally has no price for them, or they
+    /// registered after registration closed. Worth a look by hand.
+    pub unpriced: usize,
+}
+
+/// Registration amounts and who they cover
+#[derive(Debug, sqlx::FromRow)]
+struct FinancialRow {
+    /// Registrant held a lifetime membership when registering
+    lifetime_member: bool,
+    partner_attending: bool,
+    amount_paid_cents: Option<i32>,
+    amount_due_cents: Option<i32>,
+    created: i64,
+}
+
+/// Reconcile a rally's registrations: the fees they should have been charged
+/// against what was billed (`amount_due`) and paid (`amount_paid`).
+///
+/// Registrations record the registrant and whether a second adult attends,
+/// but not youth or children, and not campsites, so those fees are absent
+/// from `expected` while they can be part of `billed`. Payments are stored
+/// per registration, so only the expected fees are broken down by attendee.
+///
+/// `None` when there is no rally `rally_uid`.
+pub async fn financials(db: &Db, rally_uid: u64) -> Result<Option<RallyFinancials>> {
+    let Some(rally) = by_uid(db, rally_uid).await? else {
+        return Ok(None);
+    };
+    let rows = db.fetch("rally financials", async || {
+        sqlx::query_as::<_, FinancialRow>(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT 1
+                    FROM paragraphs_item_field_data p
+                    JOIN paragraph__field_membership_class mc
+                        ON mc.entity_id = p.id AND mc.deleted = 0
+                    JOIN taxonomy_term_field_data ttd ON ttd.tid = mc.field_membership_class_target_id
+                    JOIN paragraph__field_join_date fjd ON fjd.entity_id = p.id AND fjd.deleted = 0
+                    LEFT JOIN paragraph__field_leave_date fld ON fld.entity_id = p.id AND fld.deleted = 0
+                    WHERE p.parent_id = fur.field_user_registered_target_id
+                      AND p.type = 'ssp_international_membership'
+                      AND p.status = 1
+                      AND ttd.name = 'Lifetime'
+                      AND DATE(fjd.field_join_date_value) <= DATE(FROM_UNIXTIME(nd.created))
+                      AND (fld.field_leave_date_value IS NULL
+                           OR DATE(fld.field_leave_date_value) >= DATE(FROM_UNIXTIME(nd.created)))
+                ) AS lifetime_member,
+                a2fn.field_attendee_2_first_name_value IS NOT NULL AS partner_attending,
+                CAST(fap.field_amount_paid_value * 100 AS SIGNED) AS amount_paid_cents,
+                CAST(fad.field_amount_due_value * 100 AS SIGNED) AS amount_due_cents,
+                nd.created
+            FROM node_field_data nd
+            JOIN node__field_rally fr ON fr.entity_id = nd.nid AND fr.deleted = 0
+            JOIN node__field_user_registered fur ON fur.entity_id = nd.nid AND fur.deleted = 0
+            LEFT JOIN node__field_attendee_2_first_name a2fn ON a2fn.entity_id = nd.nid AND a2fn.deleted = 0
+            LEFT JOIN node__field_amount_paid fap ON fap.entity_id = nd.nid AND fap.deleted = 0
+            LEFT JOIN node__field_amount_due fad ON fad.entity_id = nd.nid AND fad.deleted = 0
+            WHERE nd.type = 'rally_registration'
+              AND fr.field_rally_target_id = ?
+            "#,
+        )
+        .bind(rally_uid)
+        .fetch_all(db.pool())
+        .await
+    })
+    .await?;
+    Ok(Some(financials_from(&rally, &rows)))
+}
+
+fn financials_from(rally: &InternationalRally, rows: &[FinancialRow]) -> RallyFinancials {
+    let mut by_attendee: BTreeMap<AttendeeKind, AttendeeFinancials> = BTreeMap::new();
+    let mut financials = RallyFinancials {
+        rally_uid: rally.uid,
+        registrations: rows.len(),
+        expected_cents: 0,
+        billed_cents: 0,
+        collected_cents: 0,
+        outstanding_cents: 0,
+        by_attendee: vec![],
+    };
+    for row in rows {
+        let registered = chrono::DateTime::from_timestamp(row.created, 0)
+            .unwrap_or_default()
+            .date_naive();
+        let registrant = if row.lifetime_member {
+            AttendeeKind::LifetimeMember
+        } else {
+            AttendeeKind::Adult
+        };
+        let partner = row.partner_attending.then_some(AttendeeKind::Adult);
+        for attendee in std::iter::once(registrant).chain(partner) {
+            let totals = by_attendee
+                .entry(attendee)
+                .or_insert_with(|| AttendeeFinancials {
+                    attendee,
+                    count: 0,
+                    expected_cents: 0,
+                    unpriced: 0,
+                });
+            totals.count += 1;
+            match rally.price_for(attendee, registered) {
+                Some(price) => totals.expected_cents += price.cents(),
+                None => totals.unpriced += 1,
+            }
+        }
+        let paid = row.amount_paid_cents.unwrap_or(0);
+        let due = row.amount_due_cents.unwrap_or(0);
+        financials.billed_cents += due;
+        financials.collected_cents += paid;
+        financials.outstanding_cents += (due - paid).max(0);
+    }
+    financials.by_attendee = by_attendee.into_values().collect();
+    financials.expected_cents = financials
+        .by_attendee
+        .iter()
+        .map(|totals| totals.expected_cents)
+        .sum();
+    financials
+}
+
+/// Daily agenda item for a rally (agenda paragraph)
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct AgendaItem {
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub day: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub start_time: Option<NaiveTime>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub title: Option<String>,
+    /// HTML description
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub location: Option<String>,
+}
+
+/// Fetch the agenda for a rally, ordered by day and start time.
+///
+/// Agenda items are stored in `field_agenda` paragraphs on the rally node.
+pub async fn agenda(db: &Db, rally_uid: u64) -> Result<Vec<AgendaItem>> {
+    db.fetch("rally agenda", async || {
+        sqlx::query_as::<_, AgendaItem>(
+            r#"
+        SELECT
+            CAST(ad.field_agenda_date_value AS DATE) AS day,
+            CAST(ast.field_start_time_value AS TIME) AS start_time,
+            at.field_title_value AS title,
+            adesc.field_description_value AS description,
+            aloc.field_location_value AS location
+        FROM node__field_agenda fa
+        LEFT JOIN paragraph__field_agenda_date ad
+            ON ad.entity_id = fa.field_agenda_target_id AND ad.deleted = 0
+        LEFT JOIN paragraph__field_start_time ast
+            ON ast.entity_id = fa.field_agenda_target_id AND ast.deleted = 0
+        LEFT JOIN paragraph__field_title at
+            ON at.entity_id = fa.field_agenda_target_id AND at.deleted = 0
+        LEFT JOIN paragraph__field_description adesc
+            ON adesc.entity_id = fa.field_agenda_target_id AND adesc.deleted = 0
+        LEFT JOIN paragraph__field_location aloc
+            ON aloc.entity_id = fa.field_agenda_target_id AND aloc.deleted = 0
+        WHERE fa.entity_id = ?
+          AND fa.deleted = 0
+        ORDER BY day, start_time, fa.delta
+        "#,
+        )
+        .bind(rally_uid)
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn rally() -> InternationalRally {
+        InternationalRally {
+            uid: 1,
+            title: "International Rally".to_string(),
+            location: None,
+            start_date: Some(date("2025-07-12")),
+            early_registration_date: Some(date("2025-03-31")),
+            registration_end_date: Some(date("2025-06-30")),
+            adult_price_cents: Some(30_000),
+            youth_price_cents: Some(10_000),
+            child_price_cents: None,
+            campsite_price_cents: Some(20_000),
+            lifetime_member_discount_cents: Some(5_000),
+            adult_price_raw: Some("300.00".to_string()),
+            youth_price_raw: Some("100.00".to_string()),
+            child_price_raw: None,
+            campsite_price_raw: Some("200.00".to_string()),
+            lifetime_member_discount_raw: Some("50.00".to_string()),
+            status: true,
+            year: Some(2025),
+            year_raw: Some("2025".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year("2025"), Some(2025));
+        assert_eq!(parse_year(" 2025 "), Some(2025));
+        assert_eq!(parse_year("2025 (tentative)"), Some(2025));
+        assert_eq!(parse_year("TBD"), None);
+        assert_eq!(parse_year("25"), None);
+        assert_eq!(parse_year(""), None);
+    }
+
+    #[test]
+    fn test_parse_price() {
+        assert_eq!(parse_price("149"), Some(14_900));
+        assert_eq!(parse_price("$149"), Some(14_900));
+        assert_eq!(parse_price("149.00 "), Some(14_900));
+        assert_eq!(parse_price(" $ 1,149.5"), Some(114_950));
+        assert_eq!(parse_price(".75"), Some(75));
+        assert_eq!(parse_price("0"), Some(0));
+        assert_eq!(parse_price("TBD"), None);
+        assert_eq!(parse_price("149.999"), None);
+        assert_eq!(parse_price("-10"), None);
+        assert_eq!(parse_price("$"), None);
+        assert_eq!(parse_price("1.2.3"), None);
+    }
+
+    #[test]
+    fn test_price_for() {
+        let rally = rally();
+        let early = date("2025-03-31");
+        let late = date("2025-04-01");
+
+        assert_eq!(
+            rally.price_for(AttendeeKind::Adult, early),
+            Some(Money(30_000))
+        );
+        assert_eq!(
+            rally.price_for(AttendeeKind::LifetimeMember, early),
+            Some(Money(25_000))
+        );
+        assert_eq!(
+            rally.price_for(AttendeeKind::LifetimeMember, late),
+            Some(Money(30_000))
+        );
+        assert_eq!(
+            rally.price_for(AttendeeKind::Youth, late),
+            Some(Money(10_000))
+        );
+        assert_eq!(rally.price_for(AttendeeKind::Child, early), None);
+        assert_eq!(
+            rally.price_for(AttendeeKind::Adult, date("2025-07-01")),
+            None
+        );
+    }
+
+    fn registration(
+        lifetime_member: bool,
+        partner: bool,
+        created: &str,
+        paid: i32,
+        due: i32,
+    ) -> FinancialRow {
+        let created = date(created).and_hms_opt(12, 0, 0).unwrap().and_utc();
+        FinancialRow {
+            lifetime_member,
+            partner_attending: partner,
+            amount_paid_cents: Some(paid),
+            amount_due_cents: Some(due),
+            created: created.timestamp(),
+        }
+    }
+
+    #[test]
+    fn test_financials_from() {
+        let rows = [
+            // Lifetime member with partner, early: 25,000 + 30,000
+            registration(true, true, "2025-03-01", 55_000, 55_000),
+            // Adult alone, late, partly paid
+            registration(false, false, "2025-05-01", 10_000, 30_000),
+            // Registered after registration closed
+            registration(false, false, "2025-07-01", 0, 30_000),
+        ];
+        let financials = financials_from(&rally(), &rows);
+
+        assert_eq!(financials.registrations, 3);
+        assert_eq!(financials.expected_cents, 85_000);
+        assert_eq!(financials.billed_cents, 115_000);
+        assert_eq!(financials.collected_cents, 65_000);
+        assert_eq!(financials.outstanding_cents, 50_000);
+        assert_eq!(
+            financials.by_attendee,
+            vec![
+                AttendeeFinancials {
+                    attendee: AttendeeKind::Adult,
+                    count: 3,
+                    expected_cents: 60_000,
+                    unpriced: 1,
+                },
+                AttendeeFinancials {
+                    attendee: AttendeeKind::LifetimeMember,
+                    count: 1,
+                    expected_cents: 25_000,
+                    unpriced: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stable_id() {
+        assert_eq!(rally().stable_id(), "2025-international-rally-1");
+    }
 }
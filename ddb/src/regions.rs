@@ -1,31 +1,53 @@
-use crate::{Error, Result};
-use futures::TryFutureExt;
-use sqlx::{MySql, MySqlPool};
+use crate::{Db, Result};
+use sqlx::MySql;
 
-pub async fn all(pool: &MySqlPool) -> Result<Vec<Region>> {
-    sqlx::query_as::<_, Region>(FETCH_REGIONS_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
+pub async fn all(db: &Db) -> Result<Vec<Region>> {
+    db.fetch("regions", async || {
+        sqlx::query_as::<_, Region>(FETCH_REGIONS_QUERY)
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Number of regions [`all`] would return, without fetching them
+pub async fn count(db: &Db) -> Result<u64> {
+    db.fetch("regions", async || {
+        sqlx::query_scalar(
+            "SELECT CAST(COUNT(*) AS UNSIGNED) FROM node__field_region_number region \
+             INNER JOIN node_field_data fields ON fields.nid = region.entity_id",
+        )
+        .fetch_one(db.pool())
         .await
+    })
+    .await
 }
 
-pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<Region>> {
-    let region = fetch_regions_query()
-        .push("where region.entity_id = ")
-        .push_bind(uid)
-        .build_query_as::<Region>()
-        .fetch_optional(pool)
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<Region>> {
+    let region = db
+        .fetch("regions", async || {
+            fetch_regions_query()
+                .push("where region.entity_id = ")
+                .push_bind(uid)
+                .build_query_as::<Region>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(region)
 }
 
-pub async fn by_number(pool: &MySqlPool, number: i32) -> Result<Option<Region>> {
-    let region = fetch_regions_query()
-        .push("where region.field_region_number_value = ")
-        .push_bind(number)
-        .build_query_as::<Region>()
-        .fetch_optional(pool)
+pub async fn by_number(db: &Db, number: i32) -> Result<Option<Region>> {
+    let region = db
+        .fetch("regions", async || {
+            fetch_regions_query()
+                .push("where region.field_region_number_value = ")
+                .push_bind(number)
+                .build_query_as::<Region>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(region)
@@ -48,9 +70,9 @@ fn fetch_regions_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct Region {
     pub uid: u64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub number: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub name: Option<String>,
     pub active: bool,
 }
@@ -0,0 +1,109 @@
+//! Retry of bulk queries that lose a lock to the live site's writes.
+//!
+//! The big joins run against the production database while Drupal is
+//! writing, so MySQL occasionally picks them as a deadlock victim (1213) or
+//! gives up waiting for a row lock (1205). Every export only reads, so these
+//! are safe to re-run, as are failures to get a connection: a pool checkout
+//! timing out, or the connection dropping mid-query (I/O errors). Any other
+//! error, such as a syntax error or a query killed for running too long, is
+//! returned on the first attempt.
+//!
+//! How often and how patiently to retry is the [`RetryPolicy`] of the
+//! [`crate::Db`] the query runs on (the CLI's `--retries` and
+//! `--retry-delay`).
+
+use crate::{Error, Result};
+use sqlx::mysql::MySqlDatabaseError;
+use std::time::Duration;
+
+/// `ER_LOCK_DEADLOCK` and `ER_LOCK_WAIT_TIMEOUT`
+const RETRYABLE_ERRORS: [u16; 2] = [1213, 1205];
+
+/// How bulk queries are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 disables retries
+    pub attempts: u32,
+    /// Wait before the first retry, doubled for each further one
+    pub base_delay: Duration,
+    /// Longest wait between two attempts
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    const DEFAULT: Self = Self {
+        attempts: 4,
+        base_delay: Duration::from_millis(250),
+        max_delay: Duration::from_secs(8),
+    };
+
+    /// Wait after failed attempt number `attempt` (starting at 1)
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Run `query`, re-running it after a backoff when it fails with one of the
+/// retryable errors above, as `policy` allows. Failures are tagged with
+/// `entity` as in [`Error::query`].
+pub(crate) async fn retry<T, F: Future<Output = sqlx::Result<T>>>(
+    policy: &RetryPolicy,
+    entity: &'static str,
+    mut query: impl FnMut() -> F,
+) -> Result<T> {
+    let mut attempt = 1;
+    loop {
+        match query().await {
+            Ok(rows) => return Ok(rows),
+            Err(err) if attempt < policy.attempts && is_retryable(&err) => {
+                let delay = policy.delay(attempt);
+                log::warn!("{entity} query attempt {attempt} failed, retrying in {delay:?}: {err}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(Error::query(entity)(err)),
+        }
+    }
+}
+
+fn is_retryable(err: &sqlx::Error) -> bool {
+    match err {
+        sqlx::Error::PoolTimedOut | sqlx::Error::Io(_) => true,
+        _ => err
+            .as_database_error()
+            .and_then(|err| err.try_downcast_ref::<MySqlDatabaseError>())
+            .is_some_and(|err| RETRYABLE_ERRORS.contains(&err.number())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy {
+            attempts: 6,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(1),
+        };
+        let delays: Vec<u128> = (1..=4)
+            .map(|attempt| policy.delay(attempt).as_millis())
+            .collect();
+        assert_eq!(delays, vec![250, 500, 1000, 1000]);
+        assert_eq!(RetryPolicy::default().delay(40), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(is_retryable(&sqlx::Error::PoolTimedOut));
+        assert!(!is_retryable(&sqlx::Error::RowNotFound));
+    }
+}
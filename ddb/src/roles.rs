@@ -1,8 +1,6 @@
 //! User roles and microsite admin assignments from Drupal.
 
-use crate::{Error, Result};
-use futures::TryFutureExt;
-use sqlx::MySqlPool;
+use crate::{Db, Result};
 
 /// User role assignment from Drupal's user__roles table
 #[derive(Debug, sqlx::FromRow, serde::Serialize)]
@@ -23,16 +21,37 @@ pub struct MicrositeAdmin {
 }
 
 /// Fetch all user role assignments from Drupal
-pub async fn all(pool: &MySqlPool) -> Result<Vec<UserRole>> {
-    sqlx::query_as::<_, UserRole>(
-        r#"
+pub async fn all(db: &Db) -> Result<Vec<UserRole>> {
+    db.fetch("roles", async || {
+        sqlx::query_as::<_, UserRole>(
+            r#"
         SELECT entity_id AS user_uid, roles_target_id AS role
         FROM user__roles
         WHERE deleted = 0
         "#,
-    )
-    .fetch_all(pool)
-    .map_err(Error::from)
+        )
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
+
+/// Fetch the role names assigned to one user
+pub async fn by_user(db: &Db, user_uid: u64) -> Result<Vec<String>> {
+    db.fetch("roles", async || {
+        sqlx::query_scalar(
+            r#"
+        SELECT roles_target_id
+        FROM user__roles
+        WHERE deleted = 0
+          AND entity_id = ?
+        ORDER BY delta
+        "#,
+        )
+        .bind(user_uid)
+        .fetch_all(db.pool())
+        .await
+    })
     .await
 }
 
@@ -40,9 +59,10 @@ pub async fn all(pool: &MySqlPool) -> Result<Vec<UserRole>> {
 ///
 /// This query joins via `field_main_site_club` which links ssp_club/ssp_region
 /// nodes to their corresponding microsite_homepage (no title matching needed).
-pub async fn microsite_admins(pool: &MySqlPool) -> Result<Vec<MicrositeAdmin>> {
-    sqlx::query_as::<_, MicrositeAdmin>(
-        r#"
+pub async fn microsite_admins(db: &Db) -> Result<Vec<MicrositeAdmin>> {
+    db.fetch("microsite admins", async || {
+        sqlx::query_as::<_, MicrositeAdmin>(
+            r#"
         SELECT
             uf.entity_id AS user_uid,
             COALESCE(region_link.entity_id, club_link.entity_id) AS entity_uid,
@@ -59,8 +79,9 @@ pub async fn microsite_admins(pool: &MySqlPool) -> Result<Vec<MicrositeAdmin>> {
         WHERE uf.deleted = 0
           AND (club_link.entity_id IS NOT NULL OR region_link.entity_id IS NOT NULL)
         "#,
-    )
-    .fetch_all(pool)
-    .map_err(Error::from)
+        )
+        .fetch_all(db.pool())
+        .await
+    })
     .await
 }
@@ -0,0 +1,176 @@
+//! Mutation inputs for seeding the new site through its GraphQL API.
+//!
+//! Targets schema version [`SCHEMA_VERSION`] of the new site:
+//!
+//! ```graphql
+//! createUser(input: CreateUserInput!): User
+//! createEvent(input: CreateEventInput!): Event
+//! ```
+//!
+//! Field names and enum values follow the schema (camelCase fields,
+//! SCREAMING_CASE enums). Drupal IDs are passed as `legacyId` so the new
+//! site can link records and re-runs can upsert. Update the structs here and
+//! bump [`SCHEMA_VERSION`] when the schema changes.
+
+use crate::{events::Event, users::User};
+use chrono::{DateTime, Utc};
+
+/// Version of the new site's GraphQL schema these inputs match
+pub const SCHEMA_VERSION: &str = "1";
+
+/// Every mutation input of a seed run, keyed by mutation name
+#[derive(Debug, serde::Serialize)]
+pub struct SeedBatch<'a> {
+    #[serde(rename = "schemaVersion")]
+    pub schema_version: &'static str,
+    #[serde(rename = "createUser")]
+    pub create_user: Vec<CreateUserInput<'a>>,
+    #[serde(rename = "createEvent")]
+    pub create_event: Vec<CreateEventInput<'a>>,
+}
+
+/// Build the seed batch for `users` and `events`
+pub fn batch<'a>(users: &'a [User], events: &'a [Event]) -> SeedBatch<'a> {
+    SeedBatch {
+        schema_version: SCHEMA_VERSION,
+        create_user: users.iter().map(CreateUserInput::from).collect(),
+        create_event: events.iter().map(CreateEventInput::from).collect(),
+    }
+}
+
+/// `CreateUserInput`
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUserInput<'a> {
+    pub legacy_id: u64,
+    pub email: &'a str,
+    pub first_name: Option<&'a str>,
+    pub last_name: Option<&'a str>,
+    /// Blocked Drupal accounts are seeded but can't sign in
+    pub active: bool,
+}
+
+impl<'a> From<&'a User> for CreateUserInput<'a> {
+    fn from(user: &'a User) -> Self {
+        Self {
+            legacy_id: user.uid,
+            email: &user.email,
+            first_name: user.first_name.as_deref(),
+            last_name: user.last_name.as_deref(),
+            active: user.active,
+        }
+    }
+}
+
+/// `CreateEventInput`
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEventInput<'a> {
+    pub legacy_id: u64,
+    pub title: &'a str,
+    /// `DateTime` scalars are RFC 3339 in UTC
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// IANA zone for displaying the times
+    pub time_zone: &'static str,
+    pub summary: Option<&'a str>,
+    pub body_html: Option<&'a str>,
+    pub location: EventLocationInput<'a>,
+    pub website_url: Option<&'a str>,
+    pub registration_url: Option<&'a str>,
+    pub tags: &'a [String],
+    /// `None` for international events
+    pub owner: Option<OwnerInput>,
+    pub published: bool,
+}
+
+/// `EventLocationInput`
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLocationInput<'a> {
+    pub name: Option<&'a str>,
+    pub address: Option<&'a str>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// `OwnerInput`: the club or region an event belongs to
+#[derive(Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnerInput {
+    pub kind: OwnerKind,
+    pub legacy_id: u64,
+}
+
+/// `OwnerKind`
+#[derive(Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OwnerKind {
+    Club,
+    Region,
+}
+
+impl<'a> From<&'a Event> for CreateEventInput<'a> {
+    fn from(event: &'a Event) -> Self {
+        let owner = event.owner().and_then(|owner| {
+            let kind = if owner.is_club() {
+                OwnerKind::Club
+            } else if owner.is_region() {
+                OwnerKind::Region
+            } else {
+                return None;
+            };
+            Some(OwnerInput {
+                kind,
+                legacy_id: owner.uid,
+            })
+        });
+        Self {
+            legacy_id: event.uid,
+            title: &event.title,
+            starts_at: event.start_utc(),
+            ends_at: event.end_utc(),
+            time_zone: event.zone().name(),
+            summary: event.description.as_deref(),
+            body_html: event.body.as_deref(),
+            location: EventLocationInput {
+                name: event.location_name.as_deref(),
+                address: event.address.as_deref(),
+                latitude: event.latitude,
+                longitude: event.longitude,
+            },
+            website_url: event.website_url.as_deref(),
+            registration_url: event.registration_url.as_deref(),
+            tags: &event.tags,
+            owner,
+            published: event.status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_create_user_input() {
+        let mut user = User::test(42, "pat@example.com");
+        user.first_name = Some("Pat".to_string());
+        let batch = batch(std::slice::from_ref(&user), &[]);
+        assert_eq!(
+            serde_json::to_value(&batch).unwrap(),
+            json!({
+                "schemaVersion": SCHEMA_VERSION,
+                "createUser": [{
+                    "legacyId": 42,
+                    "email": "pat@example.com",
+                    "firstName": "Pat",
+                    "lastName": null,
+                    "active": true
+                }],
+                "createEvent": []
+            })
+        );
+    }
+}
@@ -0,0 +1,94 @@
+//! Serialization switches shared by the exported structs.
+//!
+//! Exported structs skip `None` fields with
+//! `#[serde(skip_serializing_if = "crate::ser::skip_none")]`. Consumers that
+//! validate against a schema can ask for explicit `null`s instead by
+//! serializing inside [`with_explicit_nulls`], so every struct follows the
+//! switch without it being threaded through each of them.
+//!
+//! Empty lists and strings are skipped with [`skip_empty`] and
+//! [`skip_empty_str`]. Inside [`with_all_fields`] nothing is skipped, so a
+//! record serializes with every field its type declares.
+
+use std::cell::Cell;
+
+thread_local! {
+    static EXPLICIT_NULLS: Cell<bool> = const { Cell::new(false) };
+    static ALL_FIELDS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with `None` fields serialized as `null` on this thread instead of
+/// omitted
+pub fn with_explicit_nulls<R>(f: impl FnOnce() -> R) -> R {
+    let previous = EXPLICIT_NULLS.replace(true);
+    let result = f();
+    EXPLICIT_NULLS.set(previous);
+    result
+}
+
+/// Run `f` with no field skipped on this thread, e.g. to list the fields of
+/// a record's type from any one record
+pub fn with_all_fields<R>(f: impl FnOnce() -> R) -> R {
+    let previous = ALL_FIELDS.replace(true);
+    let result = f();
+    ALL_FIELDS.set(previous);
+    result
+}
+
+fn all_fields() -> bool {
+    ALL_FIELDS.get()
+}
+
+/// `skip_serializing_if` predicate: skip `None` unless explicit nulls are on
+pub fn skip_none<T>(value: &Option<T>) -> bool {
+    skip_none_with(EXPLICIT_NULLS.get() || all_fields(), value)
+}
+
+/// `skip_serializing_if` predicate: skip empty lists
+pub fn skip_empty<T>(value: &[T]) -> bool {
+    value.is_empty() && !all_fields()
+}
+
+/// `skip_serializing_if` predicate: skip empty strings
+pub fn skip_empty_str(value: &str) -> bool {
+    value.is_empty() && !all_fields()
+}
+
+fn skip_none_with<T>(explicit_nulls: bool, value: &Option<T>) -> bool {
+    value.is_none() && !explicit_nulls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_none() {
+        assert!(skip_none_with::<u64>(false, &None));
+        assert!(!skip_none_with(false, &Some(1)));
+        assert!(!skip_none_with::<u64>(true, &None));
+        assert!(!skip_none_with(true, &Some(1)));
+    }
+
+    #[test]
+    fn test_with_explicit_nulls() {
+        assert!(skip_none::<u64>(&None));
+        with_explicit_nulls(|| {
+            assert!(!skip_none::<u64>(&None));
+            assert!(skip_empty::<u64>(&[]));
+        });
+        assert!(skip_none::<u64>(&None));
+    }
+
+    #[test]
+    fn test_with_all_fields() {
+        assert!(skip_empty::<u64>(&[]));
+        assert!(skip_empty_str(""));
+        with_all_fields(|| {
+            assert!(!skip_none::<u64>(&None));
+            assert!(!skip_empty::<u64>(&[]));
+            assert!(!skip_empty_str(""));
+        });
+        assert!(skip_empty_str(""));
+    }
+}
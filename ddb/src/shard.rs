@@ -0,0 +1,98 @@
+//! Deterministic partitioning of bulk exports across parallel workers.
+//!
+//! Shard `I/N` holds the rows whose user ID leaves remainder `I` when divided
+//! by `N`, so `N` workers run with `0/N` through `N-1/N` export every row
+//! exactly once between them, and a user is always in the same shard.
+//!
+//! The filter is on `users_field_data.uid`, the primary key. A modulo can't
+//! be looked up in the index, so each worker still walks the key, but that
+//! walk is cheap: the joins that make the exports slow only run for the
+//! shard's own rows.
+
+use sqlx::{MySql, QueryBuilder};
+use std::{fmt, num::NonZeroU32, str::FromStr};
+
+/// One of `count` slices of an export, written `index/count`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// Zero-based, below `count`
+    index: u32,
+    count: NonZeroU32,
+}
+
+impl Shard {
+    /// `None` unless `index < count`
+    pub fn new(index: u32, count: NonZeroU32) -> Option<Self> {
+        (index < count.get()).then_some(Self { index, count })
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn count(&self) -> NonZeroU32 {
+        self.count
+    }
+
+    /// Whether the row of user `uid` falls in this shard
+    pub fn contains(&self, uid: u64) -> bool {
+        uid % u64::from(self.count.get()) == u64::from(self.index)
+    }
+
+    /// Append ` AND {uid_column} % count = index` to `query`
+    pub(crate) fn push_filter(&self, query: &mut QueryBuilder<'_, MySql>, uid_column: &str) {
+        query
+            .push(format!(" AND {uid_column} % "))
+            .push_bind(self.count.get())
+            .push(" = ")
+            .push_bind(self.index);
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid shard {value} (expected I/N with I < N, e.g. 0/4)");
+        let (index, count) = value.split_once('/').ok_or_else(invalid)?;
+        let index = index.trim().parse().map_err(|_| invalid())?;
+        let count = count.trim().parse().map_err(|_| invalid())?;
+        Self::new(index, count).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_from_str() {
+        let shard: Shard = "1/4".parse().unwrap();
+        assert_eq!((shard.index(), shard.count().get()), (1, 4));
+        assert_eq!(shard.to_string(), "1/4");
+        assert!(shard.contains(5) && !shard.contains(4));
+
+        for invalid in ["4/4", "1", "1/0", "-1/4", "a/b"] {
+            assert!(invalid.parse::<Shard>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_shard_push_filter() {
+        let mut query = QueryBuilder::<MySql>::new("SELECT uid FROM users_field_data WHERE 1");
+        "2/8"
+            .parse::<Shard>()
+            .unwrap()
+            .push_filter(&mut query, "users_field_data.uid");
+        assert_eq!(
+            query.sql(),
+            "SELECT uid FROM users_field_data WHERE 1 AND users_field_data.uid % ? = ?"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+//! URL slugs for exported content.
+
+/// Lowercase ASCII slug: runs of anything but ASCII letters and digits become
+/// a single hyphen, with no leading or trailing hyphens.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    if slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// Human-readable but collision-free id: `{year}-{title}-{nid}`.
+///
+/// The trailing nid keeps ids unique when titles repeat; the year is left
+/// out when unknown.
+pub(crate) fn stable_id(year: Option<i32>, title: &str, nid: u64) -> String {
+    let title = slugify(title);
+    [
+        year.map(|year| year.to_string()),
+        Some(title),
+        Some(nid.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("International Rally"), "international-rally");
+        assert_eq!(slugify("  Spring Fling -- Day 1! "), "spring-fling-day-1");
+        assert_eq!(slugify("Café & Crêpes"), "caf-cr-pes");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn test_stable_id() {
+        assert_eq!(
+            stable_id(Some(2025), "International Rally", 12345),
+            "2025-international-rally-12345"
+        );
+        assert_eq!(stable_id(None, "Potluck", 7), "potluck-7");
+        assert_eq!(stable_id(Some(2025), "???", 7), "2025-7");
+    }
+}
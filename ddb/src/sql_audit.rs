@@ -0,0 +1,103 @@
+//! Test-only checks on the SQL text of export queries.
+//!
+//! Structs read with `#[sqlx(flatten)]` from several queries (see
+//! [`crate::users::User`]) need every query to select every field;
+//! [`missing_aliases`] catches a query that was not updated with the struct.
+//!
+//! Drupal keeps field rows of deleted fields (and of fields being purged) in
+//! the same `node__field_*` tables with `deleted = 1`. A join without a
+//! `deleted = 0` guard picks them up next to the live row, which shows up as
+//! duplicated records or a stale value winning, e.g. an old hero image.
+
+/// Field tables joined in `sql` without an `<alias>.deleted = 0` condition.
+///
+/// A field table is any `*__field_*` table or `*__body`. Only the join's own
+/// `ON` clause is checked, up to the next join or `WHERE`.
+pub(crate) fn unguarded_field_joins(sql: &str) -> Vec<String> {
+    const CLAUSE_END: &[&str] = &[
+        "JOIN", "LEFT", "INNER", "WHERE", "ORDER", "GROUP", "--", ")",
+    ];
+
+    let tokens: Vec<&str> = sql.split_whitespace().collect();
+    let mut unguarded = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        if *token != "JOIN" {
+            continue;
+        }
+        let Some(table) = tokens.get(index + 1) else {
+            continue;
+        };
+        if !(table.contains("__field_") || table.ends_with("__body")) {
+            continue;
+        }
+        let alias = match tokens.get(index + 2) {
+            Some(&"AS") => tokens.get(index + 3),
+            alias => alias,
+        };
+        let Some(alias) = alias else {
+            continue;
+        };
+        let clause = tokens[index + 1..]
+            .iter()
+            .take_while(|token| !CLAUSE_END.contains(token))
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !clause.contains(&format!("{alias}.deleted = 0")) {
+            unguarded.push(format!("{table} {alias}"));
+        }
+    }
+    unguarded
+}
+
+/// `columns` that `sql` never selects as `AS <column>`.
+///
+/// Aliases are matched case-insensitively on whitespace-separated tokens, so
+/// `x AS first_name,` counts but a column selected without an alias doesn't.
+pub(crate) fn missing_aliases<'a>(sql: &str, columns: &[&'a str]) -> Vec<&'a str> {
+    let tokens: Vec<&str> = sql.split_whitespace().collect();
+    let aliases: Vec<&str> = tokens
+        .windows(2)
+        .filter(|pair| pair[0].eq_ignore_ascii_case("AS"))
+        .map(|pair| pair[1].trim_end_matches(','))
+        .collect();
+    columns
+        .iter()
+        .copied()
+        .filter(|column| !aliases.contains(column))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_aliases() {
+        let sql = "SELECT u.uid AS uid, u.mail as email,\n NULL AS pass FROM users_field_data u";
+        assert_eq!(
+            missing_aliases(sql, &["uid", "email", "pass", "active"]),
+            vec!["active"]
+        );
+    }
+
+    #[test]
+    fn test_unguarded_field_joins() {
+        let sql = r#"
+            SELECT n.nid, hbi.field_hero_banner_image_target_id
+            FROM node_field_data n
+            LEFT JOIN node__body b ON b.entity_id = n.nid AND b.deleted = 0
+            -- A deleted hero image row would be joined next to the live one
+            LEFT JOIN node__field_hero_banner_image hbi ON hbi.entity_id = n.nid
+            LEFT JOIN media__field_media_image AS mfi
+                ON mfi.entity_id = hbi.field_hero_banner_image_target_id
+                AND mfi.deleted = 0
+            LEFT JOIN file_managed f ON f.fid = mfi.field_media_image_target_id
+            WHERE n.nid = ?
+        "#;
+        assert_eq!(
+            unguarded_field_joins(sql),
+            vec!["node__field_hero_banner_image hbi"]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+//! TLS options for the Drupal database connection.
+//!
+//! Managed databases typically require TLS with a private CA; the connection
+//! URL alone can't carry a CA bundle path, so these are supplied separately
+//! (e.g. `ACI__DDB_SSL_CA` / `ACI__DDB_SSL_MODE`).
+
+use sqlx::mysql::{MySqlConnectOptions, MySqlSslMode};
+use std::{path::PathBuf, str::FromStr};
+
+/// How strictly the connection requires and verifies TLS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SslMode {
+    /// Plain connection
+    Disabled,
+    /// Use TLS if the server supports it (sqlx default)
+    Preferred,
+    /// Require TLS without verifying the server certificate
+    Required,
+    /// Require TLS and verify the server certificate against the CA
+    VerifyCa,
+}
+
+impl FromStr for SslMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().replace('-', "_").as_str() {
+            "disabled" => Ok(Self::Disabled),
+            "preferred" => Ok(Self::Preferred),
+            "required" => Ok(Self::Required),
+            "verify_ca" => Ok(Self::VerifyCa),
+            other => Err(format!(
+                "unexpected ssl mode {other} (expected disabled, preferred, required or verify_ca)"
+            )),
+        }
+    }
+}
+
+impl From<SslMode> for MySqlSslMode {
+    fn from(value: SslMode) -> Self {
+        match value {
+            SslMode::Disabled => Self::Disabled,
+            SslMode::Preferred => Self::Preferred,
+            SslMode::Required => Self::Required,
+            SslMode::VerifyCa => Self::VerifyCa,
+        }
+    }
+}
+
+/// TLS settings applied on top of the connection URL
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct SslOptions {
+    /// CA bundle (PEM) used to verify the server certificate
+    #[serde(default)]
+    pub ca: Option<PathBuf>,
+    /// Overrides any `ssl-mode` given in the URL
+    #[serde(default)]
+    pub mode: Option<SslMode>,
+}
+
+impl SslOptions {
+    /// Apply these options to connect options parsed from the URL
+    pub fn apply(&self, mut options: MySqlConnectOptions) -> MySqlConnectOptions {
+        if let Some(ca) = &self.ca {
+            options = options.ssl_ca(ca);
+        }
+        if let Some(mode) = self.mode {
+            options = options.ssl_mode(mode.into());
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ssl_mode() {
+        assert_eq!("verify_ca".parse(), Ok(SslMode::VerifyCa));
+        assert_eq!("VERIFY-CA".parse(), Ok(SslMode::VerifyCa));
+        assert_eq!("required".parse(), Ok(SslMode::Required));
+        assert!("verify_identity".parse::<SslMode>().is_err());
+    }
+}
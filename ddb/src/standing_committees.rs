@@ -1,22 +1,38 @@
-use crate::{Error, Result};
-use futures::TryFutureExt;
-use sqlx::{MySql, MySqlPool};
+use crate::{Db, Result};
+use sqlx::MySql;
 
-pub async fn all(pool: &MySqlPool) -> Result<Vec<StandingCommittee>> {
-    sqlx::query_as::<_, StandingCommittee>(FETCH_STANDING_COMMITTEES_QUERY)
-        .fetch_all(pool)
-        .map_err(Error::from)
-        .await
+pub async fn all(db: &Db) -> Result<Vec<StandingCommittee>> {
+    db.fetch("standing committees", async || {
+        sqlx::query_as::<_, StandingCommittee>(FETCH_STANDING_COMMITTEES_QUERY)
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
 }
 
-pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<StandingCommittee>> {
-    fetch_standing_committees_query()
-        .push(" WHERE nd.nid = ")
-        .push_bind(uid)
-        .build_query_as::<StandingCommittee>()
-        .fetch_optional(pool)
-        .map_err(Error::from)
+/// Number of standing committees [`all`] would return, without fetching them
+pub async fn count(db: &Db) -> Result<u64> {
+    db.fetch("standing committees", async || {
+        sqlx::query_scalar(
+            "SELECT CAST(COUNT(*) AS UNSIGNED) FROM node_field_data nd \
+             WHERE nd.type = 'ssp_standing_committees'",
+        )
+        .fetch_one(db.pool())
         .await
+    })
+    .await
+}
+
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<StandingCommittee>> {
+    db.fetch("standing committees", async || {
+        fetch_standing_committees_query()
+            .push(" WHERE nd.nid = ")
+            .push_bind(uid)
+            .build_query_as::<StandingCommittee>()
+            .fetch_optional(db.pool())
+            .await
+    })
+    .await
 }
 
 const FETCH_STANDING_COMMITTEES_QUERY: &str = r#"
@@ -0,0 +1,46 @@
+//! Taxonomy vocabularies as flat term lists.
+//!
+//! Fields such as `field_race` or an event's category store only a term ID;
+//! exporting the vocabulary once lets the new site resolve them itself.
+
+use crate::{Db, Result};
+
+/// A taxonomy term
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct Term {
+    /// Drupal taxonomy term ID
+    pub tid: u64,
+    pub name: String,
+    /// Parent term ID, `None` for top-level terms. Only the first parent of
+    /// a term with several is kept.
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub parent: Option<u64>,
+    pub weight: i32,
+}
+
+/// Fetch every term of a vocabulary by machine name (e.g. `ssp_race`),
+/// ordered by weight and name as Drupal lists them. An unknown vocabulary
+/// returns no terms.
+pub async fn vocabulary(db: &Db, machine_name: &str) -> Result<Vec<Term>> {
+    db.fetch("taxonomy terms", async || {
+        sqlx::query_as::<_, Term>(
+            r#"
+        SELECT
+            t.tid,
+            t.name,
+            NULLIF(p.parent_target_id, 0) AS parent,
+            t.weight
+        FROM taxonomy_term_field_data t
+        LEFT JOIN taxonomy_term__parent p
+            ON p.entity_id = t.tid AND p.delta = 0 AND p.deleted = 0
+        WHERE t.vid = ?
+          AND t.default_langcode = 1
+        ORDER BY t.weight, t.name, t.tid
+        "#,
+        )
+        .bind(machine_name)
+        .fetch_all(db.pool())
+        .await
+    })
+    .await
+}
@@ -0,0 +1,127 @@
+//! Timing of bulk export queries as `tracing` spans.
+//!
+//! With the `tracing` feature, every query run through
+//! [`crate::Db::fetch`] is wrapped in a `query` span carrying the `entity`
+//! name, the number of `rows` returned and `elapsed_ms`, retries and their
+//! backoff included, so a subscriber can chart which exports are getting
+//! slower. Without the feature the query is simply awaited.
+//!
+//! Streamed queries ([`crate::Db::stream`]) get the same span, plus
+//! `first_row_ms`: how long MySQL took to send the first row, which is what a
+//! caller writing rows as they arrive waits for.
+//!
+//! This is separate from the `log` warning for slow statements set up in
+//! [`crate::connect_limited`], which keeps working either way.
+
+use crate::Result;
+
+/// Rows in a query result
+pub(crate) trait RowCount {
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    fn row_count(&self) -> usize;
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> RowCount for Option<T> {
+    fn row_count(&self) -> usize {
+        usize::from(self.is_some())
+    }
+}
+
+/// A `fetch_one` scalar, e.g. a count
+macro_rules! single_row {
+    ($($scalar:ty),*) => {
+        $(impl RowCount for $scalar {
+            fn row_count(&self) -> usize {
+                1
+            }
+        })*
+    };
+}
+
+single_row!(i64, u64);
+
+/// Await `query` inside a `query` span for `entity`
+#[cfg(feature = "tracing")]
+pub(crate) async fn timed<T: RowCount>(
+    entity: &'static str,
+    query: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    use tracing::{Instrument, field::Empty};
+
+    let span = tracing::info_span!("query", entity, rows = Empty, elapsed_ms = Empty);
+    let started = std::time::Instant::now();
+    let result = query.instrument(span.clone()).await;
+    span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+    match &result {
+        Ok(rows) => {
+            span.record("rows", rows.row_count());
+            tracing::debug!(parent: &span, "{entity} query finished");
+        }
+        Err(err) => tracing::debug!(parent: &span, "{entity} query failed: {err}"),
+    }
+    result
+}
+
+/// Await `query` inside a `query` span for `entity`
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn timed<T: RowCount>(
+    _entity: &'static str,
+    query: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    query.await
+}
+
+/// Pass `rows` through inside a `query` span for `entity`, recording when
+/// the first row arrived and, once the stream ends, how many rows it had
+#[cfg(feature = "tracing")]
+pub(crate) fn timed_stream<'a, T: Send + 'a>(
+    entity: &'static str,
+    rows: crate::Stream<'a, T>,
+) -> crate::Stream<'a, T> {
+    use futures::StreamExt;
+    use tracing::{Instrument, field::Empty};
+
+    let span = tracing::info_span!(
+        "query",
+        entity,
+        rows = Empty,
+        first_row_ms = Empty,
+        elapsed_ms = Empty
+    );
+    let started = std::time::Instant::now();
+    futures::stream::unfold((rows, span, 0), move |(mut rows, span, count)| async move {
+        match rows.next().instrument(span.clone()).await {
+            Some(row) => {
+                if count == 0 {
+                    span.record("first_row_ms", started.elapsed().as_millis() as u64);
+                }
+                if let Err(err) = &row {
+                    tracing::debug!(parent: &span, "{entity} query failed: {err}");
+                }
+                Some((row, (rows, span, count + 1)))
+            }
+            None => {
+                span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+                span.record("rows", count);
+                tracing::debug!(parent: &span, "{entity} query finished");
+                None
+            }
+        }
+    })
+    .boxed()
+}
+
+/// Pass `rows` through inside a `query` span for `entity`
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn timed_stream<'a, T>(
+    _entity: &'static str,
+    rows: crate::Stream<'a, T>,
+) -> crate::Stream<'a, T> {
+    rows
+}
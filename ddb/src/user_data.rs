@@ -0,0 +1,66 @@
+//! Everything stored about one user, for data subject access requests.
+//!
+//! Collects the records the other modules export in bulk into a single
+//! document, so a GDPR/CCPA request can be answered with one export instead of
+//! assembling the pieces by hand. The password hash is never included.
+
+use crate::{
+    Db, Result, addresses,
+    addresses::Address,
+    airstreams,
+    airstreams::Airstream,
+    brns,
+    brns::Brn,
+    members,
+    members::{Member, MembershipPeriod},
+    rallies,
+    rallies::RallyRegistration,
+    roles, users,
+    users::User,
+};
+
+/// A user's complete record
+#[derive(Debug, serde::Serialize)]
+pub struct UserData {
+    pub profile: User,
+    /// Avatar file URI (public://...)
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub avatar: Option<String>,
+    /// Drupal role machine names
+    pub roles: Vec<String>,
+    pub addresses: Vec<Address>,
+    /// Current membership with home club; `None` for non-members
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub membership: Option<Member>,
+    /// Every club membership, current and past
+    pub clubs: Vec<MembershipPeriod>,
+    pub brns: Vec<Brn>,
+    pub airstreams: Vec<Airstream>,
+    pub rally_registrations: Vec<RallyRegistration>,
+}
+
+/// Collect everything about the user `uid`, or `None` if there is no such user
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<UserData>> {
+    let Some(profile) = users::by_uid(db, uid).await? else {
+        return Ok(None);
+    };
+    Ok(Some(UserData {
+        profile,
+        avatar: users::avatar(db, uid).await?.map(|avatar| avatar.uri),
+        roles: roles::by_user(db, uid).await?,
+        addresses: addresses::by_user_id(db, uid).await?,
+        membership: members::by_uid(db, uid).await?,
+        clubs: members::history_for_user(db, uid).await?,
+        brns: brns::by_user(db, uid).await?,
+        airstreams: airstreams::by_user_id(db, uid).await?,
+        rally_registrations: rallies::registrations_by_user(db, uid).await?,
+    }))
+}
+
+/// Collect everything about the user with this email address
+pub async fn by_email(db: &Db, email: &str) -> Result<Option<UserData>> {
+    match users::by_email(db, email).await? {
+        Some(user) => by_uid(db, user.uid).await,
+        None => Ok(None),
+    }
+}
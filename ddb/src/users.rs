@@ -1,5 +1,9 @@
-use crate::Result;
-use sqlx::{MySqlPool, mysql::MySql};
+use crate::{
+    Db, Result, Shard,
+    progress::{self, Progress},
+};
+use sqlx::mysql::MySql;
+use std::collections::{HashMap, HashSet};
 
 /// Drupal user data.
 ///
@@ -10,51 +14,122 @@ use sqlx::{MySqlPool, mysql::MySql};
 /// - `members.rs`: `FETCH_ALL_MEMBERS_QUERY` - uses NULL placeholders
 /// - `members.rs`: `FETCH_CLUB_MEMBERS_QUERY` - uses NULL placeholders
 /// - `members.rs`: `impl From<PartnerUser> for Option<User>` - manual construction
-#[derive(Debug, sqlx::FromRow, serde::Serialize, Clone)]
+///
+/// Each of the SQL queries has a test checking that it selects every column
+/// in `User::columns()` (test-only).
+#[derive(Debug, sqlx::FromRow, serde::Serialize, Clone, PartialEq)]
 pub struct User {
     pub uid: u64,
     pub email: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub first_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub last_name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub birthday: Option<chrono::NaiveDate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub last_login: Option<chrono::NaiveDate>,
     /// Drupal PHPass hash (e.g., "$S$E..."). Excluded from serialization.
     #[serde(skip_serializing)]
     pub pass: Option<String>,
     // Communication preferences
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub gender: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub race_tid: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Raw `field_communication_preferences` value; see [`User::comm_pref`]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub communication_preference: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub blue_beret_mail: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub publish_info: Option<bool>,
     // Accessibility
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub special_needs: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub ada_parking: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub member_notes: Option<String>,
     // Background
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub military_status: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
     pub first_responder_status: Option<String>,
     /// Account status: true = active (can log in), false = blocked
     pub active: bool,
+    /// Alternate contact addresses from `field_secondary_email`, in the order
+    /// entered. Only filled in by the full user exports ([`all`],
+    /// [`all_in_shard`], [`stream`]); not selected by any query.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "crate::ser::skip_empty")]
+    pub secondary_emails: Vec<String>,
 }
 
-fn fetch_user_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
-    sqlx::QueryBuilder::new(
-        r#"
+#[cfg(test)]
+impl User {
+    /// Minimal active user for unit tests; set other fields as needed
+    pub(crate) fn test(uid: u64, email: &str) -> Self {
+        Self {
+            uid,
+            email: email.to_string(),
+            first_name: None,
+            last_name: None,
+            birthday: None,
+            last_login: None,
+            pass: None,
+            gender: None,
+            race_tid: None,
+            communication_preference: None,
+            blue_beret_mail: None,
+            publish_info: None,
+            special_needs: None,
+            ada_parking: None,
+            member_notes: None,
+            military_status: None,
+            first_responder_status: None,
+            active: true,
+            secondary_emails: vec![],
+        }
+    }
+
+    /// Every field, as the column name the queries must select.
+    ///
+    /// The destructuring has no `..`, so a new field fails to compile here
+    /// until it's listed, or marked as filled in after the query.
+    pub(crate) fn columns() -> Vec<&'static str> {
+        macro_rules! columns {
+            ($($field:ident),* ; skipped: $($skipped:ident),*) => {{
+                let User { $($field: _,)* $($skipped: _),* } = User::test(0, "");
+                vec![$(stringify!($field)),*]
+            }};
+        }
+        columns!(
+            uid,
+            email,
+            first_name,
+            last_name,
+            birthday,
+            last_login,
+            pass,
+            gender,
+            race_tid,
+            communication_preference,
+            blue_beret_mail,
+            publish_info,
+            special_needs,
+            ada_parking,
+            member_notes,
+            military_status,
+            first_responder_status,
+            active;
+            skipped: secondary_emails
+        )
+    }
+}
+
+/// Users query ending in `AND`, for the caller to append a condition
+const FETCH_USER_QUERY: &str = r#"
             SELECT DISTINCT
                 users_field_data.uid AS uid,
                 users_field_data.mail as email,
@@ -92,45 +167,633 @@ fn fetch_user_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
             WHERE
                 users_field_data.mail IS NOT NULL
                 AND
-            "#,
-    )
+            "#;
+
+fn fetch_user_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
+    sqlx::QueryBuilder::new(FETCH_USER_QUERY)
 }
 
-pub async fn by_uid(pool: &MySqlPool, uid: u64) -> Result<Option<User>> {
-    let user = fetch_user_query()
-        .push("users_field_data.uid = ")
-        .push_bind(uid)
-        .build_query_as::<User>()
-        .fetch_optional(pool)
+pub async fn by_uid(db: &Db, uid: u64) -> Result<Option<User>> {
+    let user = db
+        .fetch("users", async || {
+            fetch_user_query()
+                .push("users_field_data.uid = ")
+                .push_bind(uid)
+                .build_query_as::<User>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(user)
 }
 
-pub async fn by_email(pool: &MySqlPool, email: &str) -> Result<Option<User>> {
-    let user = fetch_user_query()
-        .push("users_field_data.mail = ")
-        .push_bind(email)
-        .build_query_as::<User>()
-        .fetch_optional(pool)
+/// Fetch several users, one query per [`Db::in_chunk_size`] uids
+pub async fn by_uids(db: &Db, uids: &[u64]) -> Result<Vec<User>> {
+    crate::chunked::chunked_in(uids, db.in_chunk_size(), async |chunk| {
+        db.fetch("users", async || {
+            let mut builder = fetch_user_query();
+            let mut separated = builder.push("users_field_data.uid IN (").separated(", ");
+            for uid in chunk {
+                separated.push_bind(*uid);
+            }
+            separated.push_unseparated(")");
+            builder.build_query_as::<User>().fetch_all(db.pool()).await
+        })
+        .await
+    })
+    .await
+}
+
+pub async fn by_email(db: &Db, email: &str) -> Result<Option<User>> {
+    let user = db
+        .fetch("users", async || {
+            fetch_user_query()
+                .push("users_field_data.mail = ")
+                .push_bind(email)
+                .build_query_as::<User>()
+                .fetch_optional(db.pool())
+                .await
+        })
         .await?;
 
     Ok(user)
 }
 
+/// Fetch every user holding the BRN `number`, matched after
+/// [`crate::brns::normalize`] against the comma separated `v_brns` list
+pub async fn by_brn(db: &Db, number: &str) -> Result<Vec<User>> {
+    let users = db
+        .fetch("users", async || {
+            fetch_user_query()
+                .push(
+                    "users_field_data.uid IN (SELECT user_id FROM v_brns \
+             WHERE FIND_IN_SET(",
+                )
+                .push_bind(crate::brns::normalize(number))
+                .push(", REPLACE(brns_values, ' ', '')) > 0)")
+                .build_query_as::<User>()
+                .fetch_all(db.pool())
+                .await
+        })
+        .await?;
+
+    Ok(users)
+}
+
 /// Fetch all users with valid email addresses
-pub async fn all(pool: &MySqlPool) -> Result<Vec<User>> {
-    use futures::TryFutureExt;
-    fetch_user_query()
-        .push("users_field_data.mail != ''")
-        .build_query_as::<User>()
-        .fetch_all(pool)
-        .map_err(Into::into)
+pub async fn all(db: &Db) -> Result<Vec<User>> {
+    all_with_progress(db, None).await
+}
+
+/// [`all`], reporting the rows received to `progress` as they stream in
+pub async fn all_with_progress(db: &Db, progress: Progress<'_>) -> Result<Vec<User>> {
+    let mut secondary = secondary_emails(db).await?;
+    let users = db
+        .fetch("users", async || {
+            let mut query = fetch_user_query();
+            query.push("users_field_data.mail != ''");
+            progress::collect(
+                "users",
+                query.build_query_as::<User>().fetch(db.pool()),
+                progress,
+            )
+            .await
+        })
+        .await?;
+    Ok(with_secondary_emails(users, &mut secondary))
+}
+
+/// Number of users [`all`] (or [`all_in_shard`] with `shard`) would return,
+/// counted on `users_field_data` alone without the export's field joins
+pub async fn count(db: &Db, shard: Option<Shard>) -> Result<u64> {
+    db.fetch("users", async || {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT CAST(COUNT(*) AS UNSIGNED) FROM users_field_data \
+             WHERE users_field_data.mail IS NOT NULL AND users_field_data.mail != ''",
+        );
+        if let Some(shard) = shard {
+            shard.push_filter(&mut query, "users_field_data.uid");
+        }
+        query.build_query_scalar().fetch_one(db.pool()).await
+    })
+    .await
+}
+
+/// [`all`] as a stream of users ordered by uid, so a caller can start
+/// writing before the whole result set has arrived.
+///
+/// Unlike [`all`], a failed stream is not retried: rows already handed out
+/// can't be taken back.
+pub fn stream(db: &Db) -> crate::Stream<'_, User> {
+    use futures::{StreamExt, TryStreamExt};
+
+    futures::stream::once(secondary_emails(db))
+        .map_ok(move |mut secondary| {
+            let mut distinct = Distinct::default();
+            db.stream(
+                "users",
+                sqlx::query_as::<_, User>(&STREAM_USERS_QUERY).fetch(db.pool()),
+            )
+            .try_filter(move |user| std::future::ready(distinct.is_new(user)))
+            .map_ok(move |mut user| {
+                user.secondary_emails = secondary.remove(&user.uid).unwrap_or_default();
+                user
+            })
+        })
+        .try_flatten()
+        .boxed()
+}
+
+/// The [`all`] query for [`stream`], without `DISTINCT` and ordered by uid.
+///
+/// MySQL has to build the whole result of a `SELECT DISTINCT` over these
+/// joins in a temporary table before it can send the first row. Read in
+/// primary key order instead, rows go out as the join produces them, and the
+/// duplicates `DISTINCT` would have removed are dropped by [`Distinct`].
+static STREAM_USERS_QUERY: std::sync::LazyLock<String> = std::sync::LazyLock::new(|| {
+    format!(
+        "{} users_field_data.mail != '' ORDER BY users_field_data.uid",
+        FETCH_USER_QUERY.replacen("SELECT DISTINCT", "SELECT", 1)
+    )
+});
+
+/// Drops repeated rows from users ordered by uid, as `SELECT DISTINCT` would
+#[derive(Default)]
+struct Distinct {
+    /// Rows already passed for the current uid
+    seen: Vec<User>,
+}
+
+impl Distinct {
+    fn is_new(&mut self, user: &User) -> bool {
+        if self.seen.first().is_some_and(|seen| seen.uid != user.uid) {
+            self.seen.clear();
+        }
+        if self.seen.contains(user) {
+            return false;
+        }
+        self.seen.push(user.clone());
+        true
+    }
+}
+
+/// [`all_with_progress`] limited to the users in `shard`
+pub async fn all_in_shard(db: &Db, shard: Shard, progress: Progress<'_>) -> Result<Vec<User>> {
+    let mut secondary = secondary_emails(db).await?;
+    let users = db
+        .fetch("users", async || {
+            let mut query = fetch_user_query();
+            query.push("users_field_data.mail != ''");
+            shard.push_filter(&mut query, "users_field_data.uid");
+            progress::collect(
+                "users",
+                query.build_query_as::<User>().fetch(db.pool()),
+                progress,
+            )
+            .await
+        })
+        .await?;
+    Ok(with_secondary_emails(users, &mut secondary))
+}
+
+/// Whether this site has the `field_secondary_email` user field; sites
+/// without it export no secondary emails
+async fn has_secondary_email_field(db: &Db) -> Result<bool> {
+    let count: i64 = db
+        .fetch("secondary emails", async || {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM information_schema.TABLES \
+         WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = 'user__field_secondary_email'",
+            )
+            .fetch_one(db.pool())
+            .await
+        })
+        .await?;
+    Ok(count > 0)
+}
+
+#[derive(sqlx::FromRow)]
+struct SecondaryEmail {
+    uid: u64,
+    email: String,
+}
+
+/// Non-empty secondary emails by uid, without any that repeat the user's
+/// primary email
+async fn secondary_emails(db: &Db) -> Result<HashMap<u64, Vec<String>>> {
+    if !has_secondary_email_field(db).await? {
+        return Ok(HashMap::new());
+    }
+    let rows: Vec<SecondaryEmail> = db
+        .fetch("secondary emails", async || {
+            sqlx::query_as(
+                r#"
+            SELECT se.entity_id AS uid, TRIM(se.field_secondary_email_value) AS email
+            FROM user__field_secondary_email se
+            JOIN users_field_data u ON u.uid = se.entity_id
+            WHERE se.deleted = 0
+            AND TRIM(se.field_secondary_email_value) != ''
+            AND LOWER(TRIM(se.field_secondary_email_value)) != LOWER(u.mail)
+            ORDER BY se.entity_id, se.delta
+            "#,
+            )
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?;
+    let mut emails: HashMap<u64, Vec<String>> = HashMap::new();
+    for row in rows {
+        emails.entry(row.uid).or_default().push(row.email);
+    }
+    Ok(emails)
+}
+
+fn with_secondary_emails(
+    mut users: Vec<User>,
+    secondary: &mut HashMap<u64, Vec<String>>,
+) -> Vec<User> {
+    for user in &mut users {
+        user.secondary_emails = secondary.remove(&user.uid).unwrap_or_default();
+    }
+    users
+}
+
+/// Fetch users who may receive bulk email.
+///
+/// Inclusion is gated by exactly these fields:
+/// - `users_field_data.status = 1` (account not blocked)
+/// - `users_field_data.mail` is non-empty
+/// - `field_communication_preferences` is unset or anything other than
+///   `none` (compared case-insensitively); `none` is the explicit opt-out
+///
+/// `field_blue_beret_mail` is the postal magazine subscription and does not
+/// affect email consent; `field_publish_info` only controls directory listing.
+pub async fn mailable(db: &Db) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        fetch_user_query()
+            .push(
+                "users_field_data.mail != '' \
+             AND users_field_data.status = 1 \
+             AND (ufcp.field_communication_preferences_value IS NULL \
+                  OR LOWER(TRIM(ufcp.field_communication_preferences_value)) != 'none')",
+            )
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Blocked accounts (`status = 0`), which must not be able to sign in to the
+/// new site. [`all`] includes them too, with `active: false`.
+pub async fn blocked(db: &Db) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        fetch_user_query()
+            .push("users_field_data.status = 0")
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// A deleted Drupal account that other records still point to
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct Tombstone {
+    pub uid: u64,
+    /// Where the uid is still referenced: `membership`, `brn` or
+    /// `rally_registration`
+    #[sqlx(flatten, try_from = "TombstoneSources")]
+    pub referenced_by: Vec<String>,
+}
+
+/// `|` separated reference kinds, selected as `sources`
+#[derive(Debug, sqlx::FromRow)]
+struct TombstoneSources {
+    sources: String,
+}
+
+impl From<TombstoneSources> for Vec<String> {
+    fn from(value: TombstoneSources) -> Self {
+        value.sources.split('|').map(str::to_string).collect()
+    }
+}
+
+/// Deleted accounts, whose records should be purged rather than migrated.
+///
+/// Drupal has no soft delete: cancelling an account with "delete" removes its
+/// `users_field_data` row (the other cancel methods only block it, see
+/// [`blocked`]). Records the account owned are not always removed with it,
+/// so a deleted account is detected as a uid that memberships, BRNs or rally
+/// registrations still reference but that has no user row. Accounts deleted
+/// without leaving such a reference can't be told apart from uids that never
+/// existed.
+pub async fn tombstones(db: &Db) -> Result<Vec<Tombstone>> {
+    db.fetch("user tombstones", async || {
+        sqlx::query_as::<_, Tombstone>(
+            r#"
+        SELECT
+            refs.uid,
+            GROUP_CONCAT(DISTINCT refs.source ORDER BY refs.source SEPARATOR '|') AS sources
+        FROM (
+            SELECT CAST(p.parent_id AS UNSIGNED) AS uid, 'membership' AS source
+            FROM paragraphs_item_field_data p
+            WHERE p.type IN ('membership', 'ssp_international_membership')
+            UNION ALL
+            SELECT CAST(b.user_id AS UNSIGNED), 'brn'
+            FROM ssp_complete_brn b
+            WHERE b.user_id > 0
+            UNION ALL
+            SELECT fur.field_user_registered_target_id, 'rally_registration'
+            FROM node__field_user_registered fur
+            WHERE fur.deleted = 0
+        ) refs
+        LEFT JOIN users_field_data u ON u.uid = refs.uid
+        WHERE u.uid IS NULL
+          AND refs.uid > 0
+        GROUP BY refs.uid
+        ORDER BY refs.uid
+        "#,
+        )
+        .fetch_all(db.pool())
         .await
+    })
+    .await
+}
+
+/// Users who flagged `special_needs` or `ada_parking`, for allocating
+/// accessible sites. `member_notes` usually carries the specifics.
+pub async fn accessibility_needs(db: &Db) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        fetch_accessibility_query()
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// [`accessibility_needs`] limited to users registered for a rally
+pub async fn accessibility_needs_for_rally(db: &Db, rally_uid: u64) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        fetch_accessibility_query()
+            .push(
+                " AND users_field_data.uid IN ( \
+             SELECT fur.field_user_registered_target_id \
+             FROM node_field_data reg \
+             JOIN node__field_rally fr ON fr.entity_id = reg.nid AND fr.deleted = 0 \
+             JOIN node__field_user_registered fur ON fur.entity_id = reg.nid AND fur.deleted = 0 \
+             WHERE reg.type = 'rally_registration' AND fr.field_rally_target_id = ",
+            )
+            .push_bind(rally_uid)
+            .push(")")
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+fn fetch_accessibility_query<'builder>() -> sqlx::QueryBuilder<'builder, MySql> {
+    let mut query = fetch_user_query();
+    query.push(
+        "users_field_data.mail != '' \
+         AND (ufsm.field_special_member_value = 1 OR ufap.field_ada_parking_value = 1)",
+    );
+    query
+}
+
+/// Users whose last login is before `before`, or who never logged in
+/// (Drupal stores `login = 0` then), longest dormant first. Blocked accounts
+/// are left out unless `include_blocked` is set, as [`blocked`] covers them.
+pub async fn dormant(
+    db: &Db,
+    before: chrono::NaiveDate,
+    include_blocked: bool,
+) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        let mut query = fetch_user_query();
+        query.push("users_field_data.mail != ''");
+        if !include_blocked {
+            query.push(" AND users_field_data.status = 1");
+        }
+        query
+            .push(
+                " AND (COALESCE(users_field_data.login, 0) = 0 \
+                 OR DATE(FROM_UNIXTIME(users_field_data.login)) < ",
+            )
+            .push_bind(before)
+            // Ordered by selected columns, as required with SELECT DISTINCT
+            .push(") ORDER BY last_login, uid")
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// Users with a non-empty `military_status` or `first_responder_status`,
+/// for the rally recognition ceremony. See [`User::recognition_categories`].
+pub async fn recognition(db: &Db) -> Result<Vec<User>> {
+    db.fetch("users", async || {
+        fetch_user_query()
+            .push(
+                "users_field_data.mail != '' \
+             AND users_field_data.status = 1 \
+             AND (TRIM(COALESCE(ufmil.field_military_value, '')) != '' \
+                  OR TRIM(COALESCE(uffr.field_first_responder_value, '')) != '')",
+            )
+            .build_query_as::<User>()
+            .fetch_all(db.pool())
+            .await
+    })
+    .await
+}
+
+/// How a user wants to be contacted (`field_communication_preferences`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommunicationPreference {
+    /// `email`
+    Email,
+    /// `mail`: postal mail
+    Mail,
+    /// `none`: the explicit opt-out from club communication
+    OptedOut,
+    /// Any other stored value, kept as is
+    Unknown(String),
+}
+
+impl CommunicationPreference {
+    /// Whether bulk email may be sent; only an explicit opt-out prevents it,
+    /// matching the [`mailable`] filter
+    pub fn allows_email(&self) -> bool {
+        !matches!(self, Self::OptedOut)
+    }
+}
+
+impl From<&str> for CommunicationPreference {
+    /// Compared case-insensitively, ignoring surrounding whitespace
+    fn from(value: &str) -> Self {
+        match value.trim().to_lowercase().as_str() {
+            "email" | "e-mail" => Self::Email,
+            "mail" => Self::Mail,
+            "none" => Self::OptedOut,
+            _ => Self::Unknown(value.to_string()),
+        }
+    }
+}
+
+impl User {
+    /// Typed [`User::communication_preference`], `None` when unset
+    pub fn comm_pref(&self) -> Option<CommunicationPreference> {
+        self.communication_preference
+            .as_deref()
+            .map(CommunicationPreference::from)
+    }
+
+    /// First and last name, empty when neither is set
+    pub fn full_name(&self) -> String {
+        [&self.first_name, &self.last_name]
+            .into_iter()
+            .flatten()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Profile completeness, 0–100, given whether the user has an address
+    /// and a custom avatar (both stored outside the user row).
+    ///
+    /// See the `*_WEIGHT` constants for how each field counts.
+    pub fn completeness(&self, has_address: bool, has_avatar: bool) -> u8 {
+        self.row_completeness()
+            + if has_address { ADDRESS_WEIGHT } else { 0 }
+            + if has_avatar { AVATAR_WEIGHT } else { 0 }
+    }
+
+    /// The part of [`User::completeness`] scored from the user row alone, at
+    /// most 60
+    pub fn row_completeness(&self) -> u8 {
+        let filled =
+            |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        [
+            (filled(&self.first_name), FIRST_NAME_WEIGHT),
+            (filled(&self.last_name), LAST_NAME_WEIGHT),
+            (self.birthday.is_some(), BIRTHDAY_WEIGHT),
+            (filled(&self.gender), GENDER_WEIGHT),
+        ]
+        .into_iter()
+        .filter_map(|(present, weight)| present.then_some(weight))
+        .sum()
+    }
+}
+
+// Weights of the profile completeness score, summing to 100. Contact details
+// (name, address) count most since outreach depends on them.
+pub const FIRST_NAME_WEIGHT: u8 = 15;
+pub const LAST_NAME_WEIGHT: u8 = 15;
+pub const BIRTHDAY_WEIGHT: u8 = 20;
+pub const GENDER_WEIGHT: u8 = 10;
+/// At least one address paragraph, whatever its contents
+pub const ADDRESS_WEIGHT: u8 = 25;
+/// A custom avatar, as in [`avatars`]
+pub const AVATAR_WEIGHT: u8 = 15;
+
+/// A user's profile completeness, for "complete your profile" outreach
+#[derive(Debug, serde::Serialize)]
+pub struct ProfileCompleteness {
+    #[serde(flatten)]
+    pub user: User,
+    pub has_address: bool,
+    pub has_avatar: bool,
+    /// [`User::completeness`]
+    pub completeness: u8,
+}
+
+/// Active users whose profile completeness is below `below` (0–100), least
+/// complete first
+pub async fn incomplete_profiles(db: &Db, below: u8) -> Result<Vec<ProfileCompleteness>> {
+    let users = all(db).await?;
+    let address_uids: HashSet<u64> = db
+        .fetch("addresses", async || {
+            sqlx::query_scalar(
+                "SELECT DISTINCT entity_id FROM user__field_address WHERE deleted = 0",
+            )
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?
+        .into_iter()
+        .collect();
+    let avatar_uids: HashSet<u64> = avatars(db)
+        .await?
+        .into_iter()
+        .map(|avatar| avatar.uid)
+        .collect();
+    Ok(incomplete_profiles_from(
+        users,
+        &address_uids,
+        &avatar_uids,
+        below,
+    ))
+}
+
+fn incomplete_profiles_from(
+    users: Vec<User>,
+    address_uids: &HashSet<u64>,
+    avatar_uids: &HashSet<u64>,
+    below: u8,
+) -> Vec<ProfileCompleteness> {
+    let mut profiles: Vec<ProfileCompleteness> = users
+        .into_iter()
+        .filter(|user| user.active)
+        .map(|user| {
+            let has_address = address_uids.contains(&user.uid);
+            let has_avatar = avatar_uids.contains(&user.uid);
+            ProfileCompleteness {
+                completeness: user.completeness(has_address, has_avatar),
+                user,
+                has_address,
+                has_avatar,
+            }
+        })
+        .filter(|profile| profile.completeness < below)
+        .collect();
+    profiles.sort_by_key(|profile| (profile.completeness, profile.user.uid));
+    profiles
+}
+
+/// Category a user is recognized under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecognitionCategory {
+    Military,
+    FirstResponder,
+}
+
+impl User {
+    /// Recognition categories from the non-empty status fields
+    pub fn recognition_categories(&self) -> Vec<RecognitionCategory> {
+        let present = |status: &Option<String>| {
+            status
+                .as_deref()
+                .is_some_and(|status| !status.trim().is_empty())
+        };
+        let mut categories = vec![];
+        if present(&self.military_status) {
+            categories.push(RecognitionCategory::Military);
+        }
+        if present(&self.first_responder_status) {
+            categories.push(RecognitionCategory::FirstResponder);
+        }
+        categories
+    }
 }
 
 /// User avatar from Drupal file_managed table.
-#[derive(Debug, sqlx::FromRow)]
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
 pub struct UserAvatar {
     /// Drupal user ID
     pub uid: u64,
@@ -138,24 +801,92 @@ pub struct UserAvatar {
     pub uri: String,
 }
 
+/// Custom (non-default) public avatar files and their users
+const AVATAR_JOINS: &str = r#"
+    FROM users_field_data u
+    JOIN user__user_picture p ON u.uid = p.entity_id
+    JOIN file_managed f ON p.user_picture_target_id = f.fid
+    WHERE f.uri NOT LIKE '%default%'
+      AND f.uri LIKE 'public://%'
+"#;
+
 /// Fetch all users with custom avatars (excluding default images).
-pub async fn avatars(pool: &MySqlPool) -> Result<Vec<UserAvatar>> {
-    let avatars = sqlx::query_as::<_, UserAvatar>(
-        r#"
-        SELECT u.uid, CAST(f.uri AS CHAR) AS uri
-        FROM users_field_data u
-        JOIN user__user_picture p ON u.uid = p.entity_id
-        JOIN file_managed f ON p.user_picture_target_id = f.fid
-        WHERE f.uri NOT LIKE '%default%'
-          AND f.uri LIKE 'public://%'
-        "#,
-    )
-    .fetch_all(pool)
-    .await?;
+pub async fn avatars(db: &Db) -> Result<Vec<UserAvatar>> {
+    let avatars = db
+        .fetch("avatars", async || {
+            sqlx::query_as::<_, UserAvatar>(&format!(
+                "SELECT u.uid, CAST(f.uri AS CHAR) AS uri {AVATAR_JOINS}"
+            ))
+            .fetch_all(db.pool())
+            .await
+        })
+        .await?;
 
     Ok(avatars)
 }
 
+/// Fetch a user's custom avatar, if any
+pub async fn avatar(db: &Db, uid: u64) -> Result<Option<UserAvatar>> {
+    db.fetch("avatars", async || {
+        sqlx::QueryBuilder::new(format!(
+            "SELECT u.uid, CAST(f.uri AS CHAR) AS uri {AVATAR_JOINS}"
+        ))
+        .push(" AND u.uid = ")
+        .push_bind(uid)
+        .build_query_as::<UserAvatar>()
+        .fetch_optional(db.pool())
+        .await
+    })
+    .await
+}
+
+/// User avatar with the owner's email, for naming downloaded files
+#[derive(Debug, sqlx::FromRow, serde::Serialize)]
+pub struct AvatarWithUser {
+    pub uid: u64,
+    #[serde(skip_serializing_if = "crate::ser::skip_none")]
+    pub email: Option<String>,
+    /// File URI (e.g., "public://pictures/2020-02/Marc164.png")
+    pub uri: String,
+}
+
+impl AvatarWithUser {
+    /// File name for the downloaded avatar: the lowercased email with the
+    /// file's extension, e.g. `jane@example.com.png`. Characters other than
+    /// ASCII letters, digits and `@._+-` become `-`. Falls back to the uid
+    /// when the user has no email.
+    pub fn file_name(&self) -> String {
+        let stem = match self.email.as_deref().map(str::trim) {
+            Some(email) if !email.is_empty() => email
+                .chars()
+                .map(|c| match c.to_ascii_lowercase() {
+                    c @ ('a'..='z' | '0'..='9' | '@' | '.' | '_' | '+' | '-') => c,
+                    _ => '-',
+                })
+                .collect(),
+            _ => self.uid.to_string(),
+        };
+        let file = self.uri.rsplit('/').next().unwrap_or_default();
+        match file.rsplit_once('.') {
+            Some((_, extension)) if !extension.is_empty() => {
+                format!("{stem}.{}", extension.to_ascii_lowercase())
+            }
+            _ => stem,
+        }
+    }
+}
+
+/// Fetch all custom avatars (default images excluded, as in [`avatars`])
+/// with their user's email
+pub async fn avatars_with_email(db: &Db) -> Result<Vec<AvatarWithUser>> {
+    db.fetch("avatars", async || {
+        sqlx::query_as::<_, AvatarWithUser>(&format!(
+        "SELECT u.uid, u.mail AS email, CAST(f.uri AS CHAR) AS uri {AVATAR_JOINS} ORDER BY u.uid"
+    )).fetch_all(db.pool()).await
+    })
+    .await
+}
+
 /// Convert Drupal file URI to downloadable path.
 /// e.g., "public://pictures/2020-02/Marc164.png" -> "/sites/default/files/pictures/2020-02/Marc164.png"
 pub fn avatar_uri_to_path(uri: &str) -> Option<String> {
@@ -179,3 +910,147 @@ pub mod db {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::db as app_db;
+
+    #[test]
+    fn test_user_query_selects_every_field() {
+        let missing = crate::sql_audit::missing_aliases(FETCH_USER_QUERY, &User::columns());
+        assert_eq!(missing, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_stream_query_reads_in_uid_order_without_distinct() {
+        assert!(!STREAM_USERS_QUERY.contains("DISTINCT"));
+        assert!(STREAM_USERS_QUERY.ends_with("ORDER BY users_field_data.uid"));
+        let missing = crate::sql_audit::missing_aliases(&STREAM_USERS_QUERY, &User::columns());
+        assert_eq!(missing, Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_distinct_drops_repeated_rows() {
+        let a = User::test(1, "a@example.com");
+        let mut a_renamed = a.clone();
+        a_renamed.first_name = Some("A".to_string());
+        let b = User::test(2, "b@example.com");
+        let mut distinct = Distinct::default();
+        let kept: Vec<bool> = [&a, &a_renamed, &a, &b, &b, &a]
+            .into_iter()
+            .map(|user| distinct.is_new(user))
+            .collect();
+        assert_eq!(kept, [true, true, false, true, false, true]);
+    }
+
+    #[test]
+    fn test_into_app_user() {
+        let mut user = User::test(1234, "Jane.Doe@Example.com");
+        user.first_name = Some("Jane".to_string());
+        user.last_name = Some("Doe".to_string());
+
+        let app_user: app_db::user::User = user.into();
+        assert_eq!(app_user.uid, 1234);
+        assert_eq!(app_user.email, "Jane.Doe@Example.com");
+        assert_eq!(app_user.first_name.as_deref(), Some("Jane"));
+        assert_eq!(app_user.last_name.as_deref(), Some("Doe"));
+        assert_eq!(
+            app_user.id,
+            app_db::user::id_for_email("Jane.Doe@Example.com")
+        );
+    }
+
+    #[test]
+    fn test_into_app_user_id_is_deterministic() {
+        let id = |email: &str| app_db::user::User::from(User::test(1, email)).id;
+        assert_eq!(id("jane.doe@example.com"), id(" Jane.Doe@EXAMPLE.com "));
+        assert_ne!(id("jane.doe@example.com"), id("john.doe@example.com"));
+    }
+
+    #[test]
+    fn test_recognition_categories() {
+        let mut user = User::test(1, "a@example.com");
+        assert!(user.recognition_categories().is_empty());
+
+        user.military_status = Some("Army".to_string());
+        user.first_responder_status = Some("  ".to_string());
+        assert_eq!(
+            user.recognition_categories(),
+            vec![RecognitionCategory::Military]
+        );
+
+        user.first_responder_status = Some("Firefighter".to_string());
+        assert_eq!(
+            user.recognition_categories(),
+            vec![
+                RecognitionCategory::Military,
+                RecognitionCategory::FirstResponder
+            ]
+        );
+    }
+
+    #[test]
+    fn test_profile_completeness() {
+        let mut user = User::test(1, "pat@example.com");
+        assert_eq!(user.row_completeness(), 0);
+        assert_eq!(user.completeness(true, true), 40);
+
+        user.first_name = Some("Pat".to_string());
+        user.last_name = Some(" ".to_string());
+        user.birthday = chrono::NaiveDate::from_ymd_opt(1960, 1, 1);
+        user.gender = Some("Female".to_string());
+        assert_eq!(user.row_completeness(), 45);
+        user.last_name = Some("Smith".to_string());
+        assert_eq!(user.row_completeness(), 60);
+        assert_eq!(user.completeness(true, true), 100);
+
+        let mut blocked = User::test(2, "blocked@example.com");
+        blocked.active = false;
+        let profiles = incomplete_profiles_from(
+            vec![user, User::test(3, "new@example.com"), blocked],
+            &HashSet::from([1, 3]),
+            &HashSet::from([1]),
+            100,
+        );
+        let scores: Vec<(u64, u8)> = profiles
+            .iter()
+            .map(|profile| (profile.user.uid, profile.completeness))
+            .collect();
+        assert_eq!(scores, vec![(3, 25)]);
+    }
+
+    #[test]
+    fn test_comm_pref() {
+        let mut user = User::test(1, "pat@example.com");
+        assert_eq!(user.comm_pref(), None);
+
+        user.communication_preference = Some(" Email ".to_string());
+        assert_eq!(user.comm_pref(), Some(CommunicationPreference::Email));
+
+        user.communication_preference = Some("NONE".to_string());
+        let pref = user.comm_pref().unwrap();
+        assert_eq!(pref, CommunicationPreference::OptedOut);
+        assert!(!pref.allows_email());
+
+        assert_eq!(
+            CommunicationPreference::from("text"),
+            CommunicationPreference::Unknown("text".to_string())
+        );
+        assert!(CommunicationPreference::from("text").allows_email());
+    }
+
+    #[test]
+    fn test_avatar_file_name() {
+        let mut avatar = AvatarWithUser {
+            uid: 7,
+            email: Some("Jane Doe@Example.com".to_string()),
+            uri: "public://pictures/2020-02/Marc164.PNG".to_string(),
+        };
+        assert_eq!(avatar.file_name(), "jane-doe@example.com.png");
+
+        avatar.email = Some(" ".to_string());
+        avatar.uri = "public://pictures/avatar".to_string();
+        assert_eq!(avatar.file_name(), "7");
+    }
+}
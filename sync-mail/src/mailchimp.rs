@@ -5,7 +5,7 @@ use mailchimp::{
     RetryPolicy,
     members::{MembersQuery, member_id},
 };
-use sqlx::{Database, Encode, MySqlPool, PgPool, Type, query::QueryAs};
+use sqlx::{Database, Encode, PgPool, Type, query::QueryAs};
 use std::{collections::HashSet, time::Instant};
 
 #[derive(Debug, serde::Serialize)]
@@ -182,7 +182,7 @@ impl Job {
         Ok(mailchimp::client::from_api_key(&self.api_key)?)
     }
 
-    async fn db_members(&self, db: &MySqlPool) -> Result<Vec<ddb::members::Member>> {
+    async fn db_members(&self, db: &ddb::Db) -> Result<Vec<ddb::members::Member>> {
         let db_members = if let Some(club) = self.club {
             ddb::members::by_club(db, club as u64).await?
         } else if let Some(region) = self.region {
@@ -289,7 +289,7 @@ impl Job {
     /// `mc_members` for upsert/hash computation, `db_members` for tag updates.
     async fn prepare_mc_members(
         &self,
-        db: &MySqlPool,
+        db: &ddb::Db,
     ) -> Result<(Vec<ddb::members::Member>, Vec<mailchimp::members::Member>)> {
         let db_members = self.db_members(db).await?;
         let merge_fields = self.merge_fields()?;
@@ -1,7 +1,7 @@
 use crate::{Context, Result};
 use config::{Config, Environment};
 use serde::Deserialize;
-use sqlx::{MySqlPool, PgPool};
+use sqlx::PgPool;
 
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct Settings {
@@ -67,6 +67,9 @@ impl DatabaseSettings {
 pub struct AciDatabaseSettings {
     #[serde(default = "default_ddb_url")]
     pub url: String,
+    /// TLS options (ACI__DDB_SSL_CA, ACI__DDB_SSL_MODE)
+    #[serde(default)]
+    pub ssl: ddb::SslOptions,
 }
 
 fn default_ddb_url() -> String {
@@ -74,7 +77,9 @@ fn default_ddb_url() -> String {
 }
 
 impl AciDatabaseSettings {
-    pub async fn connect(&self) -> Result<MySqlPool> {
-        ddb::connect(&self.url).await.context("opening database")
+    pub async fn connect(&self) -> Result<ddb::Db> {
+        ddb::connect_with_ssl(&self.url, &self.ssl)
+            .await
+            .context("opening database")
     }
 }